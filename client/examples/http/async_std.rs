@@ -1,10 +1,12 @@
-use async_jsonrpc_client::{BatchTransport, HttpClient, HttpClientError, ResponseObj, Transport};
+use async_jsonrpc_client::{BatchTransport, HttpClient, HttpClientError, ResponseObj, SurfBackend, Transport};
 
 #[async_std::main]
 async fn main() -> Result<(), HttpClientError> {
     env_logger::init();
 
-    let client = HttpClient::new("https://rpc.polkadot.io")?;
+    let client = HttpClient::builder()
+        .backend(SurfBackend::new()?)
+        .build("https://rpc.polkadot.io")?;
 
     let response = client.request("system_chain", None).await?;
     log::info!("Response: {}", ResponseObj::Single(response));
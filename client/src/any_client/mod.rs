@@ -0,0 +1,133 @@
+#[cfg(test)]
+mod tests;
+
+use jsonrpc_types::v2::*;
+
+#[cfg(any(feature = "http-async-std", feature = "http-tokio", feature = "http-hyper"))]
+use crate::HttpClient;
+#[cfg(any(feature = "ipc-async-std", feature = "ipc-tokio"))]
+use crate::IpcClient;
+#[cfg(any(feature = "ws-async-std", feature = "ws-tokio"))]
+use crate::WsClient;
+use crate::{
+    error::AnyClientError,
+    transport::{BatchTransport, Transport},
+};
+
+/// A client of any transport, which is chosen by the scheme of the url at runtime.
+///
+/// The supported schemes are `http://` and `https://` for `HttpClient`, `ws://` and `wss://` for
+/// `WsClient`, and `ipc://` (e.g. `ipc:///path/to.sock`) for `IpcClient`, if the corresponding
+/// features are enabled.
+#[derive(Clone)]
+pub enum AnyClient {
+    /// HTTP client.
+    #[cfg(any(feature = "http-async-std", feature = "http-tokio", feature = "http-hyper"))]
+    Http(Box<HttpClient>),
+    /// WebSocket client.
+    #[cfg(any(feature = "ws-async-std", feature = "ws-tokio"))]
+    Ws(WsClient),
+    /// IPC client.
+    #[cfg(any(feature = "ipc-async-std", feature = "ipc-tokio"))]
+    Ipc(IpcClient),
+}
+
+impl AnyClient {
+    /// Creates a new client with the default configuration by the scheme of the `url`.
+    ///
+    /// The WebSocket and IPC clients are connected to the server before returned.
+    pub async fn new(url: impl Into<String>) -> Result<Self, AnyClientError> {
+        let url = url.into();
+        let scheme = url.split_once("://").map(|(scheme, _)| scheme.to_ascii_lowercase());
+        match scheme.as_deref() {
+            #[cfg(any(feature = "http-async-std", feature = "http-tokio", feature = "http-hyper"))]
+            Some("http") | Some("https") => Ok(Self::Http(Box::new(HttpClient::new(url)?))),
+            #[cfg(any(feature = "ws-async-std", feature = "ws-tokio"))]
+            Some("ws") | Some("wss") => Ok(Self::Ws(WsClient::new(url).await?)),
+            #[cfg(any(feature = "ipc-async-std", feature = "ipc-tokio"))]
+            Some("ipc") => Ok(Self::Ipc(IpcClient::new(&url["ipc://".len()..]).await?)),
+            _ => Err(AnyClientError::UnsupportedScheme(url)),
+        }
+    }
+}
+
+/// Dispatches the expression to the client of the variant.
+macro_rules! dispatch {
+    ($any:expr, $client:ident => $expr:expr) => {
+        match $any {
+            #[cfg(any(feature = "http-async-std", feature = "http-tokio", feature = "http-hyper"))]
+            AnyClient::Http($client) => Ok($expr?),
+            #[cfg(any(feature = "ws-async-std", feature = "ws-tokio"))]
+            AnyClient::Ws($client) => Ok($expr?),
+            #[cfg(any(feature = "ipc-async-std", feature = "ipc-tokio"))]
+            AnyClient::Ipc($client) => Ok($expr?),
+        }
+    };
+}
+
+#[async_trait::async_trait]
+impl Transport for AnyClient {
+    type Error = AnyClientError;
+
+    async fn request<M>(&self, method: M, params: Option<Params>) -> Result<Response, Self::Error>
+    where
+        M: Into<String> + Send,
+    {
+        dispatch!(self, client => client.request(method, params).await)
+    }
+}
+
+#[async_trait::async_trait]
+impl BatchTransport for AnyClient {
+    async fn request_batch<I, M>(&self, batch: I) -> Result<BatchResponse, Self::Error>
+    where
+        I: IntoIterator<Item = (M, Option<Params>)> + Send,
+        I::IntoIter: Send,
+        M: Into<String>,
+    {
+        dispatch!(self, client => client.request_batch(batch).await)
+    }
+}
+
+#[cfg(any(
+    feature = "ws-async-std",
+    feature = "ws-tokio",
+    feature = "ipc-async-std",
+    feature = "ipc-tokio"
+))]
+#[async_trait::async_trait]
+impl crate::transport::PubsubTransport for AnyClient {
+    type NotificationStream = crate::duplex::DuplexSubscription<SubscriptionNotification>;
+
+    async fn subscribe<M>(
+        &self,
+        subscribe_method: M,
+        params: Option<Params>,
+    ) -> Result<(Id, Self::NotificationStream), Self::Error>
+    where
+        M: Into<String> + Send,
+    {
+        match self {
+            #[cfg(any(feature = "http-async-std", feature = "http-tokio", feature = "http-hyper"))]
+            AnyClient::Http(_) => Err(AnyClientError::SubscriptionUnsupported),
+            #[cfg(any(feature = "ws-async-std", feature = "ws-tokio"))]
+            AnyClient::Ws(client) => Ok(client.subscribe(subscribe_method, params).await?),
+            #[cfg(any(feature = "ipc-async-std", feature = "ipc-tokio"))]
+            AnyClient::Ipc(client) => Ok(client.subscribe(subscribe_method, params).await?),
+        }
+    }
+
+    async fn unsubscribe<M>(&self, unsubscribe_method: M, subscription_id: Id) -> Result<bool, Self::Error>
+    where
+        M: Into<String> + Send,
+    {
+        match self {
+            #[cfg(any(feature = "http-async-std", feature = "http-tokio", feature = "http-hyper"))]
+            AnyClient::Http(_) => Err(AnyClientError::SubscriptionUnsupported),
+            #[cfg(any(feature = "ws-async-std", feature = "ws-tokio"))]
+            AnyClient::Ws(client) => Ok(client.unsubscribe(unsubscribe_method, subscription_id).await?),
+            #[cfg(any(feature = "ipc-async-std", feature = "ipc-tokio"))]
+            AnyClient::Ipc(client) => Ok(client.unsubscribe(unsubscribe_method, subscription_id).await?),
+        }
+    }
+}
@@ -0,0 +1,40 @@
+use futures::executor::block_on;
+
+use super::*;
+
+#[test]
+fn test_unsupported_scheme() {
+    for url in &["ftp://127.0.0.1:8545", "127.0.0.1:8545"] {
+        match block_on(AnyClient::new(*url)) {
+            Err(AnyClientError::UnsupportedScheme(scheme)) => assert_eq!(scheme, *url),
+            Err(err) => panic!("unexpected error: {}", err),
+            Ok(_) => panic!("unexpected client of {}", url),
+        }
+    }
+}
+
+#[cfg(feature = "http-tokio")]
+#[tokio::test]
+async fn test_dyn_transport() {
+    use crate::{error::DynTransportError, transport::DynTransport};
+
+    let client = AnyClient::new("HTTP://127.0.0.1:1").await.unwrap();
+    assert!(matches!(client, AnyClient::Http(_)));
+
+    let transport: Box<dyn DynTransport> = Box::new(client);
+    let err: DynTransportError = transport.request("foo", None).await.unwrap_err();
+    assert!(matches!(
+        err.downcast_ref::<AnyClientError>(),
+        Some(AnyClientError::Http(_))
+    ));
+}
+
+#[cfg(all(feature = "http-tokio", any(feature = "ws-async-std", feature = "ws-tokio")))]
+#[tokio::test]
+async fn test_http_subscription_unsupported() {
+    use crate::transport::PubsubTransport;
+
+    let client = AnyClient::new("http://127.0.0.1:1").await.unwrap();
+    let result = client.subscribe("foo_subscribe", None).await;
+    assert!(matches!(result, Err(AnyClientError::SubscriptionUnsupported)));
+}
@@ -0,0 +1,28 @@
+mod record;
+mod replay;
+#[cfg(test)]
+mod tests;
+
+use jsonrpc_types::v2::*;
+use serde::{Deserialize, Serialize};
+
+pub use self::{
+    record::{RecordSubscription, RecordTransport},
+    replay::{ReplaySubscription, ReplayTransport},
+};
+
+/// A line of the cassette, which is a JSON-RPC exchange or a subscription notification.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+enum Entry {
+    /// A request (or a batch of requests) and its response.
+    ///
+    /// The request IDs are generated by the recorder, since the IDs used by the inner transport
+    /// are not exposed, so the requests are matched by their methods and parameters only.
+    Exchange { request: RequestObj, response: ResponseObj },
+    /// A subscribe request and the subscription ID responded to it, which the following
+    /// notifications of the subscription belong to.
+    Subscription { request: Request, subscription: Id },
+    /// A notification of the subscription, which is recorded once it's received.
+    Notification(SubscriptionNotification),
+}
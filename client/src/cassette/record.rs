@@ -0,0 +1,193 @@
+use std::{
+    fs::File,
+    io::{self, LineWriter, Write},
+    path::Path,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+};
+
+use futures::stream::{Stream, StreamExt};
+use jsonrpc_types::v2::*;
+
+use crate::{
+    cassette::Entry,
+    error::RecordTransportError,
+    id::{IdGenerator, SequentialIdGenerator},
+    transport::{BatchTransport, PubsubTransport, Transport},
+};
+
+type Cassette = Arc<Mutex<LineWriter<File>>>;
+
+fn write_entry(cassette: &Cassette, entry: &Entry) -> io::Result<()> {
+    let line = serde_json::to_string(entry)?;
+    log::trace!("[record] Write cassette entry: {}", line);
+    let mut cassette = cassette.lock().expect("lock cassette");
+    writeln!(cassette, "{}", line)
+}
+
+/// Transport wrapper that records every request, response and subscription notification passing
+/// through the inner transport into a JSON-lines cassette file, which can be replayed by
+/// `ReplayTransport`.
+pub struct RecordTransport<T> {
+    inner: T,
+    cassette: Cassette,
+    id_generator: Arc<dyn IdGenerator>,
+}
+
+impl<T> RecordTransport<T> {
+    /// Creates a new record transport wrapping the `inner` transport, the cassette file at `path`
+    /// is created, or truncated if it exists.
+    pub fn new(inner: T, path: impl AsRef<Path>) -> io::Result<Self> {
+        let file = File::create(path)?;
+        Ok(Self {
+            inner,
+            cassette: Arc::new(Mutex::new(LineWriter::new(file))),
+            id_generator: Arc::new(SequentialIdGenerator::default()),
+        })
+    }
+
+    /// Returns the inner transport.
+    pub fn inner(&self) -> &T {
+        &self.inner
+    }
+
+    /// Consumes the record transport, returning the inner transport.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+
+    fn record<E>(&self, request: RequestObj, response: ResponseObj) -> Result<(), RecordTransportError<E>> {
+        write_entry(&self.cassette, &Entry::Exchange { request, response })?;
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl<T> Transport for RecordTransport<T>
+where
+    T: Transport + Send + Sync,
+    T::Error: Send,
+{
+    type Error = RecordTransportError<T::Error>;
+
+    async fn request<M>(&self, method: M, params: Option<Params>) -> Result<Response, Self::Error>
+    where
+        M: Into<String> + Send,
+    {
+        let method = method.into();
+        let response = self
+            .inner
+            .request(method.clone(), params.clone())
+            .await
+            .map_err(RecordTransportError::Transport)?;
+        let request = Request::new(method, params, self.id_generator.next_id());
+        self.record(RequestObj::Single(request), ResponseObj::Single(response.clone()))?;
+        Ok(response)
+    }
+}
+
+#[async_trait::async_trait]
+impl<T> BatchTransport for RecordTransport<T>
+where
+    T: BatchTransport + Send + Sync,
+    T::Error: Send,
+{
+    async fn request_batch<I, M>(&self, batch: I) -> Result<BatchResponse, Self::Error>
+    where
+        I: IntoIterator<Item = (M, Option<Params>)> + Send,
+        I::IntoIter: Send,
+        M: Into<String>,
+    {
+        let batch = batch
+            .into_iter()
+            .map(|(method, params)| (method.into(), params))
+            .collect::<Vec<(String, Option<Params>)>>();
+        let responses = self
+            .inner
+            .request_batch(batch.clone())
+            .await
+            .map_err(RecordTransportError::Transport)?;
+        let requests = batch
+            .into_iter()
+            .map(|(method, params)| Request::new(method, params, self.id_generator.next_id()))
+            .collect();
+        self.record(RequestObj::Batch(requests), ResponseObj::Batch(responses.clone()))?;
+        Ok(responses)
+    }
+}
+
+#[async_trait::async_trait]
+impl<T> PubsubTransport for RecordTransport<T>
+where
+    T: PubsubTransport + Send + Sync,
+    T::Error: Send,
+    T::NotificationStream: Send,
+{
+    type NotificationStream = RecordSubscription<T::NotificationStream>;
+
+    async fn subscribe<M>(
+        &self,
+        subscribe_method: M,
+        params: Option<Params>,
+    ) -> Result<(Id, Self::NotificationStream), Self::Error>
+    where
+        M: Into<String> + Send,
+    {
+        let subscribe_method = subscribe_method.into();
+        let (subscription_id, stream) = self
+            .inner
+            .subscribe(subscribe_method.clone(), params.clone())
+            .await
+            .map_err(RecordTransportError::Transport)?;
+        let request = Request::new(subscribe_method, params, self.id_generator.next_id());
+        let entry = Entry::Subscription {
+            request,
+            subscription: subscription_id.clone(),
+        };
+        write_entry(&self.cassette, &entry)?;
+        let stream = RecordSubscription {
+            stream: Box::pin(stream),
+            cassette: self.cassette.clone(),
+        };
+        Ok((subscription_id, stream))
+    }
+
+    async fn unsubscribe<M>(&self, unsubscribe_method: M, subscription_id: Id) -> Result<bool, Self::Error>
+    where
+        M: Into<String> + Send,
+    {
+        let unsubscribe_method = unsubscribe_method.into();
+        let params = Params::Array(vec![serde_json::to_value(&subscription_id)?]);
+        let result = self
+            .inner
+            .unsubscribe(unsubscribe_method.clone(), subscription_id)
+            .await
+            .map_err(RecordTransportError::Transport)?;
+        let id = self.id_generator.next_id();
+        let response = Response::success(Value::Bool(result), id.clone());
+        let request = Request::new(unsubscribe_method, Some(params), id);
+        self.record(RequestObj::Single(request), ResponseObj::Single(response))?;
+        Ok(result)
+    }
+}
+
+/// Active subscription on a record transport, which records the notifications once they're received.
+pub struct RecordSubscription<S> {
+    stream: Pin<Box<S>>,
+    cassette: Cassette,
+}
+
+impl<S: Stream<Item = SubscriptionNotification>> Stream for RecordSubscription<S> {
+    type Item = SubscriptionNotification;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let notification = futures::ready!(self.stream.poll_next_unpin(cx));
+        if let Some(notification) = &notification {
+            if let Err(err) = write_entry(&self.cassette, &Entry::Notification(notification.clone())) {
+                log::error!("[record] Write subscription notification error: {}", err);
+            }
+        }
+        Poll::Ready(notification)
+    }
+}
@@ -0,0 +1,234 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    fs,
+    path::Path,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+};
+
+use futures::stream::Stream;
+use jsonrpc_types::v2::*;
+
+use crate::{
+    cassette::Entry,
+    error::ReplayTransportError,
+    transport::{BatchTransport, PubsubTransport, Transport},
+};
+
+/// The kind of the replayed calls.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+enum CallKind {
+    Single,
+    Batch,
+    Subscribe,
+}
+
+/// The recorded outcome of an exchange.
+#[derive(Clone, Debug)]
+enum Outcome {
+    /// The response of a request or a batch of requests.
+    Response(ResponseObj),
+    /// The subscription ID responded to a subscribe request, and the notifications of the subscription.
+    Subscription(Id, Vec<SubscriptionNotification>),
+}
+
+/// A recorded exchange.
+#[derive(Debug)]
+struct Exchange {
+    request: RequestObj,
+    outcome: Outcome,
+    replayed: bool,
+}
+
+impl Exchange {
+    /// Returns `true` if the recorded request has the same kind, methods and parameters as `calls`.
+    fn matches(&self, calls: &[(String, Option<Params>)], kind: CallKind) -> bool {
+        let requests = match (&self.request, &self.outcome, kind) {
+            (RequestObj::Single(request), Outcome::Response(_), CallKind::Single)
+            | (RequestObj::Single(request), Outcome::Subscription(..), CallKind::Subscribe) => {
+                std::slice::from_ref(request)
+            }
+            (RequestObj::Batch(requests), Outcome::Response(_), CallKind::Batch) => requests.as_slice(),
+            _ => return false,
+        };
+        requests.len() == calls.len()
+            && requests
+                .iter()
+                .zip(calls)
+                .all(|(request, (method, params))| &request.method == method && &request.params == params)
+    }
+}
+
+/// Offline transport that serves the responses and subscription notifications recorded in the
+/// cassette by `RecordTransport`.
+///
+/// Every request is matched with the first recorded request which has the same method and
+/// parameters and has not been replayed yet, so the same requests are replayed in the recorded
+/// order. The request without any matched record fails with the `UnrecordedRequest` error.
+#[derive(Clone, Debug)]
+pub struct ReplayTransport {
+    exchanges: Arc<Mutex<Vec<Exchange>>>,
+}
+
+impl ReplayTransport {
+    /// Creates a new replay transport from the cassette file at `path`.
+    pub fn new(path: impl AsRef<Path>) -> Result<Self, ReplayTransportError> {
+        let cassette = fs::read_to_string(path)?;
+        Self::from_cassette(&cassette)
+    }
+
+    /// Creates a new replay transport from the content of cassette.
+    pub fn from_cassette(cassette: &str) -> Result<Self, ReplayTransportError> {
+        let mut exchanges = Vec::<Exchange>::new();
+        // the latest subscribe exchange of the subscription id
+        let mut subscriptions = HashMap::new();
+        for line in cassette.lines().filter(|line| !line.trim().is_empty()) {
+            match serde_json::from_str::<Entry>(line)? {
+                Entry::Exchange { request, response } => exchanges.push(Exchange {
+                    request,
+                    outcome: Outcome::Response(response),
+                    replayed: false,
+                }),
+                Entry::Subscription { request, subscription } => {
+                    subscriptions.insert(subscription.clone(), exchanges.len());
+                    exchanges.push(Exchange {
+                        request: RequestObj::Single(request),
+                        outcome: Outcome::Subscription(subscription, Vec::new()),
+                        replayed: false,
+                    });
+                }
+                Entry::Notification(notification) => {
+                    let outcome = subscriptions
+                        .get(&notification.params.subscription)
+                        .map(|&index| &mut exchanges[index].outcome);
+                    match outcome {
+                        Some(Outcome::Subscription(_, notifications)) => notifications.push(notification),
+                        _ => log::warn!("[replay] Ignore notification of unknown subscription: {}", notification),
+                    }
+                }
+            }
+        }
+        Ok(Self {
+            exchanges: Arc::new(Mutex::new(exchanges)),
+        })
+    }
+
+    /// Returns `true` if all recorded exchanges have been replayed.
+    pub fn is_finished(&self) -> bool {
+        self.exchanges
+            .lock()
+            .expect("lock exchanges")
+            .iter()
+            .all(|exchange| exchange.replayed)
+    }
+
+    /// Replays the recorded outcome of the calls.
+    fn replay(&self, calls: Vec<(String, Option<Params>)>, kind: CallKind) -> Result<Outcome, ReplayTransportError> {
+        let mut exchanges = self.exchanges.lock().expect("lock exchanges");
+        let exchange = exchanges
+            .iter_mut()
+            .find(|exchange| !exchange.replayed && exchange.matches(&calls, kind));
+        match exchange {
+            Some(exchange) => {
+                log::debug!("[replay] Replay request: {}", exchange.request);
+                exchange.replayed = true;
+                Ok(exchange.outcome.clone())
+            }
+            None => Err(ReplayTransportError::UnrecordedRequest(format!("{:?}", calls))),
+        }
+    }
+
+    fn replay_single(&self, method: String, params: Option<Params>) -> Result<Response, ReplayTransportError> {
+        match self.replay(vec![(method, params)], CallKind::Single)? {
+            Outcome::Response(ResponseObj::Single(response)) => Ok(response),
+            _ => Err(ReplayTransportError::UnexpectedResponse),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Transport for ReplayTransport {
+    type Error = ReplayTransportError;
+
+    async fn request<M>(&self, method: M, params: Option<Params>) -> Result<Response, Self::Error>
+    where
+        M: Into<String> + Send,
+    {
+        self.replay_single(method.into(), params)
+    }
+}
+
+#[async_trait::async_trait]
+impl BatchTransport for ReplayTransport {
+    async fn request_batch<I, M>(&self, batch: I) -> Result<BatchResponse, Self::Error>
+    where
+        I: IntoIterator<Item = (M, Option<Params>)> + Send,
+        I::IntoIter: Send,
+        M: Into<String>,
+    {
+        let batch = batch
+            .into_iter()
+            .map(|(method, params)| (method.into(), params))
+            .collect();
+        match self.replay(batch, CallKind::Batch)? {
+            Outcome::Response(ResponseObj::Batch(responses)) => Ok(responses),
+            _ => Err(ReplayTransportError::UnexpectedResponse),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl PubsubTransport for ReplayTransport {
+    type NotificationStream = ReplaySubscription;
+
+    async fn subscribe<M>(
+        &self,
+        subscribe_method: M,
+        params: Option<Params>,
+    ) -> Result<(Id, Self::NotificationStream), Self::Error>
+    where
+        M: Into<String> + Send,
+    {
+        match self.replay(vec![(subscribe_method.into(), params)], CallKind::Subscribe)? {
+            Outcome::Subscription(id, notifications) => {
+                let notifications = notifications.into_iter().collect();
+                Ok((id.clone(), ReplaySubscription { id, notifications }))
+            }
+            Outcome::Response(_) => Err(ReplayTransportError::UnexpectedResponse),
+        }
+    }
+
+    async fn unsubscribe<M>(&self, unsubscribe_method: M, subscription_id: Id) -> Result<bool, Self::Error>
+    where
+        M: Into<String> + Send,
+    {
+        let params = Params::Array(vec![serde_json::to_value(subscription_id)?]);
+        match self.replay_single(unsubscribe_method.into(), Some(params))? {
+            Response::Success(success) => Ok(serde_json::from_value::<bool>(success.result)?),
+            Response::Failure(_) => Err(ReplayTransportError::InvalidUnsubscribeResult),
+        }
+    }
+}
+
+/// Active subscription on a replay transport, which yields the recorded notifications.
+pub struct ReplaySubscription {
+    /// Subscription ID.
+    pub id: Id,
+    notifications: VecDeque<SubscriptionNotification>,
+}
+
+impl ReplaySubscription {
+    /// Returns the next recorded notification.
+    pub async fn next(&mut self) -> Option<SubscriptionNotification> {
+        self.notifications.pop_front()
+    }
+}
+
+impl Stream for ReplaySubscription {
+    type Item = SubscriptionNotification;
+
+    fn poll_next(mut self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Poll::Ready(self.notifications.pop_front())
+    }
+}
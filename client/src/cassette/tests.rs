@@ -0,0 +1,121 @@
+use futures::{executor::block_on, stream::StreamExt};
+
+use super::*;
+use crate::transport::{BatchTransport, PubsubTransport, Transport};
+
+const CASSETTE: &str = concat!(
+    r#"{"request":{"jsonrpc":"2.0","method":"foo","id":1},"response":{"jsonrpc":"2.0","result":"x","id":7}}"#,
+    "\n",
+    r#"{"request":[{"jsonrpc":"2.0","method":"foo","id":2},{"jsonrpc":"2.0","method":"bar","id":3}],"response":[{"jsonrpc":"2.0","result":"y","id":9},{"jsonrpc":"2.0","result":"x","id":8}]}"#,
+    "\n",
+    r#"{"request":{"jsonrpc":"2.0","method":"subscribe","id":4},"subscription":"0x1"}"#,
+    "\n",
+    r#"{"jsonrpc":"2.0","method":"notify","params":{"subscription":"0x1","result":1}}"#,
+    "\n",
+    r#"{"jsonrpc":"2.0","method":"notify","params":{"subscription":"0x1","result":2}}"#,
+    "\n",
+    r#"{"request":{"jsonrpc":"2.0","method":"unsubscribe","params":["0x1"],"id":5},"response":{"jsonrpc":"2.0","result":true,"id":5}}"#,
+    "\n",
+);
+
+#[test]
+fn replay_recorded_cassette() {
+    let transport = ReplayTransport::from_cassette(CASSETTE).unwrap();
+
+    let response = block_on(transport.request("foo", None)).unwrap();
+    assert_eq!(response, Response::success("x".into(), 7.into()));
+    // the request has been replayed.
+    assert!(matches!(
+        block_on(transport.request("foo", None)),
+        Err(crate::error::ReplayTransportError::UnrecordedRequest(_))
+    ));
+
+    let response = block_on(transport.request_batch(vec![("foo", None), ("bar", None)])).unwrap();
+    assert_eq!(
+        response,
+        vec![
+            Response::success("y".into(), 9.into()),
+            Response::success("x".into(), 8.into())
+        ]
+    );
+
+    let (id, subscription) = block_on(transport.subscribe("subscribe", None)).unwrap();
+    assert_eq!(id, Id::Str("0x1".into()));
+    let results = block_on(
+        subscription
+            .map(|notification| notification.params.result)
+            .collect::<Vec<_>>(),
+    );
+    assert_eq!(results, vec![Value::from(1), Value::from(2)]);
+    assert!(block_on(transport.unsubscribe("unsubscribe", id)).unwrap());
+    assert!(transport.is_finished());
+}
+
+#[cfg(feature = "mock")]
+#[test]
+fn record_and_replay_exchanges() {
+    use crate::mock_transport::MockTransport;
+
+    let mock = MockTransport::new();
+    mock.expect("foo")
+        .returns("x")
+        .expect("bar")
+        .fails(Error::method_not_found())
+        .expect("subscribe")
+        .notifies("notify", vec![1, 2])
+        .expect("unsubscribe")
+        .returns(true);
+
+    let path = std::env::temp_dir().join(format!("jsonrpc-cassette-test-{}.jsonl", std::process::id()));
+    let transport = RecordTransport::new(mock, &path).unwrap();
+    let response = block_on(transport.request("foo", None)).unwrap();
+    let batch_response = block_on(transport.request_batch(vec![("foo", None), ("bar", None)])).unwrap();
+    let (id, subscription) = block_on(transport.subscribe("subscribe", None)).unwrap();
+    let notifications = block_on(subscription.take(2).collect::<Vec<_>>());
+    assert_eq!(notifications.len(), 2);
+    assert!(block_on(transport.unsubscribe("unsubscribe", id.clone())).unwrap());
+    assert_eq!(transport.into_inner().requests().len(), 5);
+
+    let transport = ReplayTransport::new(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(block_on(transport.request("foo", None)).unwrap(), response);
+    assert_eq!(
+        block_on(transport.request_batch(vec![("foo", None), ("bar", None)])).unwrap(),
+        batch_response
+    );
+    let (replayed_id, subscription) = block_on(transport.subscribe("subscribe", None)).unwrap();
+    assert_eq!(replayed_id, id);
+    assert_eq!(block_on(subscription.collect::<Vec<_>>()), notifications);
+    assert!(block_on(transport.unsubscribe("unsubscribe", id)).unwrap());
+    assert!(transport.is_finished());
+}
+
+#[cfg(feature = "mock")]
+#[test]
+fn replay_notifications_of_subscription_only() {
+    use crate::mock_transport::MockTransport;
+
+    // the subscription ID is the request ID of mock transport.
+    let mock = MockTransport::new();
+    mock.expect("subscribe")
+        .notifies("notify", vec![1, 2])
+        .expect("blockNumber")
+        .returns(1);
+
+    let path = std::env::temp_dir().join(format!("jsonrpc-cassette-collision-{}.jsonl", std::process::id()));
+    let transport = RecordTransport::new(mock, &path).unwrap();
+    let (id, subscription) = block_on(transport.subscribe("subscribe", None)).unwrap();
+    assert_eq!(id, Id::Num(1));
+    // the result is the same as the subscription ID.
+    let response = block_on(transport.request("blockNumber", None)).unwrap();
+    assert_eq!(response, Response::success(1.into(), 2.into()));
+    let notifications = block_on(subscription.take(2).collect::<Vec<_>>());
+
+    let transport = ReplayTransport::new(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    let (replayed_id, subscription) = block_on(transport.subscribe("subscribe", None)).unwrap();
+    assert_eq!(replayed_id, id);
+    assert_eq!(block_on(transport.request("blockNumber", None)).unwrap(), response);
+    assert_eq!(block_on(subscription.collect::<Vec<_>>()), notifications);
+    assert!(transport.is_finished());
+}
@@ -0,0 +1,191 @@
+use std::{error::Error, fmt, future::Future, sync::Arc, time::Duration};
+
+use futures::{
+    channel::mpsc,
+    sink::{Sink, SinkExt},
+    stream::{Stream, StreamExt},
+};
+use jsonrpc_types::v2::{Id, Notification};
+
+use crate::{
+    duplex::{CancelNotification, DuplexClient, DuplexTask, Frontend, IncomingHandler, UnsubscribeMethod},
+    error::DuplexClientError,
+    id::{IdGenerator, SequentialIdGenerator},
+};
+
+/// A `DuplexClientBuilder` can be used to create a `DuplexClient` with custom configuration.
+pub struct DuplexClientBuilder {
+    handler: Option<Arc<dyn IncomingHandler>>,
+    cancel_notification: Option<CancelNotification>,
+    unsubscribe_method: Option<UnsubscribeMethod>,
+    id_generator: Arc<dyn IdGenerator>,
+    timeout: Option<Duration>,
+    max_concurrent_request_capacity: usize,
+    max_capacity_per_subscription: usize,
+}
+
+impl fmt::Debug for DuplexClientBuilder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DuplexClientBuilder")
+            .field("handler", &self.handler.as_ref().map(|_| "IncomingHandler"))
+            .field("cancel_notification", &self.cancel_notification.as_ref().map(|_| "Fn"))
+            .field("unsubscribe_method", &self.unsubscribe_method.as_ref().map(|_| "Fn"))
+            .field("timeout", &self.timeout)
+            .field("max_concurrent_request_capacity", &self.max_concurrent_request_capacity)
+            .field("max_capacity_per_subscription", &self.max_capacity_per_subscription)
+            .finish()
+    }
+}
+
+impl Default for DuplexClientBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DuplexClientBuilder {
+    /// Creates a new `DuplexClientBuilder`.
+    ///
+    /// This is the same as `DuplexClient::builder()`.
+    pub fn new() -> Self {
+        Self {
+            handler: None,
+            cancel_notification: None,
+            unsubscribe_method: None,
+            id_generator: Arc::new(SequentialIdGenerator::default()),
+            timeout: None,
+            max_concurrent_request_capacity: 256,
+            max_capacity_per_subscription: 64,
+        }
+    }
+
+    // ========================================================================
+    // Handler options
+    // ========================================================================
+
+    /// Sets the handler of the requests and notifications sent by the server.
+    ///
+    /// Default responds `Method not found` errors to all requests and ignores all notifications.
+    pub fn handler<H: IncomingHandler + 'static>(mut self, handler: H) -> Self {
+        self.handler = Some(Arc::new(handler));
+        self
+    }
+
+    // ========================================================================
+    // Channel options
+    // ========================================================================
+
+    /// Sets the max channel capacity of sending request concurrently.
+    ///
+    /// Default is 256.
+    pub fn max_concurrent_request_capacity(mut self, capacity: usize) -> Self {
+        self.max_concurrent_request_capacity = capacity;
+        self
+    }
+
+    /// Sets the max channel capacity of every subscription stream.
+    ///
+    /// Default is 64.
+    pub fn max_capacity_per_subscription(mut self, capacity: usize) -> Self {
+        self.max_capacity_per_subscription = capacity;
+        self
+    }
+
+    // ========================================================================
+    // Timeout options
+    // ========================================================================
+
+    /// Enables a request timeout.
+    ///
+    /// Default is no timeout.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    // ========================================================================
+    // Request ID options
+    // ========================================================================
+
+    /// Sets the generator of request IDs.
+    ///
+    /// Default is `SequentialIdGenerator`, which generates sequential numeric IDs starting from 1.
+    pub fn id_generator<G: IdGenerator + 'static>(mut self, id_generator: G) -> Self {
+        self.id_generator = Arc::new(id_generator);
+        self
+    }
+
+    // ========================================================================
+    // Cancellation options
+    // ========================================================================
+
+    /// Sets a function to build the notification sent to the server, when a pending request
+    /// is canceled because of timeout or dropping the request future.
+    ///
+    /// The canceled request is always removed from the client, and its response will be ignored.
+    pub fn cancel_notification<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&Id) -> Notification + Send + Sync + 'static,
+    {
+        self.cancel_notification = Some(Box::new(f));
+        self
+    }
+
+    /// Sets a function to get the unsubscribe method by the subscribe method, which is called to
+    /// close the subscription on the server, when the subscribe future is dropped after the server
+    /// has answered it.
+    ///
+    /// Default replaces `subscribe` with `unsubscribe` in the subscribe method (e.g. `eth_subscribe`
+    /// to `eth_unsubscribe`), and the subscription isn't closed if the function returns `None`.
+    pub fn unsubscribe_method<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&str) -> Option<String> + Send + Sync + 'static,
+    {
+        self.unsubscribe_method = Some(Box::new(f));
+        self
+    }
+
+    // ========================================================================
+
+    /// Returns a `DuplexClient` over the message-oriented `channel`, and the background task of
+    /// the client.
+    ///
+    /// The background task processes all requests and subscriptions of the client, it must be
+    /// polled (e.g. spawned on any executor) for the client to work. The task is terminated when
+    /// the channel is closed or fails.
+    pub fn build<S>(self, channel: S) -> (DuplexClient, impl Future<Output = ()> + Send + 'static)
+    where
+        S: Stream<Item = String> + Sink<String> + Send + 'static,
+        <S as Sink<String>>::Error: Error + Send + Sync + 'static,
+    {
+        let (sink, stream) = channel.split();
+        self.build_with_split(sink, stream)
+    }
+
+    /// Returns a `DuplexClient` over the `sink` sending messages and the `stream` receiving
+    /// messages, and the background task of the client.
+    ///
+    /// The background task must be polled (e.g. spawned on any executor) for the client to work.
+    pub fn build_with_split<Tx, Rx>(
+        self,
+        sink: Tx,
+        stream: Rx,
+    ) -> (DuplexClient, impl Future<Output = ()> + Send + 'static)
+    where
+        Tx: Sink<String> + Send + 'static,
+        Tx::Error: Error + Send + Sync + 'static,
+        Rx: Stream<Item = String> + Send + 'static,
+    {
+        let sink = sink.sink_map_err(|err| DuplexClientError::Transport(Box::new(err)));
+        let stream = stream.map(Ok);
+        let (to_back, from_front) = mpsc::channel(self.max_concurrent_request_capacity);
+        let task = DuplexTask::new(self.max_capacity_per_subscription)
+            .handler(self.handler)
+            .cancel_notification(self.cancel_notification)
+            .unsubscribe_method(self.unsubscribe_method);
+        let client = DuplexClient {
+            frontend: Frontend::new(to_back, self.id_generator, self.timeout),
+        };
+        (client, task.into_task(sink, stream, from_front))
+    }
+}
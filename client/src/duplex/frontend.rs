@@ -0,0 +1,291 @@
+use std::{
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use futures::{
+    channel::{mpsc, oneshot},
+    future,
+    sink::SinkExt,
+    stream::{Stream, StreamExt},
+};
+use jsonrpc_types::v2::*;
+
+use crate::{
+    duplex::manager::{TaskError, ToBackTaskMessage},
+    id::IdGenerator,
+};
+
+/// The frontend of the clients, which sends the requests and subscriptions to the background task.
+pub(crate) struct Frontend<E> {
+    to_back: mpsc::Sender<ToBackTaskMessage<E>>,
+    id_generator: Arc<dyn IdGenerator>,
+    /// Request timeout.
+    timeout: Option<Duration>,
+}
+
+impl<E> Clone for Frontend<E> {
+    fn clone(&self) -> Self {
+        Self {
+            to_back: self.to_back.clone(),
+            id_generator: self.id_generator.clone(),
+            timeout: self.timeout,
+        }
+    }
+}
+
+impl<E: TaskError> Frontend<E> {
+    pub(crate) fn new(
+        to_back: mpsc::Sender<ToBackTaskMessage<E>>,
+        id_generator: Arc<dyn IdGenerator>,
+        timeout: Option<Duration>,
+    ) -> Self {
+        Self {
+            to_back,
+            id_generator,
+            timeout,
+        }
+    }
+
+    fn next_id(&self) -> Id {
+        self.id_generator.next_id()
+    }
+
+    /// Sends a message to the background task and waits for the response.
+    ///
+    /// If the response is not received because of timeout or dropping the returned future,
+    /// the pending requests with `ids` will be canceled in the background task.
+    async fn send_to_back<T>(
+        &self,
+        msg: ToBackTaskMessage<E>,
+        rx: oneshot::Receiver<Result<T, E>>,
+        ids: Vec<Id>,
+    ) -> Result<T, E> {
+        let mut to_back = self.to_back.clone();
+        to_back.send(msg).await.map_err(|_| E::internal_channel())?;
+        let mut guard = CancelGuard {
+            to_back,
+            ids: Some(ids).filter(|ids| !ids.is_empty()),
+        };
+
+        let res = if let Some(duration) = self.timeout {
+            let timeout = futures_timer::Delay::new(duration);
+            futures::pin_mut!(rx, timeout);
+            match future::select(rx, timeout).await {
+                future::Either::Left((response, _)) => response,
+                future::Either::Right((_, _)) => return Err(E::request_timeout()),
+            }
+        } else {
+            rx.await
+        };
+        match res {
+            Ok(Ok(output)) => {
+                guard.ids = None;
+                Ok(output)
+            }
+            Ok(Err(err)) => {
+                guard.ids = None;
+                Err(err)
+            }
+            Err(_) => Err(E::internal_channel()),
+        }
+    }
+
+    /// Sends a `method call` request to the server.
+    pub(crate) async fn send_request(&self, method: impl Into<String>, params: Option<Params>) -> Result<Response, E> {
+        let method = method.into();
+        log::debug!("[frontend] Send request: method={}, params={:?}", method, params);
+
+        let id = self.next_id();
+        let (tx, rx) = oneshot::channel();
+        let msg = ToBackTaskMessage::Request {
+            id: id.clone(),
+            method,
+            params,
+            send_back: tx,
+        };
+        self.send_to_back(msg, rx, vec![id]).await
+    }
+
+    /// Sends a batch of `method call` requests to the server.
+    pub(crate) async fn send_request_batch<I, M>(&self, batch: I) -> Result<BatchResponse, E>
+    where
+        I: IntoIterator<Item = (M, Option<Params>)>,
+        M: Into<String>,
+    {
+        let batch = batch
+            .into_iter()
+            .map(|(method, params)| (self.next_id(), method.into(), params))
+            .collect::<Vec<_>>();
+        log::debug!("[frontend] Send a batch of requests: {:?}", batch);
+
+        let ids = batch.iter().map(|(id, _, _)| id.clone()).collect();
+        let (tx, rx) = oneshot::channel();
+        let msg = ToBackTaskMessage::BatchRequest { batch, send_back: tx };
+        self.send_to_back(msg, rx, ids).await
+    }
+
+    /// Sends a notification to the server, which returns once the notification is sent.
+    pub(crate) async fn send_notification(&self, method: impl Into<String>, params: Option<Params>) -> Result<(), E> {
+        let method = method.into();
+        log::debug!("[frontend] Send notification: method={}, params={:?}", method, params);
+
+        let (tx, rx) = oneshot::channel();
+        let msg = ToBackTaskMessage::Notification {
+            method,
+            params,
+            send_back: tx,
+        };
+        // the notification is not pending in the background task, so there is nothing to cancel.
+        self.send_to_back(msg, rx, vec![]).await
+    }
+
+    /// Sends a subscribe request to the server.
+    ///
+    /// `subscribe_method` and `params` are used to ask for the subscription towards the server.
+    /// `unsubscribe_method` is used to close the subscription.
+    pub(crate) async fn send_subscribe(
+        &self,
+        subscribe_method: impl Into<String>,
+        params: Option<Params>,
+    ) -> Result<DuplexSubscription<SubscriptionNotification>, E> {
+        let subscribe_method = subscribe_method.into();
+        log::debug!("[frontend] Subscribe: method={}, params={:?}", subscribe_method, params);
+
+        let id = self.next_id();
+        let (tx, rx) = oneshot::channel();
+        let msg = ToBackTaskMessage::Subscribe {
+            id: id.clone(),
+            subscribe_method,
+            params,
+            send_back: tx,
+        };
+        let (id, notification_rx) = self.send_to_back(msg, rx, vec![id]).await?;
+        Ok(DuplexSubscription { id, notification_rx })
+    }
+
+    /// Sends an unsubscribe request to the server.
+    pub(crate) async fn send_unsubscribe(
+        &self,
+        unsubscribe_method: impl Into<String>,
+        subscription_id: Id,
+    ) -> Result<bool, E> {
+        let unsubscribe_method = unsubscribe_method.into();
+        log::debug!(
+            "[frontend] unsubscribe: method={}, id={:?}",
+            unsubscribe_method,
+            subscription_id
+        );
+
+        let id = self.next_id();
+        let (tx, rx) = oneshot::channel();
+        let msg = ToBackTaskMessage::Unsubscribe {
+            id: id.clone(),
+            unsubscribe_method,
+            subscription_id,
+            send_back: tx,
+        };
+        self.send_to_back(msg, rx, vec![id]).await
+    }
+}
+
+/// Cancels the pending requests in the background task when dropped, unless the response is received.
+struct CancelGuard<E> {
+    to_back: mpsc::Sender<ToBackTaskMessage<E>>,
+    ids: Option<Vec<Id>>,
+}
+
+impl<E> Drop for CancelGuard<E> {
+    fn drop(&mut self) {
+        if let Some(ids) = self.ids.take() {
+            log::debug!("[frontend] Cancel requests: ids={:?}", ids);
+            // Every new sender has a guaranteed slot in the channel, so it only fails when the
+            // background task has been terminated.
+            let _ = self.to_back.clone().try_send(ToBackTaskMessage::Cancel { ids });
+        }
+    }
+}
+
+/// Active subscription on a duplex client (e.g. WebSocket, IPC or TCP client).
+pub struct DuplexSubscription<Notif> {
+    /// Subscription ID.
+    pub id: Id,
+    /// Channel from which we receive notifications from the server.
+    notification_rx: mpsc::Receiver<Notif>,
+}
+
+impl<Notif> DuplexSubscription<Notif> {
+    /// Returns the next notification from the connection.
+    ///
+    /// Ignore any malformed packet.
+    pub async fn next(&mut self) -> Option<Notif> {
+        self.notification_rx.next().await
+    }
+}
+
+impl<Notif> Stream for DuplexSubscription<Notif> {
+    type Item = Notif;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        mpsc::Receiver::<Notif>::poll_next(Pin::new(&mut self.notification_rx), cx)
+    }
+}
+
+/// Implements `Transport`, `BatchTransport` and `PubsubTransport` for the client, which delegates
+/// the requests to its `frontend` field.
+macro_rules! impl_duplex_transports {
+    ($client:ty, $error:ty) => {
+        #[async_trait::async_trait]
+        impl $crate::transport::Transport for $client {
+            type Error = $error;
+
+            async fn request<M>(&self, method: M, params: Option<Params>) -> Result<Response, Self::Error>
+            where
+                M: Into<String> + Send,
+            {
+                self.frontend.send_request(method, params).await
+            }
+        }
+
+        #[async_trait::async_trait]
+        impl $crate::transport::BatchTransport for $client {
+            async fn request_batch<I, M>(&self, batch: I) -> Result<BatchResponse, Self::Error>
+            where
+                I: IntoIterator<Item = (M, Option<Params>)> + Send,
+                I::IntoIter: Send,
+                M: Into<String>,
+            {
+                self.frontend.send_request_batch(batch).await
+            }
+        }
+
+        #[async_trait::async_trait]
+        impl $crate::transport::PubsubTransport for $client {
+            type NotificationStream = $crate::duplex::DuplexSubscription<SubscriptionNotification>;
+
+            async fn subscribe<M>(
+                &self,
+                subscribe_method: M,
+                params: Option<Params>,
+            ) -> Result<(Id, Self::NotificationStream), Self::Error>
+            where
+                M: Into<String> + Send,
+            {
+                let notification_stream = self.frontend.send_subscribe(subscribe_method, params).await?;
+                Ok((notification_stream.id.clone(), notification_stream))
+            }
+
+            async fn unsubscribe<M>(&self, unsubscribe_method: M, subscription_id: Id) -> Result<bool, Self::Error>
+            where
+                M: Into<String> + Send,
+            {
+                self.frontend
+                    .send_unsubscribe(unsubscribe_method, subscription_id)
+                    .await
+            }
+        }
+    };
+}
+pub(crate) use impl_duplex_transports;
@@ -0,0 +1,28 @@
+use jsonrpc_types::v2::*;
+
+/// A handler of the requests and notifications sent by the server to the client, e.g. the
+/// `workspace/configuration` requests of a language server.
+///
+/// The requests are handled concurrently by the background task of the client, and the results
+/// are sent back to the server as responses.
+#[async_trait::async_trait]
+pub trait IncomingHandler: Send + Sync {
+    /// Handles a request sent by the server, and returns the result (or error) of the response.
+    ///
+    /// Default responds a `Method not found` error.
+    async fn handle_request(&self, method: String, params: Option<Params>) -> Result<Value, Error> {
+        log::debug!("[backend] Unhandled request: method={}, params={:?}", method, params);
+        Err(Error::method_not_found())
+    }
+
+    /// Handles a notification sent by the server.
+    ///
+    /// Default ignores the notification.
+    async fn handle_notification(&self, method: String, params: Option<Params>) {
+        log::debug!(
+            "[backend] Unhandled notification: method={}, params={:?}",
+            method,
+            params
+        );
+    }
+}
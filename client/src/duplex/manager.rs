@@ -0,0 +1,522 @@
+use std::collections::hash_map::{Entry, HashMap};
+
+use futures::channel::{mpsc, oneshot};
+use jsonrpc_types::v2::*;
+
+type PendingMethodCall<E> = oneshot::Sender<Result<Response, E>>;
+type PendingBatchMethodCall<E> = oneshot::Sender<Result<BatchResponse, E>>;
+type PendingSubscription<E> = oneshot::Sender<Result<(Id, mpsc::Receiver<SubscriptionNotification>), E>>;
+type ActiveSubscription = mpsc::Sender<SubscriptionNotification>;
+type PendingUnsubscribe<E> = oneshot::Sender<Result<bool, E>>;
+
+/// The errors of the clients whose requests and subscriptions are managed by `TaskManager`.
+pub(crate) trait TaskError: std::error::Error + From<serde_json::Error> + Send + 'static {
+    /// The request ID of a request is already used by a pending request.
+    fn duplicate_request_id() -> Self;
+    /// The request ID of a response is invalid.
+    fn invalid_request_id() -> Self;
+    /// The result of a subscribe request is not a valid subscription ID.
+    fn invalid_subscription_id() -> Self;
+    /// The result of an unsubscribe request is not a boolean.
+    fn invalid_unsubscribe_result() -> Self;
+    /// The response of a request is not received in time.
+    fn request_timeout() -> Self;
+    /// The background task is terminated.
+    fn internal_channel() -> Self;
+}
+
+/// Message that the client can send to the background task.
+pub(crate) enum ToBackTaskMessage<E> {
+    Request {
+        id: Id,
+        method: String,
+        params: Option<Params>,
+        /// One-shot channel where to send back the response of the request.
+        send_back: oneshot::Sender<Result<Response, E>>,
+    },
+    BatchRequest {
+        batch: Vec<(Id, String, Option<Params>)>,
+        /// One-shot channel where to send back the response of the batch request.
+        send_back: oneshot::Sender<Result<BatchResponse, E>>,
+    },
+    Subscribe {
+        id: Id,
+        subscribe_method: String,
+        params: Option<Params>,
+        /// One-shot channel where to send back the response (subscription id) and a `Receiver`
+        /// that will receive subscription notification when we get a response (subscription id)
+        /// from the server about the subscription.
+        send_back: oneshot::Sender<Result<(Id, mpsc::Receiver<SubscriptionNotification>), E>>,
+    },
+    Unsubscribe {
+        id: Id,
+        unsubscribe_method: String,
+        subscription_id: Id,
+        /// One-shot channel where to send back the response of the unsubscribe request.
+        send_back: oneshot::Sender<Result<bool, E>>,
+    },
+    Notification {
+        method: String,
+        params: Option<Params>,
+        /// One-shot channel where to send back the result of sending the notification.
+        send_back: oneshot::Sender<Result<(), E>>,
+    },
+    /// Cancels the pending requests whose response is no longer awaited by the frontend, or
+    /// closes the subscription whose subscribe response is no longer awaited by the frontend.
+    Cancel { ids: Vec<Id> },
+}
+
+#[derive(Debug)]
+enum RequestKind<E> {
+    PendingMethodCall(PendingMethodCall<E>),
+    PendingBatchMethodCall(PendingBatchMethodCall<E>),
+    /// The subscription with the method to close it on the server.
+    PendingSubscription((Option<String>, PendingSubscription<E>)),
+    /// The subscription with the method to close it on the server.
+    ActiveSubscription((Option<String>, ActiveSubscription)),
+    PendingUnsubscribe((Id, PendingUnsubscribe<E>)),
+}
+
+pub enum RequestStatus {
+    /// The method call is waiting for a response.
+    PendingMethodCall,
+    /// The batch of method calls is waiting for batch of responses.
+    PendingBatchMethodCall,
+    /// The subscription is waiting for a response to become an active subscription.
+    PendingSubscription,
+    /// An active subscription.
+    ActiveSubscription,
+    /// The unsubscribe method call is waiting for a response.
+    PendingUnsubscribe,
+    /// Invalid request ID.
+    Invalid,
+}
+
+/// Manages JSON-RPC 2.0 method calls and subscriptions.
+#[derive(Debug)]
+pub struct TaskManager<E> {
+    /// Requests that are waiting for response from the server.
+    requests: HashMap<Id, RequestKind<E>>,
+    /// Helper to find a request ID by subscription ID instead of looking through all requests.
+    subscriptions: HashMap<Id, Id>,
+    /// Max capacity of every subscription channel.
+    pub(crate) max_capacity_per_subscription: usize,
+}
+
+impl<E> TaskManager<E> {
+    pub fn new(max_capacity_per_subscription: usize) -> Self {
+        Self {
+            requests: HashMap::new(),
+            subscriptions: HashMap::new(),
+            max_capacity_per_subscription,
+        }
+    }
+
+    /// Tries to insert a new pending method call into manager.
+    pub fn insert_pending_method_call(
+        &mut self,
+        request_id: Id,
+        send_back: PendingMethodCall<E>,
+    ) -> Result<(), PendingMethodCall<E>> {
+        match self.requests.entry(request_id) {
+            Entry::Vacant(request) => {
+                request.insert(RequestKind::PendingMethodCall(send_back));
+                Ok(())
+            }
+            // Duplicate request ID.
+            Entry::Occupied(_) => Err(send_back),
+        }
+    }
+
+    /// Tries to complete a pending method call from manager.
+    pub fn complete_pending_method_call(&mut self, request_id: Id) -> Option<PendingMethodCall<E>> {
+        match self.requests.entry(request_id) {
+            Entry::Occupied(request) if matches!(request.get(), RequestKind::PendingMethodCall(_)) => {
+                if let (_req_id, RequestKind::PendingMethodCall(send_back)) = request.remove_entry() {
+                    Some(send_back)
+                } else {
+                    unreachable!("Kind must be PendingMethodCall; qed");
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// Tries to insert a new pending method call into manager.
+    pub fn insert_pending_batch_method_call(
+        &mut self,
+        min_request_id: Id,
+        send_back: PendingBatchMethodCall<E>,
+    ) -> Result<(), PendingBatchMethodCall<E>> {
+        match self.requests.entry(min_request_id) {
+            Entry::Vacant(request) => {
+                request.insert(RequestKind::PendingBatchMethodCall(send_back));
+                Ok(())
+            }
+            // Duplicate request ID.
+            Entry::Occupied(_) => Err(send_back),
+        }
+    }
+
+    /// Tries to complete a pending batch method call from manager.
+    pub fn complete_pending_batch_method_call(&mut self, min_request_id: Id) -> Option<PendingBatchMethodCall<E>> {
+        match self.requests.entry(min_request_id) {
+            Entry::Occupied(request) if matches!(request.get(), RequestKind::PendingBatchMethodCall(_)) => {
+                if let (_min_req_id, RequestKind::PendingBatchMethodCall(send_back)) = request.remove_entry() {
+                    Some(send_back)
+                } else {
+                    unreachable!("Kind must be PendingMethodCall; qed");
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// Tries to insert a new pending subscription into manager.
+    ///
+    /// The `unsubscribe_method` is used to close the subscription on the server, if the subscription
+    /// is canceled after it becomes active.
+    pub fn insert_pending_subscription(
+        &mut self,
+        request_id: Id,
+        unsubscribe_method: Option<String>,
+        send_back: PendingSubscription<E>,
+    ) -> Result<(), PendingSubscription<E>> {
+        match self.requests.entry(request_id) {
+            Entry::Vacant(request) => {
+                request.insert(RequestKind::PendingSubscription((unsubscribe_method, send_back)));
+                Ok(())
+            }
+            // Duplicate request ID.
+            Entry::Occupied(_) => Err(send_back),
+        }
+    }
+
+    /// Tries to complete a pending subscription from manager.
+    pub fn complete_pending_subscription(
+        &mut self,
+        request_id: Id,
+    ) -> Option<(Option<String>, PendingSubscription<E>)> {
+        match self.requests.entry(request_id) {
+            Entry::Occupied(request) if matches!(request.get(), RequestKind::PendingSubscription(_)) => {
+                if let (_id, RequestKind::PendingSubscription(send_back)) = request.remove_entry() {
+                    Some(send_back)
+                } else {
+                    unreachable!("Kind must be PendingSubscription; qed");
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// Tries to insert a new active subscription into manager.
+    pub fn insert_active_subscription(
+        &mut self,
+        request_id: Id,
+        subscription_id: Id,
+        unsubscribe_method: Option<String>,
+        send_back: ActiveSubscription,
+    ) -> Result<(), ActiveSubscription> {
+        match (
+            self.requests.entry(request_id.clone()),
+            self.subscriptions.entry(subscription_id),
+        ) {
+            (Entry::Vacant(request), Entry::Vacant(subscription)) => {
+                request.insert(RequestKind::ActiveSubscription((unsubscribe_method, send_back)));
+                subscription.insert(request_id);
+                Ok(())
+            }
+            // Duplicate request ID or subscription ID.
+            _ => Err(send_back),
+        }
+    }
+
+    /// Tries to remove an active subscription from manager.
+    pub fn remove_active_subscription(&mut self, request_id: Id, subscription_id: Id) -> Option<ActiveSubscription> {
+        match (
+            self.requests.entry(request_id),
+            self.subscriptions.entry(subscription_id),
+        ) {
+            (Entry::Occupied(request), Entry::Occupied(subscription)) => {
+                let (_req_id, kind) = request.remove_entry();
+                let (_sub_id, _req_id) = subscription.remove_entry();
+                if let RequestKind::ActiveSubscription((_unsubscribe_method, send_back)) = kind {
+                    Some(send_back)
+                } else {
+                    unreachable!("Kind must be ActiveSubscription; qed");
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// Tries to insert a new pending unsubscribe method call into manager.
+    pub fn insert_pending_unsubscribe(
+        &mut self,
+        request_id: Id,
+        subscription_id: Id,
+        send_back: PendingUnsubscribe<E>,
+    ) -> Result<(), PendingUnsubscribe<E>> {
+        match self.requests.entry(request_id) {
+            Entry::Vacant(request) => {
+                request.insert(RequestKind::PendingUnsubscribe((subscription_id, send_back)));
+                Ok(())
+            }
+            // Duplicate request ID.
+            Entry::Occupied(_) => Err(send_back),
+        }
+    }
+
+    /// Tries to complete a pending method call from manager.
+    pub fn complete_pending_unsubscribe(&mut self, request_id: Id) -> Option<(Id, PendingUnsubscribe<E>)> {
+        match self.requests.entry(request_id) {
+            Entry::Occupied(request) if matches!(request.get(), RequestKind::PendingUnsubscribe(_)) => {
+                if let (_req_id, RequestKind::PendingUnsubscribe(send_back)) = request.remove_entry() {
+                    Some(send_back)
+                } else {
+                    unreachable!("Kind must be PendingUnsubscribe; qed");
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// Tries to cancel a pending request (method call, batch method call, subscription or
+    /// unsubscribe) from manager, returns `true` if the request is canceled.
+    pub fn cancel_pending_request(&mut self, request_id: Id) -> bool {
+        match self.requests.entry(request_id) {
+            Entry::Occupied(request) if !matches!(request.get(), RequestKind::ActiveSubscription(_)) => {
+                request.remove();
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Tries to cancel an active subscription from manager, returns the subscription ID and
+    /// the method to close it on the server if the subscription is canceled.
+    pub fn cancel_active_subscription(&mut self, request_id: &Id) -> Option<(Id, Option<String>)> {
+        let subscription_id = self
+            .subscriptions
+            .iter()
+            .find(|(_sub_id, req_id)| *req_id == request_id)
+            .map(|(sub_id, _req_id)| sub_id.clone())?;
+        match self.requests.remove(request_id) {
+            Some(RequestKind::ActiveSubscription((unsubscribe_method, _send_back))) => {
+                self.subscriptions.remove(&subscription_id);
+                Some((subscription_id, unsubscribe_method))
+            }
+            _ => unreachable!("Kind must be ActiveSubscription; qed"),
+        }
+    }
+
+    /// Reverse lookup to get the request ID by a subscription ID.
+    pub fn get_request_id_by(&self, subscription_id: &Id) -> Option<Id> {
+        self.subscriptions.get(subscription_id).cloned()
+    }
+
+    /// Returns the status of a request ID.
+    pub fn request_status(&mut self, request_id: &Id) -> RequestStatus {
+        self.requests
+            .get(request_id)
+            .map_or(RequestStatus::Invalid, |kind| match kind {
+                RequestKind::PendingMethodCall(_) => RequestStatus::PendingMethodCall,
+                RequestKind::PendingBatchMethodCall(_) => RequestStatus::PendingBatchMethodCall,
+                RequestKind::PendingSubscription(_) => RequestStatus::PendingSubscription,
+                RequestKind::ActiveSubscription(_) => RequestStatus::ActiveSubscription,
+                RequestKind::PendingUnsubscribe(_) => RequestStatus::PendingUnsubscribe,
+            })
+    }
+
+    /// Gets a mutable reference to active subscription sink to send messages back to
+    /// the subscription channel.
+    pub fn as_active_subscription_mut(&mut self, request_id: &Id) -> Option<&mut ActiveSubscription> {
+        let kind = self.requests.get_mut(request_id);
+        if let Some(RequestKind::ActiveSubscription((_unsubscribe_method, sink))) = kind {
+            Some(sink)
+        } else {
+            None
+        }
+    }
+}
+
+/// Sends the response back to the pending request.
+pub(crate) fn handle_response_message<E: TaskError>(
+    response: ResponseObj,
+    manager: &mut TaskManager<E>,
+) -> Result<(), E> {
+    match response {
+        ResponseObj::Single(response) => handle_single_output(response, manager),
+        ResponseObj::Batch(responses) => handle_batch_output(responses, manager),
+    }
+}
+
+fn handle_single_output<E: TaskError>(response: Response, manager: &mut TaskManager<E>) -> Result<(), E> {
+    let response_id = response_id_of::<E>(&response)?;
+    match manager.request_status(&response_id) {
+        RequestStatus::PendingMethodCall => {
+            log::debug!("[backend] Handle response of method call: id={}", response_id);
+            let send_back = manager
+                .complete_pending_method_call(response_id)
+                .ok_or(E::invalid_request_id())?;
+            let _ = send_back.send(Ok(response));
+            Ok(())
+        }
+        RequestStatus::PendingSubscription => {
+            log::debug!("[backend] Handle response of subscription request: id={}", response_id);
+            let (unsubscribe_method, send_back) = manager
+                .complete_pending_subscription(response_id.clone())
+                .ok_or(E::invalid_request_id())?;
+            let subscription_id = match response {
+                Response::Success(success) => match serde_json::from_value::<Id>(success.result) {
+                    Ok(id) => id,
+                    Err(err) => {
+                        let _ = send_back.send(Err(E::from(err)));
+                        return Ok(());
+                    }
+                },
+                Response::Failure(_) => {
+                    let _ = send_back.send(Err(E::invalid_subscription_id()));
+                    return Ok(());
+                }
+            };
+
+            let (subscribe_tx, subscribe_rx) = mpsc::channel(manager.max_capacity_per_subscription);
+            if manager
+                .insert_active_subscription(response_id, subscription_id.clone(), unsubscribe_method, subscribe_tx)
+                .is_ok()
+            {
+                let _ = send_back.send(Ok((subscription_id, subscribe_rx)));
+            } else {
+                let _ = send_back.send(Err(E::invalid_subscription_id()));
+            }
+            Ok(())
+        }
+        RequestStatus::PendingUnsubscribe => {
+            log::debug!("[backend] Handle response of unsubscribe request: id={}", response_id);
+            let (subscription_id, send_back) = manager
+                .complete_pending_unsubscribe(response_id)
+                .ok_or(E::invalid_request_id())?;
+            let result = match response {
+                Response::Success(success) => match serde_json::from_value::<bool>(success.result) {
+                    Ok(result) => result,
+                    Err(err) => {
+                        let _ = send_back.send(Err(E::from(err)));
+                        return Ok(());
+                    }
+                },
+                Response::Failure(failure) => {
+                    log::warn!("[backend] Unexpected response of unsubscribe request: {}", failure);
+                    let _ = send_back.send(Err(E::invalid_unsubscribe_result()));
+                    return Ok(());
+                }
+            };
+
+            let _ = send_back.send(Ok(result));
+
+            if result {
+                // clean the subscription of manager according to the subscription id when unsubscribe successfully.
+                if let Some(request_id) = manager.get_request_id_by(&subscription_id) {
+                    manager.remove_active_subscription(request_id, subscription_id);
+                } else {
+                    log::error!(
+                        "[backend] Task manager cannot find subscription: id={:?}",
+                        subscription_id
+                    );
+                }
+            }
+            Ok(())
+        }
+        RequestStatus::Invalid => {
+            log::warn!(
+                "[backend] Ignore response of unknown or canceled request: id={}",
+                response_id
+            );
+            Ok(())
+        }
+        RequestStatus::ActiveSubscription | RequestStatus::PendingBatchMethodCall => Err(E::invalid_request_id()),
+    }
+}
+
+fn response_id_of<E: TaskError>(resp: &Response) -> Result<Id, E> {
+    resp.id().ok_or(E::invalid_request_id())
+}
+
+fn handle_batch_output<E: TaskError>(responses: BatchResponse, manager: &mut TaskManager<E>) -> Result<(), E> {
+    let (min_response_id, max_response_id) = response_id_range_of::<E>(&responses)?;
+    // use the min id of batch request for managing task
+    match manager.request_status(&min_response_id) {
+        RequestStatus::PendingBatchMethodCall => {
+            log::debug!(
+                "[backend] Handle batch response of batch request: id=({}~{})",
+                min_response_id,
+                max_response_id
+            );
+            let send_back = manager
+                .complete_pending_batch_method_call(min_response_id)
+                .ok_or(E::invalid_request_id())?;
+            let _ = send_back.send(Ok(responses));
+            Ok(())
+        }
+        RequestStatus::Invalid => {
+            log::warn!(
+                "[backend] Ignore batch response of unknown or canceled request: id=({}~{})",
+                min_response_id,
+                max_response_id
+            );
+            Ok(())
+        }
+        RequestStatus::PendingMethodCall
+        | RequestStatus::PendingSubscription
+        | RequestStatus::ActiveSubscription
+        | RequestStatus::PendingUnsubscribe => Err(E::invalid_request_id()),
+    }
+}
+
+fn response_id_range_of<E: TaskError>(responses: &[Response]) -> Result<(Id, Id), E> {
+    let mut ids = responses.iter().map(response_id_of::<E>);
+    // an empty batch response doesn't match any batch request.
+    let first = ids.next().ok_or_else(E::invalid_request_id)??;
+    let (mut min, mut max) = (first.clone(), first);
+    for id in ids {
+        let id = id?;
+        if id < min {
+            min = id;
+        } else if id > max {
+            max = id;
+        }
+    }
+    Ok((min, max))
+}
+
+/// Sends the notification to the active subscription.
+pub(crate) fn handle_subscription_notification_message<E>(
+    notification: SubscriptionNotification,
+    manager: &mut TaskManager<E>,
+) {
+    let subscription_id = notification.params.subscription.clone();
+    let request_id = match manager.get_request_id_by(&subscription_id) {
+        Some(id) => id,
+        None => {
+            log::error!(
+                "[backend] Task manager cannot find subscription: id={:?}",
+                subscription_id
+            );
+            return;
+        }
+    };
+    match manager.as_active_subscription_mut(&request_id) {
+        Some(send_back) => {
+            if let Err(err) = send_back.try_send(notification) {
+                log::error!("[backend] Dropping subscription: id={:?}: {}", subscription_id, err);
+                manager
+                    .remove_active_subscription(request_id, subscription_id)
+                    .expect("kind is ActiveSubscription; qed");
+            }
+        }
+        None => log::error!(
+            "[backend] Subscription id ({:?}) is not an active subscription",
+            subscription_id
+        ),
+    }
+}
@@ -0,0 +1,43 @@
+mod builder;
+mod frontend;
+mod handler;
+pub(crate) mod manager;
+mod task;
+#[cfg(test)]
+mod tests;
+
+use jsonrpc_types::v2::*;
+
+pub use self::{builder::DuplexClientBuilder, frontend::DuplexSubscription, handler::IncomingHandler};
+pub(crate) use self::{
+    frontend::{impl_duplex_transports, Frontend},
+    task::{CancelNotification, DuplexTask, UnsubscribeMethod},
+};
+use crate::error::DuplexClientError;
+
+/// Duplex JSON-RPC client, which sends and receives the messages over any message-oriented
+/// channel (e.g. in-memory pipes, QUIC streams or serial ports), supplied as a
+/// `S: Stream<Item = String> + Sink<String>` or a pair of sink and stream.
+///
+/// The requests and subscriptions are multiplexed over the channel, and the requests and
+/// notifications sent by the server are handled by the `IncomingHandler` of the client.
+#[derive(Clone)]
+pub struct DuplexClient {
+    frontend: Frontend<DuplexClientError>,
+}
+
+impl DuplexClient {
+    /// Creates a `DuplexClientBuilder` to configure a `DuplexClient`.
+    ///
+    /// This is the same as `DuplexClientBuilder::new()`.
+    pub fn builder() -> DuplexClientBuilder {
+        DuplexClientBuilder::new()
+    }
+
+    /// Sends a notification to the server, which returns once the notification is sent.
+    pub async fn notify(&self, method: impl Into<String>, params: Option<Params>) -> Result<(), DuplexClientError> {
+        self.frontend.send_notification(method, params).await
+    }
+}
+
+impl_duplex_transports!(DuplexClient, DuplexClientError);
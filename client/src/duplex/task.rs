@@ -0,0 +1,364 @@
+use std::sync::Arc;
+
+use futures::{
+    channel::mpsc,
+    future::{BoxFuture, FutureExt},
+    sink::{Sink, SinkExt},
+    stream::{FuturesUnordered, Stream, StreamExt},
+};
+use jsonrpc_types::v2::*;
+
+use crate::duplex::{
+    handler::IncomingHandler,
+    manager::{
+        handle_response_message, handle_subscription_notification_message, TaskError, TaskManager, ToBackTaskMessage,
+    },
+};
+
+/// Builds the notification sent to the server when a pending request is canceled.
+pub(crate) type CancelNotification = Box<dyn Fn(&Id) -> Notification + Send + Sync>;
+
+/// Gets the method to close a subscription on the server by its subscribe method.
+pub(crate) type UnsubscribeMethod = Box<dyn Fn(&str) -> Option<String> + Send + Sync>;
+
+/// Replaces `subscribe` with `unsubscribe` in the subscribe method, e.g. `eth_subscribe` to
+/// `eth_unsubscribe` or `chain_subscribeNewHeads` to `chain_unsubscribeNewHeads`.
+fn default_unsubscribe_method(subscribe_method: &str) -> Option<String> {
+    if subscribe_method.contains("subscribe") {
+        Some(subscribe_method.replacen("subscribe", "unsubscribe", 1))
+    } else {
+        None
+    }
+}
+
+/// The handling of a request or notification sent by the server, which returns the response to send back.
+type Handling = BoxFuture<'static, Option<String>>;
+
+/// Responds `Method not found` errors to all requests sent by the server.
+struct DefaultHandler;
+
+impl IncomingHandler for DefaultHandler {}
+
+struct DuplexSender<Tx> {
+    sink: Tx,
+}
+
+impl<E, Tx> DuplexSender<Tx>
+where
+    Tx: Sink<String, Error = E> + Unpin,
+{
+    async fn send_message(&mut self, msg: String) -> Result<(), E> {
+        log::trace!("[backend] Send message: {}", msg);
+        self.sink.feed(msg).await?;
+        self.sink.flush().await
+    }
+
+    async fn send_request(&mut self, id: Id, method: impl Into<String>, params: Option<Params>) -> Result<(), E> {
+        let call = Request::new(method, params, id);
+        let request = serde_json::to_string(&call).expect("serialize call; qed");
+        log::debug!("[backend] Send a method call: {}", request);
+        self.send_message(request).await
+    }
+
+    async fn send_batch_request<I, M>(&mut self, batch: I) -> Result<(), E>
+    where
+        I: IntoIterator<Item = (Id, M, Option<Params>)>,
+        M: Into<String>,
+    {
+        let calls = batch
+            .into_iter()
+            .map(|(id, method, params)| Request::new(method, params, id))
+            .collect::<Vec<_>>();
+        let request = RequestObj::Batch(calls);
+        let request = serde_json::to_string(&request).expect("serialize calls; qed");
+        log::debug!("[backend] Send a batch of method calls: {}", request);
+        self.send_message(request).await
+    }
+
+    async fn send_notification(&mut self, notification: Notification) -> Result<(), E> {
+        let notification = serde_json::to_string(&notification).expect("serialize notification; qed");
+        log::debug!("[backend] Send a notification: {}", notification);
+        self.send_message(notification).await
+    }
+
+    async fn stop_subscription(
+        &mut self,
+        id: Id,
+        unsubscribe_method: impl Into<String>,
+        subscription_id: Id,
+    ) -> Result<(), E> {
+        let subscription_id = serde_json::to_value(subscription_id).expect("serialize Id");
+        let params = Params::Array(vec![subscription_id]);
+        self.send_request(id, unsubscribe_method, Some(params)).await
+    }
+}
+
+/// Helper struct for multiplexing the requests and subscriptions over a message-oriented connection.
+pub(crate) struct DuplexTask<E> {
+    manager: TaskManager<E>,
+    handler: Arc<dyn IncomingHandler>,
+    cancel_notification: Option<CancelNotification>,
+    unsubscribe_method: UnsubscribeMethod,
+}
+
+impl<E: TaskError> DuplexTask<E> {
+    pub(crate) fn new(max_capacity_per_subscription: usize) -> Self {
+        Self {
+            manager: TaskManager::new(max_capacity_per_subscription),
+            handler: Arc::new(DefaultHandler),
+            cancel_notification: None,
+            unsubscribe_method: Box::new(default_unsubscribe_method),
+        }
+    }
+
+    /// Sets the handler of the requests and notifications sent by the server.
+    pub(crate) fn handler(mut self, handler: Option<Arc<dyn IncomingHandler>>) -> Self {
+        if let Some(handler) = handler {
+            self.handler = handler;
+        }
+        self
+    }
+
+    /// Sets the builder of the notification sent to the server when a pending request is canceled.
+    pub(crate) fn cancel_notification(mut self, cancel_notification: Option<CancelNotification>) -> Self {
+        self.cancel_notification = cancel_notification;
+        self
+    }
+
+    /// Sets the function to get the method to close a subscription on the server, when the
+    /// subscription is canceled after the server has answered it.
+    pub(crate) fn unsubscribe_method(mut self, unsubscribe_method: Option<UnsubscribeMethod>) -> Self {
+        if let Some(unsubscribe_method) = unsubscribe_method {
+            self.unsubscribe_method = unsubscribe_method;
+        }
+        self
+    }
+
+    /// Convert self into a spawnable runtime task that processes message sent from the frontend and
+    /// received from backend.
+    ///
+    /// The messages are sent by the `sink` and received from the `stream`, the task is terminated
+    /// when any error occurs or the `stream` is ended.
+    pub(crate) async fn into_task<Tx, Rx>(self, sink: Tx, stream: Rx, from_front: mpsc::Receiver<ToBackTaskMessage<E>>)
+    where
+        Tx: Sink<String, Error = E>,
+        Rx: Stream<Item = Result<String, E>>,
+    {
+        let Self {
+            mut manager,
+            handler,
+            cancel_notification,
+            unsubscribe_method,
+        } = self;
+        let mut handlings = FuturesUnordered::<Handling>::new();
+
+        futures::pin_mut!(sink, from_front);
+        let from_back = stream.fuse();
+        futures::pin_mut!(from_back);
+        let mut sender = DuplexSender { sink };
+
+        loop {
+            futures::select! {
+                msg = from_front.next() => match msg {
+                    Some(msg) => handle_from_front_message(
+                        msg,
+                        &mut manager,
+                        &mut sender,
+                        cancel_notification.as_ref(),
+                        &unsubscribe_method,
+                    ).await,
+                    None => {
+                        log::debug!("[backend] Frontend channel dropped; terminate client");
+                        break;
+                    }
+                },
+                msg = from_back.next() => match msg {
+                    Some(Ok(msg)) => if let Err(err) = handle_from_back_message(msg, &mut manager, &handler, &mut handlings) {
+                        log::error!("[backend] Handle message error: {}; terminate client", err);
+                        break;
+                    }
+                    Some(Err(err)) => {
+                        log::error!("[backend] Receive message error: {}; terminate client", err);
+                        break;
+                    }
+                    None => {
+                        log::debug!("[backend] Connection closed; terminate client");
+                        break;
+                    }
+                },
+                response = handlings.select_next_some() => if let Some(response) = response {
+                    if let Err(err) = sender.send_message(response).await {
+                        log::error!("[backend] Send response error: {}; terminate client", err);
+                        break;
+                    }
+                },
+            }
+        }
+    }
+}
+
+async fn handle_from_front_message<E, Tx>(
+    msg: ToBackTaskMessage<E>,
+    manager: &mut TaskManager<E>,
+    sender: &mut DuplexSender<Tx>,
+    cancel_notification: Option<&CancelNotification>,
+    unsubscribe_method: &UnsubscribeMethod,
+) where
+    E: TaskError,
+    Tx: Sink<String, Error = E> + Unpin,
+{
+    match msg {
+        ToBackTaskMessage::Request {
+            id,
+            method,
+            params,
+            send_back,
+        } => match sender.send_request(id.clone(), method, params).await {
+            Ok(()) => {
+                if let Err(send_back) = manager.insert_pending_method_call(id, send_back) {
+                    let _ = send_back.send(Err(E::duplicate_request_id()));
+                }
+            }
+            Err(err) => {
+                log::warn!("[backend] Send request error: {}", err);
+                let _ = send_back.send(Err(err));
+            }
+        },
+        ToBackTaskMessage::BatchRequest { batch, send_back } => {
+            let min_request_id = batch.iter().map(|(id, _, _)| id).min().cloned().expect("must have one");
+            match sender.send_batch_request(batch).await {
+                Ok(()) => {
+                    if let Err(send_back) = manager.insert_pending_batch_method_call(min_request_id, send_back) {
+                        let _ = send_back.send(Err(E::duplicate_request_id()));
+                    }
+                }
+                Err(err) => {
+                    log::warn!("[backend] Send a batch of requests error: {}", err);
+                    let _ = send_back.send(Err(err));
+                }
+            }
+        }
+        ToBackTaskMessage::Subscribe {
+            id,
+            subscribe_method,
+            params,
+            send_back,
+        } => {
+            let unsubscribe_method = unsubscribe_method(&subscribe_method);
+            match sender.send_request(id.clone(), subscribe_method, params).await {
+                Ok(()) => {
+                    if let Err(send_back) = manager.insert_pending_subscription(id, unsubscribe_method, send_back) {
+                        let _ = send_back.send(Err(E::duplicate_request_id()));
+                    }
+                }
+                Err(err) => {
+                    log::warn!("[backend] Send subscription request error: {}", err);
+                    let _ = send_back.send(Err(err));
+                }
+            }
+        }
+        ToBackTaskMessage::Unsubscribe {
+            id,
+            unsubscribe_method,
+            subscription_id,
+            send_back,
+        } => match sender
+            .stop_subscription(id.clone(), unsubscribe_method, subscription_id.clone())
+            .await
+        {
+            Ok(()) => {
+                if let Err(send_back) = manager.insert_pending_unsubscribe(id, subscription_id, send_back) {
+                    let _ = send_back.send(Err(E::duplicate_request_id()));
+                }
+            }
+            Err(err) => {
+                log::warn!("[backend] Send unsubscribe request error: {}", err);
+                let _ = send_back.send(Err(err));
+            }
+        },
+        ToBackTaskMessage::Notification {
+            method,
+            params,
+            send_back,
+        } => {
+            let result = sender.send_notification(Notification::new(method, params)).await;
+            if let Err(err) = &result {
+                log::warn!("[backend] Send notification error: {}", err);
+            }
+            let _ = send_back.send(result);
+        }
+        ToBackTaskMessage::Cancel { ids } => {
+            // the batch request is managed by the min id of batch.
+            let min_request_id = ids.iter().min().cloned().expect("must have one");
+            if !manager.cancel_pending_request(min_request_id.clone()) {
+                // the response has been received already, but the subscription must be closed on
+                // the server since its notifications are no longer awaited by the frontend.
+                if let Some((subscription_id, unsubscribe_method)) = manager.cancel_active_subscription(&min_request_id)
+                {
+                    log::debug!("[backend] Cancel active subscription: id={:?}", subscription_id);
+                    if let Some(unsubscribe_method) = unsubscribe_method {
+                        // the ID of the subscribe request is reused, since it's completed, and
+                        // the response of the unsubscribe request is ignored.
+                        if let Err(err) = sender
+                            .stop_subscription(min_request_id, unsubscribe_method, subscription_id)
+                            .await
+                        {
+                            log::warn!("[backend] Send unsubscribe request error: {}", err);
+                        }
+                    }
+                }
+                return;
+            }
+            log::debug!("[backend] Cancel pending requests: ids={:?}", ids);
+            if let Some(cancel_notification) = cancel_notification {
+                for id in ids {
+                    let notification = cancel_notification(&id);
+                    if let Err(err) = sender.send_notification(notification).await {
+                        log::warn!("[backend] Send cancel notification error: {}", err);
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn handle_from_back_message<E: TaskError>(
+    msg: String,
+    manager: &mut TaskManager<E>,
+    handler: &Arc<dyn IncomingHandler>,
+    handlings: &mut FuturesUnordered<Handling>,
+) -> Result<(), E> {
+    if let Ok(response) = serde_json::from_str::<ResponseObj>(&msg) {
+        handle_response_message(response, manager)?
+    } else if let Ok(notification) = serde_json::from_str::<SubscriptionNotification>(&msg) {
+        handle_subscription_notification_message(notification, manager);
+    } else if let Ok(request) = serde_json::from_str::<Request>(&msg) {
+        log::debug!("[backend] Receive a request: {}", msg);
+        let handler = handler.clone();
+        handlings.push(
+            async move {
+                let response = match handler.handle_request(request.method, request.params).await {
+                    Ok(result) => Response::success(result, request.id),
+                    Err(err) => Response::failure(err, Some(request.id)),
+                };
+                Some(serde_json::to_string(&response).expect("serialize response; qed"))
+            }
+            .boxed(),
+        );
+    } else if let Ok(notification) = serde_json::from_str::<Notification>(&msg) {
+        log::debug!("[backend] Receive a notification: {}", msg);
+        let handler = handler.clone();
+        handlings.push(
+            async move {
+                handler
+                    .handle_notification(notification.method, notification.params)
+                    .await;
+                None
+            }
+            .boxed(),
+        );
+    } else {
+        log::warn!("[backend] Ignore unknown message: {}", msg);
+    }
+    Ok(())
+}
@@ -0,0 +1,140 @@
+use futures::{channel::mpsc, sink::SinkExt, stream::StreamExt};
+
+use super::*;
+use crate::transport::{BatchTransport, PubsubTransport, Transport};
+
+/// Responds the requests sent by the server with the method names.
+struct MethodHandler;
+
+#[async_trait::async_trait]
+impl IncomingHandler for MethodHandler {
+    async fn handle_request(&self, method: String, _params: Option<Params>) -> Result<Value, Error> {
+        Ok(Value::from(method))
+    }
+}
+
+#[tokio::test]
+async fn make_jsonrpc_requests_over_channel() {
+    let (client_tx, mut server_rx) = mpsc::channel::<String>(16);
+    let (mut server_tx, client_rx) = mpsc::channel::<String>(16);
+    let (client, task) = DuplexClient::builder()
+        .handler(MethodHandler)
+        .build_with_split(client_tx, client_rx);
+    tokio::spawn(task);
+
+    tokio::spawn(async move {
+        while let Some(request) = server_rx.next().await {
+            let responses: &[&str] = match request.as_str() {
+                r#"{"jsonrpc":"2.0","method":"foo","id":1}"# => &[r#"{"jsonrpc":"2.0","id":1,"result":"x"}"#],
+                r#"[{"jsonrpc":"2.0","method":"foo","id":2},{"jsonrpc":"2.0","method":"bar","id":3}]"# => {
+                    &[r#"[{"jsonrpc":"2.0","id":3,"result":"y"},{"jsonrpc":"2.0","id":2,"result":"x"}]"#]
+                }
+                r#"{"jsonrpc":"2.0","method":"subscribe","id":4}"# => &[
+                    r#"{"jsonrpc":"2.0","id":4,"result":"0x1"}"#,
+                    r#"{"jsonrpc":"2.0","method":"notify","params":{"subscription":"0x1","result":"z"}}"#,
+                ],
+                r#"{"jsonrpc":"2.0","method":"unsubscribe","params":["0x1"],"id":5}"# => {
+                    &[r#"{"jsonrpc":"2.0","id":5,"result":true}"#]
+                }
+                // the notification of client is answered by a request of server.
+                r#"{"jsonrpc":"2.0","method":"ping"}"# => &[r#"{"jsonrpc":"2.0","method":"pong","id":"s1"}"#],
+                r#"{"jsonrpc":"2.0","result":"pong","id":"s1"}"# => &[r#"{"jsonrpc":"2.0","id":6,"result":"done"}"#],
+                r#"{"jsonrpc":"2.0","method":"wait","id":6}"# => &[],
+                request => panic!("unexpected request: {}", request),
+            };
+            for response in responses {
+                server_tx.send(response.to_string()).await.unwrap();
+            }
+        }
+    });
+
+    let response = client.request("foo", None).await.unwrap();
+    assert_eq!(response, Response::success("x".into(), 1.into()));
+
+    let response = client.request_batch(vec![("foo", None), ("bar", None)]).await.unwrap();
+    assert_eq!(
+        response,
+        vec![
+            Response::success("y".into(), 3.into()),
+            Response::success("x".into(), 2.into()),
+        ]
+    );
+
+    let (id, mut subscription) = client.subscribe("subscribe", None).await.unwrap();
+    assert_eq!(id, Id::Str("0x1".into()));
+    let notification = subscription.next().await.unwrap();
+    assert_eq!(notification.params.result, Value::from("z"));
+    assert!(client.unsubscribe("unsubscribe", id).await.unwrap());
+
+    // the response of `wait` request is sent after the server receives the response of handler.
+    let wait = {
+        let client = client.clone();
+        tokio::spawn(async move { client.request("wait", None).await })
+    };
+    tokio::task::yield_now().await;
+    client.notify("ping", None).await.unwrap();
+    let response = wait.await.unwrap().unwrap();
+    assert_eq!(response, Response::success("done".into(), 6.into()));
+}
+
+#[tokio::test]
+async fn terminate_on_empty_batch_response() {
+    let (client_tx, mut server_rx) = mpsc::channel::<String>(16);
+    let (mut server_tx, client_rx) = mpsc::channel::<String>(16);
+    let (client, task) = DuplexClient::builder().build_with_split(client_tx, client_rx);
+    let task = tokio::spawn(task);
+
+    tokio::spawn(async move {
+        while server_rx.next().await.is_some() {
+            let _ = server_tx.send("[]".to_string()).await;
+        }
+    });
+
+    let result = client.request_batch(vec![("foo", None), ("bar", None)]).await;
+    assert!(matches!(result, Err(DuplexClientError::InternalChannel)));
+    // the task is terminated by the invalid response instead of panicking.
+    task.await.unwrap();
+}
+
+#[tokio::test]
+async fn unsubscribe_on_dropping_answered_subscription() {
+    let (client_tx, mut server_rx) = mpsc::channel::<String>(16);
+    let (mut server_tx, client_rx) = mpsc::channel::<String>(16);
+    let (client, task) = DuplexClient::builder().build_with_split(client_tx, client_rx);
+    tokio::spawn(task);
+
+    let server = tokio::spawn(async move {
+        let mut requests = Vec::new();
+        while let Some(request) = server_rx.next().await {
+            let response = match request.as_str() {
+                r#"{"jsonrpc":"2.0","method":"subscribe","id":1}"# => r#"{"jsonrpc":"2.0","id":1,"result":"0x1"}"#,
+                r#"{"jsonrpc":"2.0","method":"foo","id":2}"# => r#"{"jsonrpc":"2.0","id":2,"result":"x"}"#,
+                r#"{"jsonrpc":"2.0","method":"unsubscribe","params":["0x1"],"id":1}"# => {
+                    r#"{"jsonrpc":"2.0","id":1,"result":true}"#
+                }
+                r#"{"jsonrpc":"2.0","method":"bar","id":3}"# => r#"{"jsonrpc":"2.0","id":3,"result":"y"}"#,
+                request => panic!("unexpected request: {}", request),
+            };
+            requests.push(request);
+            server_tx.send(response.to_string()).await.unwrap();
+        }
+        requests
+    });
+
+    let mut subscribe = Box::pin(client.subscribe("subscribe", None));
+    assert!(futures::poll!(subscribe.as_mut()).is_pending());
+    // the subscribe response is handled before the response of the next request.
+    let response = client.request("foo", None).await.unwrap();
+    assert_eq!(response, Response::success("x".into(), 2.into()));
+    // drop the subscribe future, whose response has been received but not polled.
+    drop(subscribe);
+    let response = client.request("bar", None).await.unwrap();
+    assert_eq!(response, Response::success("y".into(), 3.into()));
+
+    drop(client);
+    let requests = server.await.unwrap();
+    assert_eq!(
+        requests[2],
+        r#"{"jsonrpc":"2.0","method":"unsubscribe","params":["0x1"],"id":1}"#
+    );
+}
@@ -1,52 +1,502 @@
+/// The error type for the transports used as trait objects, which wraps the error of the
+/// underlying transport.
+#[derive(Debug, thiserror::Error)]
+#[error(transparent)]
+pub struct DynTransportError(Box<dyn std::error::Error + Send + Sync>);
+
+impl DynTransportError {
+    /// Wraps the error of the underlying transport.
+    pub fn new<E: std::error::Error + Send + Sync + 'static>(error: E) -> Self {
+        Self(Box::new(error))
+    }
+
+    /// Returns a reference to the error of the underlying transport if it's of type `E`.
+    pub fn downcast_ref<E: std::error::Error + 'static>(&self) -> Option<&E> {
+        self.0.downcast_ref()
+    }
+
+    /// Consumes the error, returning the error of the underlying transport.
+    pub fn into_inner(self) -> Box<dyn std::error::Error + Send + Sync> {
+        self.0
+    }
+}
+
+/// The error type shared by all transports, which the error of each transport can be converted into,
+/// so that the failures can be handled uniformly by the code generic over transports.
+#[derive(Debug, thiserror::Error)]
+pub enum ClientError {
+    /// Error of the underlying transport, e.g. HTTP, WebSocket or IO error.
+    #[error(transparent)]
+    Transport(Box<dyn std::error::Error + Send + Sync>),
+    /// Json serialization/deserialization error.
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+    /// Request timeout.
+    #[error("Request timeout")]
+    Timeout,
+    /// The response or notification violates the JSON-RPC protocol, e.g. an unknown request ID.
+    #[error("Protocol violation: {0}")]
+    Protocol(String),
+    /// JSON-RPC error returned by the server.
+    #[error(transparent)]
+    Rpc(#[from] jsonrpc_types::v2::Error),
+}
+
+impl ClientError {
+    /// Wraps the error of the underlying transport.
+    pub fn transport<E: std::error::Error + Send + Sync + 'static>(error: E) -> Self {
+        Self::Transport(Box::new(error))
+    }
+
+    /// Converts the response into the result, the `Failure` response is converted into `ClientError::Rpc`.
+    pub fn from_response(response: jsonrpc_types::v2::Response) -> Result<jsonrpc_types::v2::Value, Self> {
+        Ok(Result::from(response)?)
+    }
+}
+
+impl From<DynTransportError> for ClientError {
+    fn from(err: DynTransportError) -> Self {
+        match err.into_inner().downcast::<ClientError>() {
+            Ok(err) => *err,
+            Err(err) => Self::Transport(err),
+        }
+    }
+}
+
 /// The error type for rpc transport.
-#[cfg(any(feature = "http-async-std", feature = "http-tokio"))]
+#[cfg(any(feature = "http-async-std", feature = "http-tokio", feature = "http-hyper"))]
 #[derive(Debug, thiserror::Error)]
 pub enum HttpClientError {
     /// Json serialization/deserialization error.
     #[error(transparent)]
     Json(#[from] serde_json::Error),
 
-    /// HTTP error.
-    #[cfg(feature = "http-async-std")]
+    /// HTTP error returned by the HTTP backend.
     #[error(transparent)]
-    Http(#[from] anyhow::Error),
+    Http(Box<dyn std::error::Error + Send + Sync>),
 
-    /// HTTP error.
-    #[cfg(feature = "http-tokio")]
-    #[error(transparent)]
-    Http(#[from] reqwest::Error),
+    /// HTTP request timeout.
+    #[error("HTTP request timeout")]
+    RequestTimeout,
+
+    /// The HTTP response body exceeds the limit of `max_response_size`.
+    #[error("HTTP response body exceeds the limit of {limit} bytes")]
+    ResponseTooLarge {
+        /// The maximum size of response body.
+        limit: usize,
+    },
+
+    /// The request still failed after retries.
+    #[error("HTTP request failed after {retries} retries: {error}")]
+    Retry {
+        /// The number of retries.
+        retries: usize,
+        /// The error of the last attempt.
+        #[source]
+        error: Box<HttpClientError>,
+    },
+
+    /// Invalid HTTP client configuration, e.g. an invalid URL.
+    #[error("Invalid HTTP client configuration: {0}")]
+    InvalidConfig(String),
+
+    /// Unexpected HTTP response status, whose body is not a JSON-RPC response.
+    #[error("Unexpected HTTP response status: {code}")]
+    Status {
+        /// HTTP response status code.
+        code: http::StatusCode,
+        /// HTTP response body.
+        body: String,
+    },
+}
+
+/// Defines the error type of a transport based on `DuplexClient`, which differs from the others
+/// only in the error of the underlying transport, and implements `TaskError` and the conversion into
+/// `ClientError` for it.
+#[cfg(feature = "duplex")]
+macro_rules! duplex_client_error {
+    (
+        $(#[$meta:meta])*
+        pub enum $error:ident {
+            $(#[$transport_meta:meta])*
+            $transport:ident($(#[$transport_field_meta:meta])* $source:ty) => $into_client_error:expr,
+            timeout = $timeout:literal,
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Debug, thiserror::Error)]
+        pub enum $error {
+            /// Json serialization/deserialization error.
+            #[error(transparent)]
+            Json(#[from] serde_json::Error),
+            $(#[$transport_meta])*
+            #[error(transparent)]
+            $transport($(#[$transport_field_meta])* $source),
+            #[doc = concat!($timeout, ".")]
+            #[error($timeout)]
+            RequestTimeout,
+            /// Duplicate request ID.
+            #[error("Duplicate request ID")]
+            DuplicateRequestId,
+            /// Invalid Request ID.
+            #[error("Invalid request ID")]
+            InvalidRequestId,
+            /// Invalid Subscription ID.
+            #[error("Invalid subscription ID")]
+            InvalidSubscriptionId,
+            /// Invalid Unsubscribe request result.
+            #[error("Invalid Unsubscribe result")]
+            InvalidUnsubscribeResult,
+            /// Internal channel error
+            #[error("Internal channel error")]
+            InternalChannel,
+        }
+
+        impl crate::duplex::manager::TaskError for $error {
+            fn duplicate_request_id() -> Self {
+                Self::DuplicateRequestId
+            }
+
+            fn invalid_request_id() -> Self {
+                Self::InvalidRequestId
+            }
+
+            fn invalid_subscription_id() -> Self {
+                Self::InvalidSubscriptionId
+            }
+
+            fn invalid_unsubscribe_result() -> Self {
+                Self::InvalidUnsubscribeResult
+            }
+
+            fn request_timeout() -> Self {
+                Self::RequestTimeout
+            }
+
+            fn internal_channel() -> Self {
+                Self::InternalChannel
+            }
+        }
+
+        impl From<$error> for ClientError {
+            fn from(err: $error) -> Self {
+                match err {
+                    $error::Json(err) => Self::Json(err),
+                    $error::$transport(err) => $into_client_error(err),
+                    $error::RequestTimeout => Self::Timeout,
+                    $error::DuplicateRequestId
+                    | $error::InvalidRequestId
+                    | $error::InvalidSubscriptionId
+                    | $error::InvalidUnsubscribeResult => Self::Protocol(err.to_string()),
+                    $error::InternalChannel => Self::transport(err),
+                }
+            }
+        }
+    };
 }
 
 /// WebSocket error type.
 #[cfg(any(feature = "ws-async-std", feature = "ws-tokio"))]
 pub use async_tungstenite::tungstenite::Error as WsError;
 
-/// The error type for websocket rpc transport.
 #[cfg(any(feature = "ws-async-std", feature = "ws-tokio"))]
+duplex_client_error! {
+    /// The error type for websocket rpc transport.
+    pub enum WsClientError {
+        /// WebSocket protocol error.
+        WebSocket(#[from] WsError) => ClientError::transport,
+        timeout = "WebSocket request timeout",
+    }
+}
+
+#[cfg(any(feature = "ipc-async-std", feature = "ipc-tokio"))]
+duplex_client_error! {
+    /// The error type for IPC rpc transport.
+    pub enum IpcClientError {
+        /// IO error of the IPC connection.
+        Io(#[from] std::io::Error) => ClientError::transport,
+        timeout = "IPC request timeout",
+    }
+}
+
+#[cfg(any(feature = "tcp-async-std", feature = "tcp-tokio"))]
+duplex_client_error! {
+    /// The error type for TCP rpc transport.
+    pub enum TcpClientError {
+        /// IO error of the TCP connection.
+        Io(#[from] std::io::Error) => ClientError::transport,
+        timeout = "TCP request timeout",
+    }
+}
+
+#[cfg(any(feature = "stdio-async-std", feature = "stdio-tokio"))]
+duplex_client_error! {
+    /// The error type for stdio rpc transport.
+    pub enum StdioClientError {
+        /// IO error of the stdio connection.
+        Io(#[from] std::io::Error) => ClientError::transport,
+        timeout = "Stdio request timeout",
+    }
+}
+
+#[cfg(feature = "duplex")]
+duplex_client_error! {
+    /// The error type for duplex rpc transport.
+    pub enum DuplexClientError {
+        /// Error of the underlying message channel.
+        Transport(Box<dyn std::error::Error + Send + Sync>) => ClientError::Transport,
+        timeout = "Duplex request timeout",
+    }
+}
+
+/// The error type for local rpc transport.
+#[cfg(feature = "local")]
 #[derive(Debug, thiserror::Error)]
-pub enum WsClientError {
-    /// Json serialization/deserialization error.
+pub enum LocalTransportError {
+    /// Json serialization/deserialization error, which only occurs when the messages are serialized.
     #[error(transparent)]
     Json(#[from] serde_json::Error),
-    /// WebSocket protocol error.
+    /// Invalid Subscription ID.
+    #[error("Invalid subscription ID")]
+    InvalidSubscriptionId,
+    /// Invalid Unsubscribe request result.
+    #[error("Invalid Unsubscribe result")]
+    InvalidUnsubscribeResult,
+}
+
+/// The error type for mock rpc transport.
+#[cfg(feature = "mock")]
+#[derive(Debug, thiserror::Error)]
+pub enum MockTransportError {
+    /// Json serialization/deserialization error.
     #[error(transparent)]
-    WebSocket(#[from] WsError),
-    /// WebSocket request timeout.
-    #[error("WebSocket request timeout")]
-    RequestTimeout,
-    /// Duplicate request ID.
-    #[error("Duplicate request ID")]
-    DuplicateRequestId,
-    /// Invalid Request ID.
-    #[error("Invalid request ID")]
-    InvalidRequestId,
+    Json(#[from] serde_json::Error),
+    /// The request doesn't match any expectation.
+    #[error("Unexpected request: {0}")]
+    UnexpectedRequest(jsonrpc_types::v2::Request),
     /// Invalid Subscription ID.
     #[error("Invalid subscription ID")]
     InvalidSubscriptionId,
     /// Invalid Unsubscribe request result.
     #[error("Invalid Unsubscribe result")]
     InvalidUnsubscribeResult,
-    /// Internal channel error
-    #[error("Internal channel error")]
-    InternalChannel,
+}
+
+/// The error type for record rpc transport.
+#[cfg(feature = "cassette")]
+#[derive(Debug, thiserror::Error)]
+pub enum RecordTransportError<E> {
+    /// Error of the inner transport.
+    #[error(transparent)]
+    Transport(E),
+    /// Json serialization/deserialization error.
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+    /// Error of writing the cassette.
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+/// The error type for replay rpc transport.
+#[cfg(feature = "cassette")]
+#[derive(Debug, thiserror::Error)]
+pub enum ReplayTransportError {
+    /// Json serialization/deserialization error.
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+    /// Error of reading the cassette.
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    /// The request is not recorded in the cassette, or has been replayed.
+    #[error("Unrecorded request: {0}")]
+    UnrecordedRequest(String),
+    /// The recorded response doesn't match the request, e.g. a batch response of a single request.
+    #[error("Unexpected response recorded in the cassette")]
+    UnexpectedResponse,
+    /// Invalid Unsubscribe request result.
+    #[error("Invalid Unsubscribe result")]
+    InvalidUnsubscribeResult,
+}
+
+/// The error type for the client of any transport.
+#[cfg(any(
+    feature = "http-async-std",
+    feature = "http-tokio",
+    feature = "http-hyper",
+    feature = "ws-async-std",
+    feature = "ws-tokio",
+    feature = "ipc-async-std",
+    feature = "ipc-tokio"
+))]
+#[derive(Debug, thiserror::Error)]
+pub enum AnyClientError {
+    /// HTTP client error.
+    #[cfg(any(feature = "http-async-std", feature = "http-tokio", feature = "http-hyper"))]
+    #[error(transparent)]
+    Http(#[from] HttpClientError),
+    /// WebSocket client error.
+    #[cfg(any(feature = "ws-async-std", feature = "ws-tokio"))]
+    #[error(transparent)]
+    Ws(#[from] WsClientError),
+    /// IPC client error.
+    #[cfg(any(feature = "ipc-async-std", feature = "ipc-tokio"))]
+    #[error(transparent)]
+    Ipc(#[from] IpcClientError),
+    /// The scheme of the url is not supported, or its feature is not enabled.
+    #[error("Unsupported url scheme: {0}")]
+    UnsupportedScheme(String),
+    /// The transport of the client doesn't support subscriptions, e.g. HTTP.
+    #[error("Subscription is not supported by the transport")]
+    SubscriptionUnsupported,
+}
+
+#[cfg(any(feature = "http-async-std", feature = "http-tokio", feature = "http-hyper"))]
+impl From<HttpClientError> for ClientError {
+    fn from(err: HttpClientError) -> Self {
+        match err {
+            HttpClientError::Json(err) => Self::Json(err),
+            HttpClientError::Http(err) => Self::Transport(err),
+            HttpClientError::RequestTimeout => Self::Timeout,
+            // The error of the last attempt decides the kind of failure.
+            HttpClientError::Retry { error, .. } => Self::from(*error),
+            HttpClientError::Status { .. } => Self::Protocol(err.to_string()),
+            err @ HttpClientError::ResponseTooLarge { .. } | err @ HttpClientError::InvalidConfig(_) => {
+                Self::transport(err)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "local")]
+impl From<LocalTransportError> for ClientError {
+    fn from(err: LocalTransportError) -> Self {
+        match err {
+            LocalTransportError::Json(err) => Self::Json(err),
+            LocalTransportError::InvalidSubscriptionId | LocalTransportError::InvalidUnsubscribeResult => {
+                Self::Protocol(err.to_string())
+            }
+        }
+    }
+}
+
+#[cfg(feature = "mock")]
+impl From<MockTransportError> for ClientError {
+    fn from(err: MockTransportError) -> Self {
+        match err {
+            MockTransportError::Json(err) => Self::Json(err),
+            MockTransportError::UnexpectedRequest(_) => Self::transport(err),
+            MockTransportError::InvalidSubscriptionId | MockTransportError::InvalidUnsubscribeResult => {
+                Self::Protocol(err.to_string())
+            }
+        }
+    }
+}
+
+#[cfg(feature = "cassette")]
+impl<E: Into<ClientError>> From<RecordTransportError<E>> for ClientError {
+    fn from(err: RecordTransportError<E>) -> Self {
+        match err {
+            RecordTransportError::Transport(err) => err.into(),
+            RecordTransportError::Json(err) => Self::Json(err),
+            RecordTransportError::Io(err) => Self::transport(err),
+        }
+    }
+}
+
+#[cfg(feature = "cassette")]
+impl From<ReplayTransportError> for ClientError {
+    fn from(err: ReplayTransportError) -> Self {
+        match err {
+            ReplayTransportError::Json(err) => Self::Json(err),
+            ReplayTransportError::Io(err) => Self::transport(err),
+            ReplayTransportError::UnrecordedRequest(_) => Self::transport(err),
+            ReplayTransportError::UnexpectedResponse | ReplayTransportError::InvalidUnsubscribeResult => {
+                Self::Protocol(err.to_string())
+            }
+        }
+    }
+}
+
+#[cfg(any(
+    feature = "http-async-std",
+    feature = "http-tokio",
+    feature = "http-hyper",
+    feature = "ws-async-std",
+    feature = "ws-tokio",
+    feature = "ipc-async-std",
+    feature = "ipc-tokio"
+))]
+impl From<AnyClientError> for ClientError {
+    fn from(err: AnyClientError) -> Self {
+        match err {
+            #[cfg(any(feature = "http-async-std", feature = "http-tokio", feature = "http-hyper"))]
+            AnyClientError::Http(err) => err.into(),
+            #[cfg(any(feature = "ws-async-std", feature = "ws-tokio"))]
+            AnyClientError::Ws(err) => err.into(),
+            #[cfg(any(feature = "ipc-async-std", feature = "ipc-tokio"))]
+            AnyClientError::Ipc(err) => err.into(),
+            AnyClientError::UnsupportedScheme(_) | AnyClientError::SubscriptionUnsupported => Self::transport(err),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use jsonrpc_types::v2::{Error, Id, Response, Value};
+
+    use super::*;
+
+    #[test]
+    fn client_error_from_response() {
+        let response = Response::success(Value::from(1), Id::Num(1));
+        assert_eq!(ClientError::from_response(response).unwrap(), Value::from(1));
+
+        let response = Response::failure(Error::method_not_found(), Some(Id::Num(1)));
+        match ClientError::from_response(response) {
+            Err(ClientError::Rpc(err)) => assert_eq!(err, Error::method_not_found()),
+            result => panic!("unexpected result: {:?}", result),
+        }
+    }
+
+    #[test]
+    fn client_error_from_dyn_transport_error() {
+        let err = ClientError::from(DynTransportError::new(ClientError::Timeout));
+        assert!(matches!(err, ClientError::Timeout));
+
+        let err = ClientError::from(DynTransportError::new(std::fmt::Error));
+        assert!(matches!(err, ClientError::Transport(err) if err.is::<std::fmt::Error>()));
+    }
+
+    #[cfg(any(feature = "http-async-std", feature = "http-tokio", feature = "http-hyper"))]
+    #[test]
+    fn client_error_from_http_client_error() {
+        let err = HttpClientError::Retry {
+            retries: 3,
+            error: Box::new(HttpClientError::RequestTimeout),
+        };
+        assert!(matches!(ClientError::from(err), ClientError::Timeout));
+
+        let err = HttpClientError::Status {
+            code: http::StatusCode::BAD_GATEWAY,
+            body: String::new(),
+        };
+        assert!(matches!(ClientError::from(err), ClientError::Protocol(_)));
+    }
+
+    #[cfg(any(feature = "ws-async-std", feature = "ws-tokio"))]
+    #[test]
+    fn client_error_from_ws_client_error() {
+        assert!(matches!(
+            ClientError::from(WsClientError::RequestTimeout),
+            ClientError::Timeout
+        ));
+        assert!(matches!(
+            ClientError::from(WsClientError::InvalidRequestId),
+            ClientError::Protocol(msg) if msg == "Invalid request ID"
+        ));
+        assert!(matches!(
+            ClientError::from(WsClientError::WebSocket(WsError::ConnectionClosed)),
+            ClientError::Transport(_)
+        ));
+    }
 }
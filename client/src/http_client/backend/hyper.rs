@@ -0,0 +1,104 @@
+#[cfg(unix)]
+use std::path::PathBuf;
+
+use futures::stream::{self, StreamExt};
+use hyper::{
+    body::HttpBody,
+    client::{connect::Connect, HttpConnector},
+};
+
+#[cfg(unix)]
+use super::UnixConnector;
+use super::{BackendConfig, HttpBackend, ResponseBody};
+use crate::error::HttpClientError;
+
+/// HTTP backend based on `hyper`, which requires the tokio runtime.
+///
+/// The default connector only supports plain HTTP, a `hyper::Client` with a custom connector
+/// (e.g. for TLS) can be used by `HyperBackend::from_client`.
+#[derive(Clone, Debug)]
+pub struct HyperBackend<C = HttpConnector> {
+    client: hyper::Client<C>,
+}
+
+impl HyperBackend {
+    /// Creates a new backend with the default configuration.
+    pub fn new() -> Self {
+        Self::with_config(&BackendConfig::default())
+    }
+
+    pub(crate) fn with_config(config: &BackendConfig) -> Self {
+        let mut connector = HttpConnector::new();
+        connector.set_connect_timeout(config.connect_timeout);
+        connector.set_keepalive(config.tcp_keepalive);
+        connector.set_nodelay(config.tcp_nodelay);
+        Self {
+            client: client_builder(config).build(connector),
+        }
+    }
+}
+
+#[cfg(unix)]
+impl HyperBackend<UnixConnector> {
+    /// Creates a backend that sends all requests over the Unix domain socket at the `path`.
+    pub fn unix<P: Into<PathBuf>>(path: P) -> Self {
+        Self::unix_with_config(path, &BackendConfig::default())
+    }
+
+    pub(crate) fn unix_with_config<P: Into<PathBuf>>(path: P, config: &BackendConfig) -> Self {
+        Self {
+            client: client_builder(config).build(UnixConnector::new(path)),
+        }
+    }
+}
+
+fn client_builder(config: &BackendConfig) -> hyper::client::Builder {
+    let mut builder = hyper::Client::builder();
+    builder.http2_only(config.http2_prior_knowledge);
+    if let Some(timeout) = config.pool_idle_timeout {
+        builder.pool_idle_timeout(timeout);
+    }
+    if let Some(max_idle) = config.pool_max_idle_per_host {
+        builder.pool_max_idle_per_host(max_idle);
+    }
+    builder
+}
+
+impl Default for HyperBackend {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<C> HyperBackend<C> {
+    /// Creates a backend from the given `hyper::Client`, which can be shared with other components.
+    pub fn from_client(client: hyper::Client<C>) -> Self {
+        Self { client }
+    }
+}
+
+#[async_trait::async_trait]
+impl<C> HttpBackend for HyperBackend<C>
+where
+    C: Connect + Clone + Send + Sync + 'static,
+{
+    async fn send(&self, request: http::Request<Vec<u8>>) -> Result<http::Response<ResponseBody>, HttpClientError> {
+        let response = self
+            .client
+            .request(request.map(hyper::Body::from))
+            .await
+            .map_err(|err| HttpClientError::Http(err.into()))?;
+        Ok(response.map(|body| {
+            stream::try_unfold(body, |mut body| async move {
+                match body.data().await {
+                    Some(chunk) => {
+                        let chunk = chunk.map_err(|err| HttpClientError::Http(err.into()))?;
+                        Ok(Some((chunk.to_vec(), body)))
+                    }
+                    None => Ok(None),
+                }
+            })
+            .boxed()
+        }))
+    }
+}
@@ -0,0 +1,93 @@
+#[cfg(feature = "http-hyper")]
+mod hyper;
+#[cfg(feature = "http-tokio")]
+mod reqwest;
+#[cfg(feature = "http-async-std")]
+mod surf;
+#[cfg(all(unix, feature = "http-hyper"))]
+mod unix;
+
+use std::{path::PathBuf, sync::Arc, time::Duration};
+
+#[cfg(feature = "http-hyper")]
+pub use self::hyper::HyperBackend;
+#[cfg(feature = "http-tokio")]
+pub use self::reqwest::ReqwestBackend;
+#[cfg(feature = "http-async-std")]
+pub use self::surf::SurfBackend;
+#[cfg(all(unix, feature = "http-hyper"))]
+pub use self::unix::{UnixConnector, UnixStream};
+use futures::stream::BoxStream;
+
+use crate::error::HttpClientError;
+
+/// The body of HTTP response, which is a stream of data chunks.
+pub type ResponseBody = BoxStream<'static, Result<Vec<u8>, HttpClientError>>;
+
+/// The HTTP stack used by `HttpClient` to send requests.
+///
+/// The backend only sends a single HTTP request, the headers, timeout, redirects and compression
+/// are handled by `HttpClient`, so a backend should neither follow redirects nor decompress responses.
+#[async_trait::async_trait]
+pub trait HttpBackend: Send + Sync {
+    /// Sends the HTTP request and returns the status and headers of the response, whose body is
+    /// read as a stream.
+    async fn send(&self, request: http::Request<Vec<u8>>) -> Result<http::Response<ResponseBody>, HttpClientError>;
+}
+
+/// The connection options of the default backends.
+#[derive(Clone, Debug)]
+pub(crate) struct BackendConfig {
+    pub(crate) connect_timeout: Option<Duration>,
+    pub(crate) http2_prior_knowledge: bool,
+    pub(crate) pool_idle_timeout: Option<Duration>,
+    pub(crate) pool_max_idle_per_host: Option<usize>,
+    pub(crate) tcp_keepalive: Option<Duration>,
+    pub(crate) tcp_nodelay: bool,
+    pub(crate) unix_socket: Option<PathBuf>,
+}
+
+impl Default for BackendConfig {
+    fn default() -> Self {
+        Self {
+            connect_timeout: None,
+            http2_prior_knowledge: false,
+            pool_idle_timeout: None,
+            pool_max_idle_per_host: None,
+            tcp_keepalive: None,
+            tcp_nodelay: true,
+            unix_socket: None,
+        }
+    }
+}
+
+/// Creates the default backend, which is the first enabled one of reqwest, surf and hyper.
+///
+/// The hyper backend is always used for the Unix domain socket.
+pub(crate) fn default_backend(config: &BackendConfig) -> Result<Arc<dyn HttpBackend>, HttpClientError> {
+    if let Some(path) = &config.unix_socket {
+        #[cfg(all(unix, feature = "http-hyper"))]
+        {
+            return Ok(Arc::new(HyperBackend::unix_with_config(path, config)));
+        }
+        #[cfg(not(all(unix, feature = "http-hyper")))]
+        {
+            return Err(HttpClientError::InvalidConfig(format!(
+                "unix socket `{}` requires the `http-hyper` feature on unix",
+                path.display()
+            )));
+        }
+    }
+    #[cfg(feature = "http-tokio")]
+    {
+        Ok(Arc::new(ReqwestBackend::with_config(config)?))
+    }
+    #[cfg(all(feature = "http-async-std", not(feature = "http-tokio")))]
+    {
+        Ok(Arc::new(SurfBackend::with_config(config)?))
+    }
+    #[cfg(all(feature = "http-hyper", not(any(feature = "http-tokio", feature = "http-async-std"))))]
+    {
+        Ok(Arc::new(HyperBackend::with_config(config)))
+    }
+}
@@ -0,0 +1,76 @@
+use futures::stream::{self, StreamExt};
+
+use super::{BackendConfig, HttpBackend, ResponseBody};
+use crate::error::HttpClientError;
+
+/// HTTP backend based on `reqwest`, which requires the tokio runtime.
+#[derive(Clone, Debug)]
+pub struct ReqwestBackend {
+    client: reqwest::Client,
+}
+
+impl ReqwestBackend {
+    /// Creates a new backend with the default configuration.
+    pub fn new() -> Result<Self, HttpClientError> {
+        Self::with_config(&BackendConfig::default())
+    }
+
+    /// Creates a backend from the given `reqwest::Client`.
+    ///
+    /// The client should be built with `reqwest::redirect::Policy::none()` and without decompression,
+    /// since redirects and compression are handled by `HttpClient`.
+    pub fn from_client(client: reqwest::Client) -> Self {
+        Self { client }
+    }
+
+    pub(crate) fn with_config(config: &BackendConfig) -> Result<Self, HttpClientError> {
+        let mut builder = reqwest::Client::builder()
+            .redirect(reqwest::redirect::Policy::none())
+            .tcp_keepalive(config.tcp_keepalive)
+            .tcp_nodelay(config.tcp_nodelay);
+        if let Some(timeout) = config.connect_timeout {
+            builder = builder.connect_timeout(timeout);
+        }
+        if config.http2_prior_knowledge {
+            builder = builder.http2_prior_knowledge();
+        }
+        if let Some(timeout) = config.pool_idle_timeout {
+            builder = builder.pool_idle_timeout(timeout);
+        }
+        if let Some(max_idle) = config.pool_max_idle_per_host {
+            builder = builder.pool_max_idle_per_host(max_idle);
+        }
+        let client = builder.build().map_err(|err| HttpClientError::Http(err.into()))?;
+        Ok(Self { client })
+    }
+}
+
+#[async_trait::async_trait]
+impl HttpBackend for ReqwestBackend {
+    async fn send(&self, request: http::Request<Vec<u8>>) -> Result<http::Response<ResponseBody>, HttpClientError> {
+        let (parts, body) = request.into_parts();
+        let response = self
+            .client
+            .request(parts.method, parts.uri.to_string())
+            .headers(parts.headers)
+            .body(body)
+            .send()
+            .await
+            .map_err(|err| HttpClientError::Http(err.into()))?;
+
+        let mut builder = http::Response::builder().status(response.status());
+        if let Some(headers) = builder.headers_mut() {
+            headers.extend(response.headers().clone());
+        }
+        let body = stream::try_unfold(response, |mut response| async move {
+            let chunk = response
+                .chunk()
+                .await
+                .map_err(|err| HttpClientError::Http(err.into()))?;
+            Ok(chunk.map(|chunk| (chunk.to_vec(), response)))
+        });
+        builder
+            .body(body.boxed())
+            .map_err(|err| HttpClientError::Http(err.into()))
+    }
+}
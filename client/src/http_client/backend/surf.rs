@@ -0,0 +1,100 @@
+use futures::{
+    io::AsyncReadExt,
+    stream::{self, StreamExt},
+};
+use isahc::config::{Configurable, VersionNegotiation};
+
+use super::{BackendConfig, HttpBackend, ResponseBody};
+use crate::error::HttpClientError;
+
+/// HTTP backend based on `surf` with the curl client, which works with the async-std runtime.
+#[derive(Clone, Debug)]
+pub struct SurfBackend {
+    client: surf::Client,
+}
+
+impl SurfBackend {
+    /// Creates a new backend with the default configuration.
+    pub fn new() -> Result<Self, HttpClientError> {
+        Self::with_config(&BackendConfig::default())
+    }
+
+    /// Creates a backend from the given `surf::Client`.
+    ///
+    /// The client should not use the redirect middleware or decompress responses, since redirects
+    /// and compression are handled by `HttpClient`.
+    pub fn from_client(client: surf::Client) -> Self {
+        Self { client }
+    }
+
+    /// The `pool_max_idle_per_host` option is not supported, since the connection cache of curl
+    /// is not limited per host.
+    pub(crate) fn with_config(config: &BackendConfig) -> Result<Self, HttpClientError> {
+        // The response is decompressed by `HttpClient` instead of curl.
+        let mut builder = isahc::HttpClient::builder().automatic_decompression(false);
+        if let Some(timeout) = config.connect_timeout {
+            builder = builder.connect_timeout(timeout);
+        }
+        if config.http2_prior_knowledge {
+            builder = builder.version_negotiation(VersionNegotiation::http2());
+        }
+        if let Some(timeout) = config.pool_idle_timeout {
+            builder = builder.connection_cache_ttl(timeout);
+        }
+        if let Some(interval) = config.tcp_keepalive {
+            builder = builder.tcp_keepalive(interval);
+        }
+        if config.tcp_nodelay {
+            builder = builder.tcp_nodelay();
+        }
+        let client = builder.build().map_err(|err| HttpClientError::Http(err.into()))?;
+        let client = surf::Client::with_http_client(http_client::isahc::IsahcClient::from_client(client));
+        Ok(Self { client })
+    }
+}
+
+#[async_trait::async_trait]
+impl HttpBackend for SurfBackend {
+    async fn send(&self, request: http::Request<Vec<u8>>) -> Result<http::Response<ResponseBody>, HttpClientError> {
+        let (parts, body) = request.into_parts();
+        let method = parts
+            .method
+            .as_str()
+            .parse::<surf::http::Method>()
+            .map_err(|err| HttpClientError::Http(err.into_inner().into()))?;
+        let url = surf::Url::parse(&parts.uri.to_string()).map_err(|err| HttpClientError::Http(err.into()))?;
+
+        let mut builder = surf::RequestBuilder::new(method, url).body(body);
+        for (name, value) in parts.headers.iter() {
+            // The header values of `surf` must be visible ASCII, which is checked by `HttpClient`.
+            let value = value.to_str().map_err(|err| HttpClientError::Http(err.into()))?;
+            builder = builder.header(name.as_str(), value);
+        }
+
+        let mut response = self.client.send(builder).await.map_err(|err| {
+            // Keeps the error of curl to tell the transient errors.
+            match err.downcast::<isahc::Error>() {
+                Ok(err) => HttpClientError::Http(err.into()),
+                Err(err) => HttpClientError::Http(err.into_inner().into()),
+            }
+        })?;
+        let mut builder = http::Response::builder().status(u16::from(response.status()));
+        for (name, values) in response.iter() {
+            for value in values {
+                builder = builder.header(name.as_str(), value.as_str());
+            }
+        }
+        let body = stream::try_unfold(response.take_body().into_reader(), |mut reader| async move {
+            let mut chunk = vec![0; 8 * 1024];
+            let len = reader
+                .read(&mut chunk)
+                .await
+                .map_err(|err| HttpClientError::Http(err.into()))?;
+            chunk.truncate(len);
+            Ok(if len > 0 { Some((chunk, reader)) } else { None })
+        });
+        builder
+            .body(body.boxed())
+            .map_err(|err| HttpClientError::Http(err.into()))
+    }
+}
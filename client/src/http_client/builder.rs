@@ -1,19 +1,55 @@
-use std::{
-    fmt,
-    sync::{atomic::AtomicU64, Arc},
-    time::Duration,
-};
+use std::{fmt, sync::Arc, time::Duration};
 
 use http::header::{self, HeaderMap, HeaderName, HeaderValue};
 
-use crate::{error::HttpClientError, http_client::HttpClient};
+use crate::{
+    error::HttpClientError,
+    http_client::{
+        backend::{self, BackendConfig},
+        check_header_values,
+        compression::Compression,
+        endpoint::Endpoints,
+        CredentialProvider, FailoverStrategy, HttpBackend, HttpClient, RetryPolicy,
+    },
+    id::{IdGenerator, SequentialIdGenerator},
+};
 
 /// A `HttpClientBuilder` can be used to create a `HttpClient` with  custom configuration.
-#[derive(Debug)]
 pub struct HttpClientBuilder {
     pub(crate) headers: HeaderMap,
+    credential_provider: Option<Arc<dyn CredentialProvider>>,
     timeout: Option<Duration>,
-    connect_timeout: Option<Duration>,
+    backend_config: BackendConfig,
+    max_redirects: usize,
+    compression: Compression,
+    stream_response: bool,
+    max_response_size: Option<usize>,
+    retry_policy: Option<RetryPolicy>,
+    failover_strategy: FailoverStrategy,
+    unhealthy_threshold: usize,
+    reprobe_interval: Duration,
+    backend: Option<Arc<dyn HttpBackend>>,
+    id_generator: Arc<dyn IdGenerator>,
+    error: Option<HttpClientError>,
+}
+
+impl fmt::Debug for HttpClientBuilder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HttpClientBuilder")
+            .field("headers", &self.headers)
+            .field("credential_provider", &self.credential_provider.is_some())
+            .field("timeout", &self.timeout)
+            .field("backend_config", &self.backend_config)
+            .field("max_redirects", &self.max_redirects)
+            .field("compression", &self.compression)
+            .field("stream_response", &self.stream_response)
+            .field("max_response_size", &self.max_response_size)
+            .field("retry_policy", &self.retry_policy)
+            .field("failover_strategy", &self.failover_strategy)
+            .field("unhealthy_threshold", &self.unhealthy_threshold)
+            .field("reprobe_interval", &self.reprobe_interval)
+            .finish()
+    }
 }
 
 impl Default for HttpClientBuilder {
@@ -29,8 +65,20 @@ impl HttpClientBuilder {
     pub fn new() -> Self {
         Self {
             headers: HeaderMap::new(),
+            credential_provider: None,
             timeout: None,
-            connect_timeout: None,
+            backend_config: BackendConfig::default(),
+            max_redirects: 10,
+            compression: Compression::default(),
+            stream_response: false,
+            max_response_size: None,
+            retry_policy: None,
+            failover_strategy: FailoverStrategy::default(),
+            unhealthy_threshold: 3,
+            reprobe_interval: Duration::from_secs(30),
+            backend: None,
+            id_generator: Arc::new(SequentialIdGenerator::default()),
+            error: None,
         }
     }
 
@@ -56,27 +104,52 @@ impl HttpClientBuilder {
     }
 
     /// Enable bearer authentication.
-    pub fn bearer_auth<T>(self, token: T) -> Self
+    ///
+    /// If the token is not a valid header value, `build` will return an error.
+    pub fn bearer_auth<T>(mut self, token: T) -> Self
     where
         T: fmt::Display,
     {
         let bearer_auth = format!("Bearer {}", token);
-        let value = HeaderValue::from_str(&bearer_auth).expect("bearer auth header value");
-        self.header(header::AUTHORIZATION, value)
+        match HeaderValue::from_str(&bearer_auth) {
+            Ok(value) => self.header(header::AUTHORIZATION, value),
+            Err(_) => {
+                self.error = Some(HttpClientError::InvalidConfig("invalid bearer token".into()));
+                self
+            }
+        }
+    }
+
+    /// Sets the `User-Agent` header for every request.
+    pub fn user_agent(self, value: HeaderValue) -> Self {
+        self.header(header::USER_AGENT, value)
     }
 
     /// Adds a `Header` for every request.
+    ///
+    /// The header values must be visible ASCII, which is required by all backends.
     pub fn header(mut self, name: HeaderName, value: HeaderValue) -> Self {
         self.headers.insert(name, value);
         self
     }
 
     /// Adds `Header`s for every request.
+    ///
+    /// The header values must be visible ASCII, which is required by all backends.
     pub fn headers(mut self, headers: HeaderMap) -> Self {
         self.headers.extend(headers);
         self
     }
 
+    /// Sets the provider of the credentials, which is invoked for every request (and again for the
+    /// `401 Unauthorized` response) to supply fresh `Authorization` or any other headers.
+    ///
+    /// The headers of the provider override the default headers.
+    pub fn credential_provider<P: CredentialProvider + 'static>(mut self, provider: P) -> Self {
+        self.credential_provider = Some(Arc::new(provider));
+        self
+    }
+
     // ========================================================================
     // Timeout options
     // ========================================================================
@@ -94,46 +167,272 @@ impl HttpClientBuilder {
 
     /// Set a timeout for only the connect phase of a `Client`.
     ///
+    /// It's applied to the default backend only, a custom backend should be configured by itself.
+    ///
     /// Default is `None`.
-    #[cfg(feature = "http-tokio")]
     pub fn connect_timeout(mut self, timeout: Duration) -> Self {
-        self.connect_timeout = Some(timeout);
+        self.backend_config.connect_timeout = Some(timeout);
         self
     }
 
     // ========================================================================
+    // Connection options
+    // ========================================================================
+    //
+    // These options are applied to the default backend only, a custom backend should be configured by itself.
 
-    /// Returns a `HttpClient` that uses this `HttpClientBuilder` configuration.
-    #[cfg(feature = "http-async-std")]
-    pub fn build<U: Into<String>>(self, url: U) -> Result<HttpClient, HttpClientError> {
-        Ok(HttpClient {
-            url: url.into(),
-            id: Arc::new(AtomicU64::new(1)),
-            client: surf::Client::new(),
-            headers: self.headers,
-            timeout: self.timeout,
-        })
+    /// Sends requests with HTTP/2 only, without the HTTP/1.1 upgrade (i.e. HTTP/2 prior knowledge).
+    ///
+    /// Default is disabled.
+    pub fn http2_prior_knowledge(mut self, enable: bool) -> Self {
+        self.backend_config.http2_prior_knowledge = enable;
+        self
+    }
+
+    /// Sets the timeout after which the idle connections in the pool are closed.
+    ///
+    /// Default is the timeout of the backend, e.g. 90 seconds for reqwest and hyper.
+    pub fn pool_idle_timeout(mut self, timeout: Duration) -> Self {
+        self.backend_config.pool_idle_timeout = Some(timeout);
+        self
+    }
+
+    /// Sets the maximum number of idle connections per host kept in the pool.
+    ///
+    /// It's not supported by the surf backend.
+    ///
+    /// Default is no limit.
+    pub fn pool_max_idle_per_host(mut self, max: usize) -> Self {
+        self.backend_config.pool_max_idle_per_host = Some(max);
+        self
+    }
+
+    /// Sends all requests over the Unix domain socket at the `path`, which requires the `http-hyper`
+    /// feature on unix.
+    ///
+    /// The url is still used for the request path and the `Host` header, e.g. `http://localhost/rpc`.
+    /// A `unix:///path/to.sock` url can also be used, which sends requests to `/` over the socket.
+    ///
+    /// It's applied to the default backend only.
+    pub fn unix_socket<P: Into<std::path::PathBuf>>(mut self, path: P) -> Self {
+        self.backend_config.unix_socket = Some(path.into());
+        self
+    }
+
+    /// Enables TCP keepalive with the interval.
+    ///
+    /// Default is disabled.
+    pub fn tcp_keepalive(mut self, interval: Duration) -> Self {
+        self.backend_config.tcp_keepalive = Some(interval);
+        self
+    }
+
+    /// Sets the `TCP_NODELAY` option of the connections.
+    ///
+    /// Default is enabled.
+    pub fn tcp_nodelay(mut self, enable: bool) -> Self {
+        self.backend_config.tcp_nodelay = enable;
+        self
+    }
+
+    // ========================================================================
+    // Redirect options
+    // ========================================================================
+
+    /// Sets the maximum number of redirects to follow, `0` disables redirects.
+    ///
+    /// Default is 10.
+    pub fn max_redirects(mut self, max: usize) -> Self {
+        self.max_redirects = max;
+        self
+    }
+
+    // ========================================================================
+    // Compression options
+    // ========================================================================
+
+    /// Enables gzip decompression of responses by sending `Accept-Encoding: gzip`.
+    ///
+    /// Default is disabled.
+    pub fn gzip(mut self, enable: bool) -> Self {
+        self.compression.gzip = enable;
+        self
+    }
+
+    /// Enables deflate decompression of responses by sending `Accept-Encoding: deflate`.
+    ///
+    /// Default is disabled.
+    pub fn deflate(mut self, enable: bool) -> Self {
+        self.compression.deflate = enable;
+        self
+    }
+
+    /// Enables brotli decompression of responses by sending `Accept-Encoding: br`.
+    ///
+    /// Default is disabled.
+    pub fn brotli(mut self, enable: bool) -> Self {
+        self.compression.brotli = enable;
+        self
+    }
+
+    /// Enables gzip compression of request bodies, which requires the server to support
+    /// `Content-Encoding: gzip`.
+    ///
+    /// Default is disabled.
+    pub fn gzip_request(mut self, enable: bool) -> Self {
+        self.compression.gzip_request = enable;
+        self
+    }
+
+    // ========================================================================
+    // Response options
+    // ========================================================================
+
+    /// Enables deserializing the response incrementally from the body stream, so the elements of
+    /// a batch response are deserialized without buffering the whole body.
+    ///
+    /// Default is disabled.
+    pub fn stream_response(mut self, enable: bool) -> Self {
+        self.stream_response = enable;
+        self
+    }
+
+    /// Sets the maximum size of the (decompressed) response body, the request is aborted with
+    /// `HttpClientError::ResponseTooLarge` error if the body exceeds it.
+    ///
+    /// Default is no limit.
+    pub fn max_response_size(mut self, size: usize) -> Self {
+        self.max_response_size = Some(size);
+        self
+    }
+
+    // ========================================================================
+    // Retry options
+    // ========================================================================
+
+    /// Enables retrying the requests that failed because of transient errors.
+    ///
+    /// Default is no retry.
+    pub fn retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry_policy = Some(policy);
+        self
+    }
+
+    // ========================================================================
+    // Failover options (for `build_multi`)
+    // ========================================================================
+
+    /// Sets the strategy to choose the endpoint for every request.
+    ///
+    /// Default is `FailoverStrategy::Priority`.
+    pub fn failover_strategy(mut self, strategy: FailoverStrategy) -> Self {
+        self.failover_strategy = strategy;
+        self
+    }
+
+    /// Sets the number of consecutive failures after which an endpoint is marked as unhealthy.
+    ///
+    /// Default is 3.
+    pub fn unhealthy_threshold(mut self, threshold: usize) -> Self {
+        self.unhealthy_threshold = threshold.max(1);
+        self
+    }
+
+    /// Sets the interval after which an unhealthy endpoint is probed again by a request.
+    ///
+    /// Default is 30 seconds.
+    pub fn reprobe_interval(mut self, interval: Duration) -> Self {
+        self.reprobe_interval = interval;
+        self
+    }
+
+    // ========================================================================
+    // Backend options
+    // ========================================================================
+
+    /// Sets the HTTP backend used to send requests, e.g. a backend that shares a `hyper::Client`
+    /// with a custom connector.
+    ///
+    /// Default is the first enabled one of `ReqwestBackend`, `SurfBackend` and `HyperBackend`.
+    pub fn backend<B: HttpBackend + 'static>(mut self, backend: B) -> Self {
+        self.backend = Some(Arc::new(backend));
+        self
+    }
+
+    // ========================================================================
+    // Request ID options
+    // ========================================================================
+
+    /// Sets the generator of request IDs.
+    ///
+    /// Default is `SequentialIdGenerator`, which generates sequential numeric IDs starting from 1.
+    pub fn id_generator<G: IdGenerator + 'static>(mut self, id_generator: G) -> Self {
+        self.id_generator = Arc::new(id_generator);
+        self
     }
 
+    // ========================================================================
+
     /// Returns a `HttpClient` that uses this `HttpClientBuilder` configuration.
-    #[cfg(feature = "http-tokio")]
     pub fn build<U: Into<String>>(self, url: U) -> Result<HttpClient, HttpClientError> {
-        let builder = reqwest::Client::builder().default_headers(self.headers);
-        let builder = if let Some(timeout) = self.timeout {
-            builder.timeout(timeout)
-        } else {
-            builder
-        };
-        let builder = if let Some(timeout) = self.connect_timeout {
-            builder.connect_timeout(timeout)
-        } else {
-            builder
+        self.build_multi(std::iter::once(url))
+    }
+
+    /// Returns a `HttpClient` that sends requests to multiple redundant endpoints, which are chosen
+    /// by the failover strategy.
+    ///
+    /// A `unix:///path/to.sock` url can't be used with other urls.
+    pub fn build_multi<I, U>(mut self, urls: I) -> Result<HttpClient, HttpClientError>
+    where
+        I: IntoIterator<Item = U>,
+        U: Into<String>,
+    {
+        if let Some(err) = self.error {
+            return Err(err);
+        }
+        check_header_values(&self.headers)?;
+        let mut urls = urls.into_iter().map(Into::into).collect::<Vec<String>>();
+        if let Some(path) = urls.iter().find_map(|url| url.strip_prefix("unix://")) {
+            if urls.len() > 1 {
+                return Err(HttpClientError::InvalidConfig(
+                    "unix socket url can't be used with other urls".into(),
+                ));
+            }
+            self.backend_config.unix_socket = Some(path.into());
+            urls = vec!["http://localhost/".into()];
+        }
+        let urls = urls
+            .into_iter()
+            .map(|url| {
+                url.parse::<http::Uri>()
+                    .map_err(|err| HttpClientError::InvalidConfig(format!("invalid url `{}`: {}", url, err)))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        if urls.is_empty() {
+            return Err(HttpClientError::InvalidConfig("no url".into()));
+        }
+        let backend = match self.backend {
+            Some(backend) => backend,
+            None => backend::default_backend(&self.backend_config)?,
         };
-        let client = builder.build()?;
         Ok(HttpClient {
-            url: url.into(),
-            id: Arc::new(AtomicU64::new(1)),
-            client,
+            endpoints: Arc::new(Endpoints::new(
+                urls,
+                self.failover_strategy,
+                self.unhealthy_threshold,
+                self.reprobe_interval,
+            )),
+            id_generator: self.id_generator,
+            backend,
+            headers: self.headers,
+            call_headers: HeaderMap::new(),
+            credential_provider: self.credential_provider,
+            timeout: self.timeout,
+            max_redirects: self.max_redirects,
+            retry_policy: self.retry_policy,
+            compression: self.compression,
+            stream_response: self.stream_response,
+            max_response_size: self.max_response_size,
         })
     }
 }
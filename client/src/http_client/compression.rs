@@ -0,0 +1,166 @@
+use std::io::{self, Write};
+
+use flate2::write::{GzDecoder, GzEncoder, ZlibDecoder};
+use http::header::{self, HeaderMap, HeaderValue};
+
+/// The compression options of `HttpClient`.
+#[derive(Copy, Clone, Debug, Default)]
+pub(crate) struct Compression {
+    pub(crate) gzip: bool,
+    pub(crate) deflate: bool,
+    pub(crate) brotli: bool,
+    pub(crate) gzip_request: bool,
+}
+
+impl Compression {
+    /// Returns the value of `Accept-Encoding` header, or `None` if no compression is enabled.
+    pub(crate) fn accept_encoding(&self) -> Option<HeaderValue> {
+        let encodings = [(self.gzip, "gzip"), (self.deflate, "deflate"), (self.brotli, "br")]
+            .iter()
+            .filter(|(enabled, _)| *enabled)
+            .map(|(_, encoding)| *encoding)
+            .collect::<Vec<_>>();
+        if encodings.is_empty() {
+            return None;
+        }
+        HeaderValue::from_str(&encodings.join(", ")).ok()
+    }
+
+    /// Compresses the request body with gzip if `gzip_request` is enabled.
+    pub(crate) fn encode_request(&self, body: Vec<u8>) -> io::Result<Vec<u8>> {
+        if !self.gzip_request {
+            return Ok(body);
+        }
+        let mut encoder = GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(&body)?;
+        encoder.finish()
+    }
+}
+
+/// Decompresses the chunks of response body according to the `Content-Encoding` header.
+pub(crate) enum Decoder {
+    Identity,
+    Gzip(Box<GzDecoder<Vec<u8>>>),
+    Deflate(Box<ZlibDecoder<Vec<u8>>>),
+    Brotli(Box<brotli_decompressor::DecompressorWriter<Vec<u8>>>),
+}
+
+impl Decoder {
+    /// Creates a decoder for the response with the `headers`.
+    pub(crate) fn new(headers: &HeaderMap) -> io::Result<Self> {
+        let encoding = match headers.get(header::CONTENT_ENCODING) {
+            Some(encoding) => encoding.to_str().unwrap_or_default().trim().to_ascii_lowercase(),
+            None => return Ok(Self::Identity),
+        };
+        match encoding.as_str() {
+            "identity" | "" => Ok(Self::Identity),
+            "gzip" | "x-gzip" => Ok(Self::Gzip(Box::new(GzDecoder::new(Vec::new())))),
+            "deflate" => Ok(Self::Deflate(Box::new(ZlibDecoder::new(Vec::new())))),
+            "br" => Ok(Self::Brotli(Box::new(brotli_decompressor::DecompressorWriter::new(
+                Vec::new(),
+                4096,
+            )))),
+            encoding => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("unsupported content encoding: {}", encoding),
+            )),
+        }
+    }
+
+    /// Decompresses the chunk and returns the decompressed data.
+    pub(crate) fn decode(&mut self, chunk: Vec<u8>) -> io::Result<Vec<u8>> {
+        match self {
+            Self::Identity => Ok(chunk),
+            Self::Gzip(decoder) => {
+                decoder.write_all(&chunk)?;
+                Ok(std::mem::take(decoder.get_mut()))
+            }
+            Self::Deflate(decoder) => {
+                decoder.write_all(&chunk)?;
+                Ok(std::mem::take(decoder.get_mut()))
+            }
+            Self::Brotli(decoder) => {
+                decoder.write_all(&chunk)?;
+                Ok(std::mem::take(decoder.get_mut()))
+            }
+        }
+    }
+
+    /// Finishes the decompression and returns the remaining data.
+    pub(crate) fn finish(&mut self) -> io::Result<Vec<u8>> {
+        match self {
+            Self::Identity => Ok(Vec::new()),
+            Self::Gzip(decoder) => {
+                decoder.try_finish()?;
+                Ok(std::mem::take(decoder.get_mut()))
+            }
+            Self::Deflate(decoder) => {
+                decoder.try_finish()?;
+                Ok(std::mem::take(decoder.get_mut()))
+            }
+            Self::Brotli(decoder) => {
+                decoder.close()?;
+                Ok(std::mem::take(decoder.get_mut()))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use flate2::write::ZlibEncoder;
+
+    use super::*;
+
+    const BODY: &[u8] = br#"{"jsonrpc":"2.0","id":1,"result":"x"}"#;
+
+    /// Decodes the body which is split into small chunks.
+    fn decode(encoding: &str, body: Vec<u8>) -> io::Result<Vec<u8>> {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::CONTENT_ENCODING, HeaderValue::from_str(encoding).unwrap());
+        let mut decoder = Decoder::new(&headers)?;
+        let mut decoded = Vec::new();
+        for chunk in body.chunks(7) {
+            decoded.extend(decoder.decode(chunk.to_vec())?);
+        }
+        decoded.extend(decoder.finish()?);
+        Ok(decoded)
+    }
+
+    #[test]
+    fn accept_encoding() {
+        let compression = Compression::default();
+        assert_eq!(compression.accept_encoding(), None);
+        let compression = Compression {
+            gzip: true,
+            brotli: true,
+            ..Default::default()
+        };
+        assert_eq!(compression.accept_encoding().unwrap(), "gzip, br");
+    }
+
+    #[test]
+    fn decode_compressed_response() {
+        let compression = Compression {
+            gzip_request: true,
+            ..Default::default()
+        };
+        let gzip = compression.encode_request(BODY.to_vec()).unwrap();
+        assert_eq!(decode("gzip", gzip.clone()).unwrap(), BODY);
+        assert!(decode("gzip", gzip[..gzip.len() - 4].to_vec()).is_err());
+
+        let mut encoder = ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(BODY).unwrap();
+        let deflate = encoder.finish().unwrap();
+        assert_eq!(decode("deflate", deflate).unwrap(), BODY);
+
+        let mut brotli = Vec::new();
+        brotli::CompressorWriter::new(&mut brotli, 4096, 5, 22)
+            .write_all(BODY)
+            .unwrap();
+        assert_eq!(decode("br", brotli).unwrap(), BODY);
+
+        assert_eq!(decode("identity", BODY.to_vec()).unwrap(), BODY);
+        assert!(decode("zstd", BODY.to_vec()).is_err());
+    }
+}
@@ -0,0 +1,26 @@
+use http::header::HeaderMap;
+
+use crate::error::HttpClientError;
+
+/// A provider of the credentials (or any other headers) of HTTP requests, which is invoked for
+/// every request.
+///
+/// Implemented for any `Fn(bool) -> Result<HeaderMap, HttpClientError>`, so a closure can be used directly.
+#[async_trait::async_trait]
+pub trait CredentialProvider: Send + Sync {
+    /// Returns the headers of the request, which override the default headers of the client.
+    ///
+    /// `refresh` is `true` if the server responded `401 Unauthorized` to the previous headers,
+    /// then the cached credentials should be refreshed.
+    async fn headers(&self, refresh: bool) -> Result<HeaderMap, HttpClientError>;
+}
+
+#[async_trait::async_trait]
+impl<F> CredentialProvider for F
+where
+    F: Fn(bool) -> Result<HeaderMap, HttpClientError> + Send + Sync,
+{
+    async fn headers(&self, refresh: bool) -> Result<HeaderMap, HttpClientError> {
+        self(refresh)
+    }
+}
@@ -0,0 +1,164 @@
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use http::Uri;
+
+/// The strategy to choose the endpoint of `HttpClient` for every request.
+///
+/// A failed request is sent again to the next endpoint only if the connection to the endpoint
+/// fails, or the methods of the request are idempotent, since the failed request may have been
+/// processed by the server. No method is idempotent unless it's allowlisted by
+/// `RetryPolicy::idempotent_methods`.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum FailoverStrategy {
+    /// Prefers the endpoints in the given order, the next one is used only if the previous ones are unhealthy.
+    Priority,
+    /// Distributes the requests across the endpoints in turn.
+    RoundRobin,
+}
+
+// `#[default]` on the enum variant requires Rust 1.62.
+#[allow(clippy::derivable_impls)]
+impl Default for FailoverStrategy {
+    fn default() -> Self {
+        Self::Priority
+    }
+}
+
+/// The endpoints of `HttpClient`, whose health is shared by all clones of the client.
+#[derive(Debug)]
+pub(crate) struct Endpoints {
+    endpoints: Vec<Endpoint>,
+    strategy: FailoverStrategy,
+    next: AtomicUsize,
+    unhealthy_threshold: usize,
+    reprobe_interval: Duration,
+}
+
+#[derive(Debug)]
+struct Endpoint {
+    url: Uri,
+    health: Mutex<Health>,
+}
+
+#[derive(Debug, Default)]
+struct Health {
+    /// The number of consecutive failures.
+    failures: usize,
+    /// The endpoint is unhealthy until the time, after which it will be probed again.
+    unhealthy_until: Option<Instant>,
+}
+
+impl Endpoints {
+    pub(crate) fn new(
+        urls: Vec<Uri>,
+        strategy: FailoverStrategy,
+        unhealthy_threshold: usize,
+        reprobe_interval: Duration,
+    ) -> Self {
+        Self {
+            endpoints: urls
+                .into_iter()
+                .map(|url| Endpoint {
+                    url,
+                    health: Mutex::new(Health::default()),
+                })
+                .collect(),
+            strategy,
+            next: AtomicUsize::new(0),
+            unhealthy_threshold,
+            reprobe_interval,
+        }
+    }
+
+    pub(crate) fn url(&self, index: usize) -> &Uri {
+        &self.endpoints[index].url
+    }
+
+    /// Returns the indexes of endpoints in the order to try for a request.
+    ///
+    /// The unhealthy endpoints are put at the end, so they are still tried as the last resort.
+    pub(crate) fn candidates(&self) -> Vec<usize> {
+        let len = self.endpoints.len();
+        let start = match self.strategy {
+            FailoverStrategy::Priority => 0,
+            FailoverStrategy::RoundRobin => self.next.fetch_add(1, Ordering::Relaxed) % len,
+        };
+        let now = Instant::now();
+        let (mut healthy, unhealthy): (Vec<usize>, Vec<usize>) =
+            (0..len).map(|i| (start + i) % len).partition(|&index| {
+                let health = self.endpoints[index].health.lock().expect("lock poisoned");
+                !matches!(health.unhealthy_until, Some(until) if until > now)
+            });
+        healthy.extend(unhealthy);
+        healthy
+    }
+
+    pub(crate) fn report_success(&self, index: usize) {
+        let mut health = self.endpoints[index].health.lock().expect("lock poisoned");
+        *health = Health::default();
+    }
+
+    pub(crate) fn report_failure(&self, index: usize) {
+        let mut health = self.endpoints[index].health.lock().expect("lock poisoned");
+        health.failures += 1;
+        if health.failures >= self.unhealthy_threshold {
+            log::warn!(
+                "Endpoint {} is unhealthy after {} failures",
+                self.url(index),
+                health.failures
+            );
+            health.unhealthy_until = Some(Instant::now() + self.reprobe_interval);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn new_endpoints(strategy: FailoverStrategy, reprobe_interval: Duration) -> Endpoints {
+        let urls = vec![
+            "http://127.0.0.1:8545".parse().unwrap(),
+            "http://127.0.0.1:8546".parse().unwrap(),
+            "http://127.0.0.1:8547".parse().unwrap(),
+        ];
+        Endpoints::new(urls, strategy, 2, reprobe_interval)
+    }
+
+    #[test]
+    fn choose_endpoints() {
+        let endpoints = new_endpoints(FailoverStrategy::Priority, Duration::from_secs(60));
+        assert_eq!(endpoints.candidates(), vec![0, 1, 2]);
+        assert_eq!(endpoints.candidates(), vec![0, 1, 2]);
+
+        let endpoints = new_endpoints(FailoverStrategy::RoundRobin, Duration::from_secs(60));
+        assert_eq!(endpoints.candidates(), vec![0, 1, 2]);
+        assert_eq!(endpoints.candidates(), vec![1, 2, 0]);
+        assert_eq!(endpoints.candidates(), vec![2, 0, 1]);
+    }
+
+    #[test]
+    fn mark_unhealthy_endpoints() {
+        let endpoints = new_endpoints(FailoverStrategy::Priority, Duration::from_secs(60));
+        endpoints.report_failure(0);
+        assert_eq!(endpoints.candidates(), vec![0, 1, 2]);
+        endpoints.report_failure(0);
+        assert_eq!(endpoints.candidates(), vec![1, 2, 0]);
+        endpoints.report_success(0);
+        assert_eq!(endpoints.candidates(), vec![0, 1, 2]);
+
+        // probe the unhealthy endpoint again after the interval
+        let endpoints = new_endpoints(FailoverStrategy::Priority, Duration::from_millis(10));
+        endpoints.report_failure(0);
+        endpoints.report_failure(0);
+        assert_eq!(endpoints.candidates(), vec![1, 2, 0]);
+        std::thread::sleep(Duration::from_millis(20));
+        assert_eq!(endpoints.candidates(), vec![0, 1, 2]);
+    }
+}
@@ -1,39 +1,71 @@
+mod backend;
 mod builder;
+mod compression;
+mod credential;
+mod endpoint;
+mod retry;
+mod stream;
 #[cfg(test)]
 mod tests;
 
-use std::sync::{
-    atomic::{AtomicU64, Ordering},
-    Arc,
-};
+use std::{sync::Arc, time::Duration};
 
+use futures::{future, stream::StreamExt};
+use http::{header, Method, StatusCode, Uri};
 use jsonrpc_types::v2::*;
-use serde::{de::DeserializeOwned, Serialize};
+use serde::{de::Error as _, Serialize};
 
-pub use self::builder::HttpClientBuilder;
+#[cfg(feature = "http-hyper")]
+pub use self::backend::HyperBackend;
+#[cfg(feature = "http-tokio")]
+pub use self::backend::ReqwestBackend;
+#[cfg(feature = "http-async-std")]
+pub use self::backend::SurfBackend;
+#[cfg(all(unix, feature = "http-hyper"))]
+pub use self::backend::{UnixConnector, UnixStream};
+pub use self::{
+    backend::{HttpBackend, ResponseBody},
+    builder::HttpClientBuilder,
+    credential::CredentialProvider,
+    endpoint::FailoverStrategy,
+    retry::RetryPolicy,
+};
+use self::{
+    compression::Compression,
+    endpoint::Endpoints,
+    retry::{is_connect_error, is_transient_error, is_transient_status},
+    stream::ResponseDecoder,
+};
 use crate::{
     error::HttpClientError,
+    id::IdGenerator,
     transport::{BatchTransport, Transport},
 };
 
 /// HTTP JSON-RPC client
-#[cfg(feature = "http-async-std")]
 #[derive(Clone)]
 pub struct HttpClient {
-    url: String,
-    id: Arc<AtomicU64>,
-    client: surf::Client,
-    headers: http::header::HeaderMap,
-    timeout: Option<std::time::Duration>,
+    endpoints: Arc<Endpoints>,
+    id_generator: Arc<dyn IdGenerator>,
+    backend: Arc<dyn HttpBackend>,
+    headers: header::HeaderMap,
+    /// The headers of the calls made by this (cloned) client, which override other headers.
+    call_headers: header::HeaderMap,
+    credential_provider: Option<Arc<dyn CredentialProvider>>,
+    timeout: Option<Duration>,
+    max_redirects: usize,
+    retry_policy: Option<RetryPolicy>,
+    compression: Compression,
+    stream_response: bool,
+    max_response_size: Option<usize>,
 }
 
-/// HTTP JSON-RPC client
-#[cfg(feature = "http-tokio")]
-#[derive(Clone)]
-pub struct HttpClient {
-    url: String,
-    id: Arc<AtomicU64>,
-    client: reqwest::Client,
+/// The body of HTTP response that has been read.
+enum Content {
+    /// The JSON-RPC response deserialized incrementally.
+    Decoded(ResponseObj),
+    /// The raw body.
+    Raw(Vec<u8>),
 }
 
 impl HttpClient {
@@ -48,76 +80,329 @@ impl HttpClient {
     pub fn builder() -> HttpClientBuilder {
         HttpClientBuilder::new()
     }
-}
 
-#[cfg(feature = "http-async-std")]
-impl HttpClient {
-    async fn send_request<REQ, RSP>(&self, request: REQ) -> Result<RSP, HttpClientError>
+    /// Returns a clone of the client whose requests are sent with the `headers`, which override
+    /// the default headers and the headers of the credential provider.
+    ///
+    /// The clone shares the connections and the endpoint health with this client.
+    pub fn with_headers(&self, headers: header::HeaderMap) -> Self {
+        let mut client = self.clone();
+        client.call_headers.extend(headers);
+        client
+    }
+
+    /// Sends the request, which is retried by the retry policy and fails over to other endpoints
+    /// only if it's `idempotent`.
+    async fn send_request<REQ>(&self, request: REQ, idempotent: bool) -> Result<ResponseObj, HttpClientError>
     where
         REQ: Serialize,
-        RSP: Serialize + DeserializeOwned,
     {
-        let request = serde_json::to_string(&request).expect("serialize request");
-        log::debug!("Request: {}", request);
-
-        let mut builder = self
-            .client
-            .post(&self.url)
-            .content_type(surf::http::mime::JSON)
-            .body(request);
-        for (header_name, header_value) in self.headers.iter() {
-            builder = builder.header(
-                header_name.as_str(),
-                header_value.to_str().expect("must be visible ascii"),
-            );
+        let request = serde_json::to_vec(&request)?;
+        log::debug!("Request: {}", String::from_utf8_lossy(&request));
+        let request = self
+            .compression
+            .encode_request(request)
+            .map_err(|err| HttpClientError::Http(err.into()))?;
+
+        let mut retries = 0;
+        let result = loop {
+            let result = self.send_with_failover(&request, idempotent).await;
+            let delay = match &self.retry_policy {
+                Some(policy) if idempotent => policy.retry_delay(retries, &result),
+                _ => None,
+            };
+            match delay {
+                Some(delay) => {
+                    retries += 1;
+                    log::debug!("Retry request after {:?}: retries={}", delay, retries);
+                    futures_timer::Delay::new(delay).await;
+                }
+                None => break result,
+            }
+        };
+        match result.and_then(parse_response) {
+            Err(err) if retries > 0 => Err(HttpClientError::Retry {
+                retries,
+                error: Box::new(err),
+            }),
+            result => result,
         }
+    }
 
-        let response = builder.send();
-        let response = if let Some(duration) = self.timeout {
-            let timeout = async_std::task::sleep(duration);
+    /// Sends the request to the endpoints in turn until one of them doesn't fail, the request that
+    /// may have been processed by the server is sent again only if it's `idempotent`.
+    async fn send_with_failover(
+        &self,
+        body: &[u8],
+        idempotent: bool,
+    ) -> Result<http::Response<Content>, HttpClientError> {
+        let candidates = self.endpoints.candidates();
+        let last = candidates.len() - 1;
+        for (i, index) in candidates.into_iter().enumerate() {
+            let url = self.endpoints.url(index).clone();
+            let result = self.send_with_timeout(url, body.to_vec()).await;
+            if !is_endpoint_failure(&result) {
+                self.endpoints.report_success(index);
+                return result;
+            }
+            self.endpoints.report_failure(index);
+            // The request that failed to connect is not sent at all, so it's safe to fail over.
+            let sent = !matches!(&result, Err(err) if is_connect_error(err));
+            if i == last || (sent && !idempotent) {
+                return result;
+            }
+            log::debug!("Fail over to the next endpoint: failed={}", self.endpoints.url(index));
+        }
+        unreachable!("there is at least one endpoint")
+    }
+
+    /// Sends the request and reads the response body within the timeout.
+    async fn send_with_timeout(&self, url: Uri, body: Vec<u8>) -> Result<http::Response<Content>, HttpClientError> {
+        let response = async {
+            let response = self.send_with_credentials(url, body).await?;
+            self.read_body(response).await
+        };
+        if let Some(duration) = self.timeout {
+            let timeout = futures_timer::Delay::new(duration);
             futures::pin_mut!(response, timeout);
-            match futures::future::select(response, timeout).await {
-                futures::future::Either::Left((response, _)) => response,
-                futures::future::Either::Right((_, _)) => return Err(anyhow::anyhow!("http request timeout").into()),
+            match future::select(response, timeout).await {
+                future::Either::Left((response, _)) => response,
+                future::Either::Right((_, _)) => Err(HttpClientError::RequestTimeout),
             }
         } else {
             response.await
+        }
+    }
+
+    /// Reads the response body, which is decompressed and limited by `max_response_size`.
+    ///
+    /// The body of successful response is deserialized incrementally if `stream_response` is enabled.
+    async fn read_body(
+        &self,
+        response: http::Response<ResponseBody>,
+    ) -> Result<http::Response<Content>, HttpClientError> {
+        let (parts, mut body) = response.into_parts();
+        let mut decoder = compression::Decoder::new(&parts.headers).map_err(|err| HttpClientError::Http(err.into()))?;
+        let mut stream_decoder = if self.stream_response && parts.status.is_success() {
+            Some(ResponseDecoder::default())
+        } else {
+            None
         };
-        let mut response = response.map_err(|err| err.into_inner())?;
-        if !response.status().is_success() {
-            log::debug!("HTTP response status {}", response.status());
-            return Err(HttpClientError::Http(anyhow::anyhow!(
-                "Unexpected response status code: {}",
-                response.status()
-            )));
+        let mut raw = Vec::new();
+        let mut size = 0;
+        let mut finished = false;
+        while !finished {
+            let chunk = match body.next().await {
+                Some(chunk) => decoder.decode(chunk?),
+                None => {
+                    finished = true;
+                    decoder.finish()
+                }
+            };
+            let chunk = chunk.map_err(|err| HttpClientError::Http(err.into()))?;
+            size += chunk.len();
+            if let Some(limit) = self.max_response_size {
+                if size > limit {
+                    return Err(HttpClientError::ResponseTooLarge { limit });
+                }
+            }
+            match &mut stream_decoder {
+                Some(stream_decoder) => stream_decoder.feed(&chunk)?,
+                None => raw.extend(chunk),
+            }
         }
+        let content = match stream_decoder {
+            Some(stream_decoder) => Content::Decoded(stream_decoder.finish()?),
+            None => Content::Raw(raw),
+        };
+        Ok(http::Response::from_parts(parts, content))
+    }
 
-        let response = response.body_string().await.map_err(|err| err.into_inner())?;
-        log::debug!("Response: {}", response);
+    /// Returns whether the request with the `method` can be retried or failed over, which is only
+    /// allowed for the methods allowlisted by the retry policy.
+    fn is_idempotent(&self, method: &str) -> bool {
+        match &self.retry_policy {
+            Some(policy) => policy.is_idempotent(method),
+            None => false,
+        }
+    }
+
+    /// Sends the request with the headers of the credential provider, which is invoked again to
+    /// refresh the credentials and resend the request once if the response is `401 Unauthorized`.
+    async fn send_with_credentials(
+        &self,
+        url: Uri,
+        body: Vec<u8>,
+    ) -> Result<http::Response<ResponseBody>, HttpClientError> {
+        let provider = match &self.credential_provider {
+            Some(provider) => provider,
+            None => return self.send_with_redirects(url, body, header::HeaderMap::new()).await,
+        };
+        let headers = provider.headers(false).await?;
+        let response = self.send_with_redirects(url.clone(), body.clone(), headers).await?;
+        if response.status() != StatusCode::UNAUTHORIZED {
+            return Ok(response);
+        }
+        log::debug!("Refresh the credentials for 401 response");
+        let headers = provider.headers(true).await?;
+        self.send_with_redirects(url, body, headers).await
+    }
+
+    /// Sends the request and follows the redirects like browsers do: `307` and `308` resend the
+    /// request, other redirect statuses switch to `GET` without body.
+    ///
+    /// Once redirected to another origin (scheme, host or port), the headers of the credential
+    /// provider and the `Authorization`, `Cookie` and `Proxy-Authorization` headers are not sent.
+    async fn send_with_redirects(
+        &self,
+        mut url: Uri,
+        body: Vec<u8>,
+        credential_headers: header::HeaderMap,
+    ) -> Result<http::Response<ResponseBody>, HttpClientError> {
+        let origin = url.clone();
+        let mut cross_origin = false;
+        let mut body = Some(body);
+        let mut redirects = 0;
+        loop {
+            let mut request = match &body {
+                Some(body) => {
+                    let mut request = http::Request::new(body.clone());
+                    *request.method_mut() = Method::POST;
+                    request.headers_mut().insert(
+                        header::CONTENT_TYPE,
+                        header::HeaderValue::from_static("application/json"),
+                    );
+                    if self.compression.gzip_request {
+                        request
+                            .headers_mut()
+                            .insert(header::CONTENT_ENCODING, header::HeaderValue::from_static("gzip"));
+                    }
+                    request
+                }
+                None => http::Request::new(Vec::new()),
+            };
+            *request.uri_mut() = url.clone();
+            request
+                .headers_mut()
+                .extend(self.headers.iter().map(|(name, value)| (name.clone(), value.clone())));
+            if !cross_origin {
+                request.headers_mut().extend(credential_headers.clone());
+            }
+            request.headers_mut().extend(self.call_headers.clone());
+            if cross_origin {
+                for name in &[header::AUTHORIZATION, header::COOKIE, header::PROXY_AUTHORIZATION] {
+                    request.headers_mut().remove(name);
+                }
+            }
+            if let Some(accept_encoding) = self.compression.accept_encoding() {
+                request
+                    .headers_mut()
+                    .entry(header::ACCEPT_ENCODING)
+                    .or_insert(accept_encoding);
+            }
+            check_header_values(request.headers())?;
+            let response = self.backend.send(request).await?;
 
-        Ok(serde_json::from_str::<RSP>(&response)?)
+            let status = response.status();
+            let location = match response.headers().get(header::LOCATION) {
+                Some(location) if status.is_redirection() && self.max_redirects > 0 => location,
+                _ => return Ok(response),
+            };
+            if redirects == self.max_redirects {
+                return Err(HttpClientError::Http("too many redirects".into()));
+            }
+            redirects += 1;
+            url = resolve_location(&url, location)?;
+            cross_origin = cross_origin || !is_same_origin(&origin, &url);
+            if !matches!(status, StatusCode::TEMPORARY_REDIRECT | StatusCode::PERMANENT_REDIRECT) {
+                body = None;
+            }
+        }
     }
 }
 
-#[cfg(feature = "http-tokio")]
-impl HttpClient {
-    async fn send_request<REQ, RSP>(&self, request: REQ) -> Result<RSP, HttpClientError>
-    where
-        REQ: Serialize,
-        RSP: Serialize + DeserializeOwned,
-    {
-        log::debug!(
-            "Request: {}",
-            serde_json::to_string(&request).expect("serialize request")
-        );
-        let builder = self.client.post(&self.url).json(&request);
-        let response = builder.send().await?;
-        let response = response.json().await?;
-        log::debug!(
-            "Response: {}",
-            serde_json::to_string(&response).expect("serialize response")
-        );
-        Ok(response)
+/// Returns whether the result means that the endpoint may be unavailable.
+fn is_endpoint_failure<B>(result: &Result<http::Response<B>, HttpClientError>) -> bool {
+    match result {
+        Ok(response) => is_transient_status(response.status()),
+        Err(err) => is_transient_error(err),
+    }
+}
+
+/// Resolves the `Location` header of a redirect response against the request url.
+fn resolve_location(base: &Uri, location: &header::HeaderValue) -> Result<Uri, HttpClientError> {
+    let invalid = || HttpClientError::Http(format!("invalid redirect location: {:?}", location).into());
+    let location = location.to_str().map_err(|_| invalid())?;
+    match location.parse::<Uri>() {
+        Ok(uri) if uri.scheme().is_some() => return Ok(uri),
+        _ => {}
+    }
+
+    let path = if location.starts_with('/') {
+        location.to_string()
+    } else {
+        let base_path = base.path();
+        let dir = &base_path[..base_path.rfind('/').map_or(0, |i| i + 1)];
+        format!("{}{}", dir, location)
+    };
+    let mut parts = base.clone().into_parts();
+    parts.path_and_query = Some(path.parse().map_err(|_| invalid())?);
+    Uri::from_parts(parts).map_err(|_| invalid())
+}
+
+/// Checks that the header values are visible ASCII, since some backends (e.g. `surf`) can't send
+/// other bytes, so that the headers are handled identically on every backend.
+fn check_header_values(headers: &header::HeaderMap) -> Result<(), HttpClientError> {
+    match headers.iter().find(|(_, value)| value.to_str().is_err()) {
+        Some((name, value)) => Err(HttpClientError::InvalidConfig(format!(
+            "header value of `{}` is not visible ASCII: {:?}",
+            name, value
+        ))),
+        None => Ok(()),
+    }
+}
+
+/// Returns whether the urls have the same scheme, host and port.
+fn is_same_origin(a: &Uri, b: &Uri) -> bool {
+    let port = |uri: &Uri| {
+        uri.port_u16().or_else(|| match uri.scheme_str() {
+            Some("https") => Some(443),
+            Some("http") => Some(80),
+            _ => None,
+        })
+    };
+    a.scheme() == b.scheme()
+        && a.host().map(str::to_ascii_lowercase) == b.host().map(str::to_ascii_lowercase)
+        && port(a) == port(b)
+}
+
+/// Parses the HTTP response body as a JSON-RPC response.
+///
+/// A JSON-RPC response (e.g. `Failure`) delivered with non-2xx status is still returned as response,
+/// otherwise the non-2xx status is returned as `HttpClientError::Status` error.
+fn parse_response(response: http::Response<Content>) -> Result<ResponseObj, HttpClientError> {
+    let (parts, content) = response.into_parts();
+    let body = match content {
+        Content::Decoded(response) => {
+            log::debug!("Response: status={}, response={}", parts.status, response);
+            return Ok(response);
+        }
+        Content::Raw(body) => body,
+    };
+    log::debug!(
+        "Response: status={}, body={}",
+        parts.status,
+        String::from_utf8_lossy(&body)
+    );
+    if parts.status.is_success() {
+        return Ok(serde_json::from_slice(&body)?);
+    }
+    match serde_json::from_slice(&body) {
+        Ok(response) => Ok(response),
+        Err(_) => Err(HttpClientError::Status {
+            code: parts.status,
+            body: String::from_utf8_lossy(&body).into_owned(),
+        }),
     }
 }
 
@@ -129,9 +414,12 @@ impl Transport for HttpClient {
     where
         M: Into<String> + Send,
     {
-        let id = self.id.fetch_add(1, Ordering::AcqRel);
-        let call = Request::new(method, params, Id::Num(id));
-        self.send_request(call).await
+        let call = Request::new(method, params, self.id_generator.next_id());
+        let idempotent = self.is_idempotent(&call.method);
+        match self.send_request(call, idempotent).await? {
+            ResponseObj::Single(response) => Ok(response),
+            ResponseObj::Batch(_) => Err(serde_json::Error::custom("expected a single response, found a batch").into()),
+        }
     }
 }
 
@@ -145,11 +433,14 @@ impl BatchTransport for HttpClient {
     {
         let calls = batch
             .into_iter()
-            .map(|(method, params)| {
-                let id = self.id.fetch_add(1, Ordering::AcqRel);
-                Request::new(method, params, Id::Num(id))
-            })
+            .map(|(method, params)| Request::new(method, params, self.id_generator.next_id()))
             .collect::<Vec<_>>();
-        self.send_request(calls).await
+        let idempotent = calls.iter().all(|call| self.is_idempotent(&call.method));
+        match self.send_request(calls, idempotent).await? {
+            ResponseObj::Batch(responses) => Ok(responses),
+            ResponseObj::Single(_) => {
+                Err(serde_json::Error::custom("expected a batch response, found a single response").into())
+            }
+        }
     }
 }
@@ -0,0 +1,314 @@
+use std::{collections::HashSet, error::Error, io, time::Duration};
+
+use http::{header, StatusCode};
+
+use crate::error::HttpClientError;
+
+/// The policy to retry the requests that failed because of transient errors.
+///
+/// The request is retried when the connection fails or is reset, the request times out, or the
+/// response status is one of `429`, `502`, `503` and `504`, with exponential backoff between the
+/// attempts.
+#[derive(Clone, Debug)]
+pub struct RetryPolicy {
+    max_retries: usize,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    multiplier: f64,
+    jitter: bool,
+    idempotent_methods: Option<HashSet<String>>,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RetryPolicy {
+    /// Creates a new `RetryPolicy` with the default configuration.
+    pub fn new() -> Self {
+        Self {
+            max_retries: 3,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(10),
+            multiplier: 2.0,
+            jitter: true,
+            idempotent_methods: None,
+        }
+    }
+
+    /// Sets the maximum number of retries.
+    ///
+    /// Default is 3.
+    pub fn max_retries(mut self, max_retries: usize) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// Sets the backoff before the first retry, which is multiplied by `multiplier` for every retry.
+    ///
+    /// Default is 100 milliseconds.
+    pub fn initial_backoff(mut self, backoff: Duration) -> Self {
+        self.initial_backoff = backoff;
+        self
+    }
+
+    /// Sets the maximum backoff between two attempts.
+    ///
+    /// Default is 10 seconds.
+    pub fn max_backoff(mut self, backoff: Duration) -> Self {
+        self.max_backoff = backoff;
+        self
+    }
+
+    /// Sets the multiplier of the exponential backoff.
+    ///
+    /// Default is 2.0.
+    pub fn multiplier(mut self, multiplier: f64) -> Self {
+        self.multiplier = multiplier;
+        self
+    }
+
+    /// Enables or disables the jitter, which randomizes the backoff between half and all of it.
+    ///
+    /// Default is enabled.
+    pub fn jitter(mut self, enabled: bool) -> Self {
+        self.jitter = enabled;
+        self
+    }
+
+    /// Sets the methods which are safe to retry, the requests (or batches) with other methods are
+    /// never retried, and only failed over to other endpoints if the connection fails.
+    ///
+    /// Default is none, i.e. no request is retried unless its method is allowlisted.
+    pub fn idempotent_methods<I, M>(mut self, methods: I) -> Self
+    where
+        I: IntoIterator<Item = M>,
+        M: Into<String>,
+    {
+        self.idempotent_methods = Some(methods.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Returns whether the request with the `method` can be retried.
+    pub(crate) fn is_idempotent(&self, method: &str) -> bool {
+        match &self.idempotent_methods {
+            Some(methods) => methods.contains(method),
+            None => false,
+        }
+    }
+
+    /// Returns the delay before the next attempt, or `None` if the `result` of the `retries`-th retry
+    /// should not be retried.
+    pub(crate) fn retry_delay<B>(
+        &self,
+        retries: usize,
+        result: &Result<http::Response<B>, HttpClientError>,
+    ) -> Option<Duration> {
+        if retries >= self.max_retries {
+            return None;
+        }
+        match result {
+            Ok(response) if is_transient_status(response.status()) => {
+                Some(retry_after(response).unwrap_or_else(|| self.backoff(retries)))
+            }
+            Ok(_) => None,
+            Err(err) if is_transient_error(err) => Some(self.backoff(retries)),
+            Err(_) => None,
+        }
+    }
+
+    fn backoff(&self, retries: usize) -> Duration {
+        let backoff = self.initial_backoff.as_secs_f64() * self.multiplier.powi(retries as i32);
+        let backoff = backoff.min(self.max_backoff.as_secs_f64());
+        let backoff = if self.jitter {
+            backoff * (0.5 + rand::random::<f64>() / 2.0)
+        } else {
+            backoff
+        };
+        Duration::from_secs_f64(backoff)
+    }
+}
+
+pub(crate) fn is_transient_status(status: StatusCode) -> bool {
+    matches!(
+        status,
+        StatusCode::TOO_MANY_REQUESTS
+            | StatusCode::BAD_GATEWAY
+            | StatusCode::SERVICE_UNAVAILABLE
+            | StatusCode::GATEWAY_TIMEOUT
+    )
+}
+
+/// Returns whether the error is transient, i.e. the connection fails, times out or is reset.
+///
+/// Other errors (e.g. too many redirects or an undecodable response) fail again if retried.
+pub(crate) fn is_transient_error(err: &HttpClientError) -> bool {
+    match err {
+        HttpClientError::RequestTimeout => true,
+        HttpClientError::Http(err) => has_source(err.as_ref(), is_transient_source),
+        _ => false,
+    }
+}
+
+/// Returns whether the connection fails, i.e. the request is not sent to the server at all.
+pub(crate) fn is_connect_error(err: &HttpClientError) -> bool {
+    match err {
+        HttpClientError::Http(err) => has_source(err.as_ref(), is_connect_source),
+        _ => false,
+    }
+}
+
+/// Returns whether the error or any of its sources matches the `predicate`.
+fn has_source(err: &(dyn Error + 'static), predicate: fn(&(dyn Error + 'static)) -> bool) -> bool {
+    let mut source = Some(err);
+    while let Some(err) = source {
+        if predicate(err) {
+            return true;
+        }
+        source = err.source();
+    }
+    false
+}
+
+/// Returns whether the error of the backend is a connect, timeout or connection reset error.
+fn is_transient_source(err: &(dyn Error + 'static)) -> bool {
+    if let Some(err) = err.downcast_ref::<io::Error>() {
+        return matches!(
+            err.kind(),
+            io::ErrorKind::ConnectionRefused
+                | io::ErrorKind::ConnectionReset
+                | io::ErrorKind::ConnectionAborted
+                | io::ErrorKind::NotConnected
+                | io::ErrorKind::BrokenPipe
+                | io::ErrorKind::TimedOut
+        );
+    }
+    #[cfg(feature = "http-tokio")]
+    {
+        if let Some(err) = err.downcast_ref::<reqwest::Error>() {
+            return err.is_connect() || err.is_timeout();
+        }
+    }
+    #[cfg(feature = "http-hyper")]
+    {
+        if let Some(err) = err.downcast_ref::<hyper::Error>() {
+            return err.is_connect() || err.is_incomplete_message();
+        }
+    }
+    #[cfg(feature = "http-async-std")]
+    {
+        if let Some(err) = err.downcast_ref::<isahc::Error>() {
+            return matches!(
+                err,
+                isahc::Error::ConnectFailed | isahc::Error::NoResponse | isahc::Error::Timeout
+            );
+        }
+    }
+    false
+}
+
+/// Returns whether the error of the backend is a connect error.
+fn is_connect_source(err: &(dyn Error + 'static)) -> bool {
+    if let Some(err) = err.downcast_ref::<io::Error>() {
+        return err.kind() == io::ErrorKind::ConnectionRefused;
+    }
+    #[cfg(feature = "http-tokio")]
+    {
+        if let Some(err) = err.downcast_ref::<reqwest::Error>() {
+            return err.is_connect();
+        }
+    }
+    #[cfg(feature = "http-hyper")]
+    {
+        if let Some(err) = err.downcast_ref::<hyper::Error>() {
+            return err.is_connect();
+        }
+    }
+    #[cfg(feature = "http-async-std")]
+    {
+        if let Some(err) = err.downcast_ref::<isahc::Error>() {
+            return matches!(err, isahc::Error::ConnectFailed | isahc::Error::CouldntResolveHost);
+        }
+    }
+    false
+}
+
+/// Returns the delay of `Retry-After` header, only the delay in seconds is supported.
+fn retry_after<B>(response: &http::Response<B>) -> Option<Duration> {
+    let value = response.headers().get(header::RETRY_AFTER)?;
+    let seconds = value.to_str().ok()?.trim().parse::<u64>().ok()?;
+    Some(Duration::from_secs(seconds))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn response(status: u16, retry_after: Option<&str>) -> Result<http::Response<Vec<u8>>, HttpClientError> {
+        let mut builder = http::Response::builder().status(status);
+        if let Some(retry_after) = retry_after {
+            builder = builder.header(header::RETRY_AFTER, retry_after);
+        }
+        Ok(builder.body(Vec::new()).unwrap())
+    }
+
+    #[test]
+    fn retry_delay() {
+        let policy = RetryPolicy::new()
+            .max_retries(4)
+            .initial_backoff(Duration::from_millis(100))
+            .max_backoff(Duration::from_millis(300))
+            .jitter(false);
+
+        // exponential backoff
+        let err: Result<http::Response<()>, _> = Err(HttpClientError::RequestTimeout);
+        assert_eq!(policy.retry_delay(0, &err), Some(Duration::from_millis(100)));
+        assert_eq!(policy.retry_delay(1, &err), Some(Duration::from_millis(200)));
+        assert_eq!(policy.retry_delay(2, &err), Some(Duration::from_millis(300)));
+        assert_eq!(policy.retry_delay(4, &err), None);
+
+        // retryable status
+        assert_eq!(
+            policy.retry_delay(0, &response(503, None)),
+            Some(Duration::from_millis(100))
+        );
+        assert_eq!(
+            policy.retry_delay(0, &response(503, Some("2"))),
+            Some(Duration::from_secs(2))
+        );
+        assert_eq!(policy.retry_delay(0, &response(500, None)), None);
+        assert_eq!(policy.retry_delay(0, &response(200, None)), None);
+        let err: Result<http::Response<()>, _> = Err(HttpClientError::InvalidConfig("invalid".into()));
+        assert_eq!(policy.retry_delay(0, &err), None);
+
+        // only the connect, timeout and connection reset errors are retried
+        let err: Result<http::Response<()>, _> = Err(HttpClientError::Http(Box::new(io::Error::from(
+            io::ErrorKind::ConnectionReset,
+        ))));
+        assert_eq!(policy.retry_delay(0, &err), Some(Duration::from_millis(100)));
+        let err: Result<http::Response<()>, _> = Err(HttpClientError::Http("too many redirects".into()));
+        assert_eq!(policy.retry_delay(0, &err), None);
+        let err: Result<http::Response<()>, _> = Err(HttpClientError::Http(Box::new(io::Error::from(
+            io::ErrorKind::InvalidData,
+        ))));
+        assert_eq!(policy.retry_delay(0, &err), None);
+
+        // jitter
+        let policy = policy.jitter(true);
+        let delay = policy
+            .retry_delay::<()>(1, &Err(HttpClientError::RequestTimeout))
+            .unwrap();
+        assert!(delay >= Duration::from_millis(100) && delay <= Duration::from_millis(200));
+    }
+
+    #[test]
+    fn idempotent_methods() {
+        let policy = RetryPolicy::new();
+        assert!(!policy.is_idempotent("eth_getBalance"));
+        let policy = policy.idempotent_methods(vec!["eth_getBalance"]);
+        assert!(policy.is_idempotent("eth_getBalance"));
+        assert!(!policy.is_idempotent("eth_sendTransaction"));
+    }
+}
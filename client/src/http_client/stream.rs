@@ -0,0 +1,166 @@
+use jsonrpc_types::v2::{Response, ResponseObj};
+use serde::de::Error as _;
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+enum State {
+    /// Waiting for the first non-whitespace character.
+    Start,
+    /// Buffering a single response.
+    Single,
+    /// Splitting the elements of a batch response.
+    Batch,
+    /// The batch response is complete.
+    End,
+}
+
+/// Deserializes a `ResponseObj` incrementally from the chunks of the response body.
+///
+/// The elements of a batch response are deserialized as soon as they are complete, so only the
+/// incomplete element is buffered instead of the whole body.
+pub(crate) struct ResponseDecoder {
+    state: State,
+    buf: Vec<u8>,
+    batch: Vec<Response>,
+    /// The nesting depth within the current batch element.
+    depth: usize,
+    in_string: bool,
+    escaped: bool,
+}
+
+impl Default for ResponseDecoder {
+    fn default() -> Self {
+        Self {
+            state: State::Start,
+            buf: Vec::new(),
+            batch: Vec::new(),
+            depth: 0,
+            in_string: false,
+            escaped: false,
+        }
+    }
+}
+
+impl ResponseDecoder {
+    /// Feeds the next chunk of the response body.
+    pub(crate) fn feed(&mut self, chunk: &[u8]) -> Result<(), serde_json::Error> {
+        for &byte in chunk {
+            match self.state {
+                State::Start => match byte {
+                    b'[' => self.state = State::Batch,
+                    byte if byte.is_ascii_whitespace() => {}
+                    byte => {
+                        self.state = State::Single;
+                        self.buf.push(byte);
+                    }
+                },
+                State::Single => self.buf.push(byte),
+                State::Batch => self.feed_batch(byte)?,
+                State::End => {
+                    if !byte.is_ascii_whitespace() {
+                        return Err(serde_json::Error::custom("trailing characters after batch response"));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn feed_batch(&mut self, byte: u8) -> Result<(), serde_json::Error> {
+        if self.in_string {
+            if self.escaped {
+                self.escaped = false;
+            } else if byte == b'\\' {
+                self.escaped = true;
+            } else if byte == b'"' {
+                self.in_string = false;
+            }
+            self.buf.push(byte);
+            return Ok(());
+        }
+        match byte {
+            b'"' => {
+                self.in_string = true;
+                self.buf.push(byte);
+            }
+            b'{' | b'[' => {
+                self.depth += 1;
+                self.buf.push(byte);
+            }
+            b'}' | b']' if self.depth > 0 => {
+                self.depth -= 1;
+                self.buf.push(byte);
+            }
+            b',' if self.depth == 0 => self.flush_element(false)?,
+            b']' => {
+                self.flush_element(true)?;
+                self.state = State::End;
+            }
+            byte => self.buf.push(byte),
+        }
+        Ok(())
+    }
+
+    /// Deserializes the buffered batch element.
+    fn flush_element(&mut self, last: bool) -> Result<(), serde_json::Error> {
+        if last && self.batch.is_empty() && self.buf.iter().all(u8::is_ascii_whitespace) {
+            // empty batch response
+            return Ok(());
+        }
+        let response = serde_json::from_slice::<Response>(&self.buf)?;
+        self.batch.push(response);
+        self.buf.clear();
+        Ok(())
+    }
+
+    /// Finishes the deserialization when the response body ends.
+    pub(crate) fn finish(self) -> Result<ResponseObj, serde_json::Error> {
+        match self.state {
+            State::Single => Ok(ResponseObj::Single(serde_json::from_slice(&self.buf)?)),
+            State::End => Ok(ResponseObj::Batch(self.batch)),
+            State::Start | State::Batch => Err(serde_json::Error::custom("unexpected end of response")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use jsonrpc_types::v2::{Error, Id, Value};
+
+    use super::*;
+
+    fn decode(body: &str, chunk_size: usize) -> Result<ResponseObj, serde_json::Error> {
+        let mut decoder = ResponseDecoder::default();
+        for chunk in body.as_bytes().chunks(chunk_size) {
+            decoder.feed(chunk)?;
+        }
+        decoder.finish()
+    }
+
+    #[test]
+    fn decode_response_incrementally() {
+        let single = r#" {"jsonrpc":"2.0","id":1,"result":"x"} "#;
+        let batch = r#"[{"jsonrpc":"2.0","id":1,"result":{"a":["]","\"}"]}},
+            {"jsonrpc":"2.0","error":{"code":-32603,"message":"Internal error"},"id":2}]"#;
+        for chunk_size in [1, 5, 1024] {
+            assert_eq!(
+                decode(single, chunk_size).unwrap(),
+                ResponseObj::Single(Response::success("x".into(), Id::Num(1)))
+            );
+            let mut object = serde_json::Map::new();
+            object.insert("a".into(), vec![Value::from("]"), Value::from("\"}")].into());
+            assert_eq!(
+                decode(batch, chunk_size).unwrap(),
+                ResponseObj::Batch(vec![
+                    Response::success(Value::Object(object), Id::Num(1)),
+                    Response::failure(Error::internal_error(), Some(Id::Num(2))),
+                ])
+            );
+        }
+        assert_eq!(decode("[ ]", 1).unwrap(), ResponseObj::Batch(vec![]));
+
+        assert!(decode("", 1).is_err());
+        assert!(decode(r#"[{"jsonrpc":"2.0","id":1,"result":"x"}"#, 1).is_err());
+        assert!(decode(r#"[{"jsonrpc":"2.0","id":1,"result":"x"},]"#, 1).is_err());
+        assert!(decode(r#"[{"jsonrpc":"2.0","id":1,"result":"x"}] x"#, 1).is_err());
+    }
+}
@@ -1,9 +1,11 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use http::header::{HeaderMap, HeaderValue};
+
 use super::*;
 
 #[test]
 fn http_header() {
-    use http::header::{self, HeaderValue};
-
     // basic auth
     let builder = HttpClientBuilder::new().basic_auth("username", Some("password"));
     let basic_auth = builder.headers.get(header::AUTHORIZATION).unwrap();
@@ -19,6 +21,341 @@ fn http_header() {
     let builder = HttpClientBuilder::new().bearer_auth("Hold my bear");
     let bearer_auth = builder.headers.get(header::AUTHORIZATION).unwrap();
     assert_eq!(bearer_auth, HeaderValue::from_static("Bearer Hold my bear"));
+    let result = HttpClientBuilder::new().bearer_auth("bear\n").build("http://127.0.0.1");
+    assert!(matches!(result, Err(HttpClientError::InvalidConfig(_))));
+
+    // invalid url
+    let result = HttpClient::new("http://[::1");
+    assert!(matches!(result, Err(HttpClientError::InvalidConfig(_))));
+}
+
+#[test]
+fn same_origin() {
+    let origin = |a: &str, b: &str| is_same_origin(&a.parse().unwrap(), &b.parse().unwrap());
+    assert!(origin("http://127.0.0.1/a", "http://127.0.0.1:80/b?c=d"));
+    assert!(origin("https://Example.com/", "https://example.com:443/"));
+    assert!(!origin("http://127.0.0.1/", "https://127.0.0.1/"));
+    assert!(!origin("http://127.0.0.1/", "http://localhost/"));
+    assert!(!origin("http://127.0.0.1:8080/", "http://127.0.0.1:8081/"));
+}
+
+#[test]
+fn redirect_location() {
+    let base = "http://127.0.0.1/a/b?c=d".parse::<Uri>().unwrap();
+    let resolve = |location| resolve_location(&base, &header::HeaderValue::from_static(location)).unwrap();
+    assert_eq!(resolve("http://localhost/x"), "http://localhost/x");
+    assert_eq!(resolve("/x?y=z"), "http://127.0.0.1/x?y=z");
+    assert_eq!(resolve("x"), "http://127.0.0.1/a/x");
+}
+
+/// Makes the JSON-RPC requests against the test server, with the client built by `builder`.
+///
+/// The test server also listens on `other_addr`, which is another origin for the redirects.
+async fn make_jsonrpc_requests(addr: &str, other_addr: &str, builder: impl Fn() -> HttpClientBuilder) {
+    {
+        let client = builder().build(format!("http://{}/v2_no_params", addr)).unwrap();
+        let response = client.request("foo", None).await.unwrap();
+        assert_eq!(response, Response::success(Value::String("x".to_string()), 1.into()));
+    }
+
+    {
+        let client = builder().build(format!("http://{}/v2_params", addr)).unwrap();
+        let response = client.request("bar", Some(Params::Array(vec![]))).await.unwrap();
+        assert_eq!(response, Response::success("y".into(), 1.into()));
+    }
+
+    {
+        let client = builder().build(format!("http://{}/v2_batch", addr)).unwrap();
+        let response = client
+            .request_batch(vec![("foo", None), ("bar", Some(Params::Array(vec![])))])
+            .await
+            .unwrap();
+        assert_eq!(
+            response,
+            vec![
+                Response::success("x".into(), 1.into()),
+                Response::success("y".into(), 2.into()),
+            ]
+        );
+    }
+
+    {
+        let client = builder().build(format!("http://{}/v2_status_failure", addr)).unwrap();
+        let response = client.request("foo", None).await.unwrap();
+        assert_eq!(response, Response::failure(Error::internal_error(), Some(1.into())));
+    }
+
+    {
+        let client = builder().build(format!("http://{}/v2_status_html", addr)).unwrap();
+        let err = client.request("foo", None).await.unwrap_err();
+        match err {
+            HttpClientError::Status { code, body } => {
+                assert_eq!(code, http::StatusCode::BAD_GATEWAY);
+                assert_eq!(body, "<html><body>502 Bad Gateway</body></html>");
+            }
+            err => panic!("unexpected error: {}", err),
+        }
+    }
+
+    {
+        let client = builder()
+            .header(
+                http::header::HeaderName::from_static("x-api-key"),
+                http::header::HeaderValue::from_static("secret"),
+            )
+            .user_agent(http::header::HeaderValue::from_static("jsonrpc-test"))
+            .build(format!("http://{}/v2_headers", addr))
+            .unwrap();
+        let response = client.request("foo", None).await.unwrap();
+        assert_eq!(response, Response::success("x".into(), 1.into()));
+    }
+
+    {
+        // non visible ASCII header values are rejected by every backend
+        let value = HeaderValue::from_bytes(b"caf\xc3\xa9").unwrap();
+        let result = builder()
+            .header(header::HeaderName::from_static("x-name"), value.clone())
+            .build(format!("http://{}/v2_no_params", addr));
+        assert!(matches!(result, Err(HttpClientError::InvalidConfig(_))));
+
+        let client = builder().build(format!("http://{}/v2_no_params", addr)).unwrap();
+        let mut headers = HeaderMap::new();
+        headers.insert("x-name", value);
+        let err = client.with_headers(headers).request("foo", None).await.unwrap_err();
+        assert!(
+            matches!(err, HttpClientError::InvalidConfig(_)),
+            "unexpected error: {}",
+            err
+        );
+    }
+
+    {
+        let client = builder()
+            .timeout(std::time::Duration::from_millis(100))
+            .connect_timeout(std::time::Duration::from_millis(100))
+            .build(format!("http://{}/v2_slow", addr))
+            .unwrap();
+        let err = client.request("foo", None).await.unwrap_err();
+        assert!(
+            matches!(err, HttpClientError::RequestTimeout),
+            "unexpected error: {}",
+            err
+        );
+    }
+
+    {
+        let client = builder().build(format!("http://{}/v2_redirect", addr)).unwrap();
+        let response = client.request("foo", None).await.unwrap();
+        assert_eq!(response, Response::success("x".into(), 1.into()));
+
+        let client = builder()
+            .max_redirects(0)
+            .build(format!("http://{}/v2_redirect", addr))
+            .unwrap();
+        let err = client.request("foo", None).await.unwrap_err();
+        assert!(
+            matches!(err, HttpClientError::Status { code, .. } if code == http::StatusCode::TEMPORARY_REDIRECT),
+            "unexpected error: {}",
+            err
+        );
+
+        // the redirect loop is not retried
+        let policy = RetryPolicy::new()
+            .max_retries(2)
+            .initial_backoff(std::time::Duration::from_millis(1));
+        let client = builder()
+            .max_redirects(3)
+            .retry_policy(policy)
+            .build(format!("http://{}/v2_redirect_loop", addr))
+            .unwrap();
+        let err = client.request("foo", None).await.unwrap_err();
+        assert!(matches!(err, HttpClientError::Http(_)), "unexpected error: {}", err);
+
+        // the credentials are not sent to another origin
+        let client = builder()
+            .bearer_auth("secret")
+            .header(header::PROXY_AUTHORIZATION, HeaderValue::from_static("Basic secret"))
+            .credential_provider(|_refresh: bool| {
+                let mut headers = HeaderMap::new();
+                headers.insert("x-api-token", HeaderValue::from_static("secret"));
+                Ok(headers)
+            })
+            .build(format!(
+                "http://{}/v2_redirect_cross_origin?http://{}/v2_no_credentials",
+                addr, other_addr
+            ))
+            .unwrap();
+        let mut headers = HeaderMap::new();
+        headers.insert(header::COOKIE, HeaderValue::from_static("session=secret"));
+        let response = client.with_headers(headers).request("foo", None).await.unwrap();
+        assert_eq!(response, Response::success("x".into(), 1.into()));
+    }
+
+    {
+        let policy = RetryPolicy::new()
+            .max_retries(2)
+            .initial_backoff(std::time::Duration::from_millis(1))
+            .idempotent_methods(vec!["foo"]);
+        let client = builder()
+            .retry_policy(policy)
+            .build(format!("http://{}/v2_unavailable", addr))
+            .unwrap();
+        let err = client.request("foo", None).await.unwrap_err();
+        match err {
+            HttpClientError::Retry { retries, error } => {
+                assert_eq!(retries, 2);
+                assert!(
+                    matches!(*error, HttpClientError::Status { code, .. } if code == http::StatusCode::SERVICE_UNAVAILABLE)
+                );
+            }
+            err => panic!("unexpected error: {}", err),
+        }
+
+        // non-idempotent methods are not retried
+        let err = client.request("bar", None).await.unwrap_err();
+        assert!(
+            matches!(err, HttpClientError::Status { .. }),
+            "unexpected error: {}",
+            err
+        );
+        let err = client
+            .request_batch(vec![("foo", None), ("bar", None)])
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(err, HttpClientError::Status { .. }),
+            "unexpected error: {}",
+            err
+        );
+    }
+
+    {
+        let client = builder()
+            .gzip(true)
+            .deflate(true)
+            .brotli(true)
+            .gzip_request(true)
+            .build(format!("http://{}/v2_compression", addr))
+            .unwrap();
+        let response = client.request("foo", None).await.unwrap();
+        assert_eq!(response, Response::success("x".into(), 1.into()));
+    }
+
+    {
+        // the stale token is refreshed for the 401 response
+        let refreshes = Arc::new(AtomicUsize::new(0));
+        let counter = refreshes.clone();
+        let client = builder()
+            .credential_provider(move |refresh: bool| {
+                let token = if refresh {
+                    counter.fetch_add(1, Ordering::SeqCst);
+                    "Bearer fresh"
+                } else {
+                    "Bearer stale"
+                };
+                let mut headers = HeaderMap::new();
+                headers.insert(header::AUTHORIZATION, HeaderValue::from_static(token));
+                Ok(headers)
+            })
+            .build(format!("http://{}/v2_auth", addr))
+            .unwrap();
+        let response = client.request("foo", None).await.unwrap();
+        assert_eq!(response, Response::success("x".into(), 1.into()));
+        assert_eq!(refreshes.load(Ordering::SeqCst), 1);
+
+        // the per-call headers override the default headers
+        let client = builder()
+            .bearer_auth("stale")
+            .build(format!("http://{}/v2_auth", addr))
+            .unwrap();
+        let err = client.request("foo", None).await.unwrap_err();
+        assert!(
+            matches!(err, HttpClientError::Status { code, .. } if code == http::StatusCode::UNAUTHORIZED),
+            "unexpected error: {}",
+            err
+        );
+        let mut headers = HeaderMap::new();
+        headers.insert(header::AUTHORIZATION, HeaderValue::from_static("Bearer fresh"));
+        let response = client.with_headers(headers).request("foo", None).await.unwrap();
+        assert_eq!(response, Response::success("x".into(), 1.into()));
+    }
+
+    {
+        let client = builder()
+            .stream_response(true)
+            .gzip(true)
+            .build(format!("http://{}/v2_batch", addr))
+            .unwrap();
+        let response = client
+            .request_batch(vec![("foo", None), ("bar", Some(Params::Array(vec![])))])
+            .await
+            .unwrap();
+        assert_eq!(
+            response,
+            vec![
+                Response::success("x".into(), 1.into()),
+                Response::success("y".into(), 2.into()),
+            ]
+        );
+        let client = builder()
+            .stream_response(true)
+            .build(format!("http://{}/v2_status_html", addr))
+            .unwrap();
+        let result = client.request("foo", None).await;
+        assert!(matches!(result, Err(HttpClientError::Status { .. })));
+
+        let client = builder()
+            .max_response_size(10)
+            .build(format!("http://{}/v2_no_params", addr))
+            .unwrap();
+        let result = client.request("foo", None).await;
+        assert!(matches!(result, Err(HttpClientError::ResponseTooLarge { limit: 10 })));
+    }
+
+    {
+        // the first endpoint is refused, the second one is unavailable
+        let urls = vec![
+            "http://127.0.0.1:1/".to_string(),
+            format!("http://{}/v2_unavailable", addr),
+            format!("http://{}/v2_no_params", addr),
+        ];
+
+        // the request which is not idempotent is only failed over if the connection fails
+        let client = builder().unhealthy_threshold(1).build_multi(urls.clone()).unwrap();
+        let err = client.request("foo", None).await.unwrap_err();
+        assert!(
+            matches!(err, HttpClientError::Status { code, .. } if code == http::StatusCode::SERVICE_UNAVAILABLE),
+            "unexpected error: {}",
+            err
+        );
+
+        let policy = RetryPolicy::new().max_retries(0).idempotent_methods(vec!["foo"]);
+        let client = builder()
+            .unhealthy_threshold(1)
+            .retry_policy(policy)
+            .build_multi(urls)
+            .unwrap();
+        let response = client.request("foo", None).await.unwrap();
+        assert_eq!(response, Response::success("x".into(), 1.into()));
+        assert_eq!(client.endpoints.candidates(), vec![2, 0, 1]);
+
+        let result = HttpClient::builder().build_multi(Vec::<String>::new());
+        assert!(matches!(result, Err(HttpClientError::InvalidConfig(_))));
+    }
+}
+
+fn gzip(data: &[u8]) -> Vec<u8> {
+    use std::io::Write;
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(data).unwrap();
+    encoder.finish().unwrap()
+}
+
+fn gunzip(data: &[u8]) -> String {
+    use std::io::Read;
+    let mut decoded = String::new();
+    flate2::read::GzDecoder::new(data).read_to_string(&mut decoded).unwrap();
+    decoded
 }
 
 #[cfg(feature = "http-async-std")]
@@ -48,59 +385,100 @@ async fn server(addr: &str) -> std::io::Result<()> {
         Ok(tide::Response::from(response))
     }
 
-    server.at("/v2_no_params").post(v2_no_params);
-    server.at("/v2_params").post(v2_params);
-    server.at("/v2_batch").post(v2_batch);
-    server.listen(addr).await
-}
-
-#[cfg(feature = "http-async-std")]
-#[async_std::test]
-async fn make_jsonrpc_request() {
-    let addr = "127.0.0.1:8080";
-    async_std::task::spawn(server(addr));
-
-    {
-        let client = HttpClient::new(format!("http://{}/v2_no_params", addr)).unwrap();
-        let response = client.request("foo", None).await.unwrap();
-        assert_eq!(response, Response::success(Value::String("x".to_string()), 1.into()));
+    async fn v2_status_failure(_req: tide::Request<()>) -> tide::Result {
+        let response = r#"{"jsonrpc":"2.0","error":{"code":-32603,"message":"Internal error"},"id":1}"#;
+        Ok(tide::Response::builder(500).body(response).build())
     }
-
-    {
-        let client = HttpClient::new(format!("http://{}/v2_params", addr)).unwrap();
-        let response = client.request("bar", Some(Params::Array(vec![]))).await.unwrap();
-        assert_eq!(response, Response::success("y".into(), 1.into()));
+    async fn v2_status_html(_req: tide::Request<()>) -> tide::Result {
+        let response = "<html><body>502 Bad Gateway</body></html>";
+        Ok(tide::Response::builder(502).body(response).build())
     }
 
-    {
-        let client = HttpClient::new(format!("http://{}/v2_batch", addr)).unwrap();
-        let response = client
-            .request_batch(vec![("foo", None), ("bar", Some(Params::Array(vec![])))])
-            .await
-            .unwrap();
-        assert_eq!(
-            response,
-            vec![
-                Response::success("x".into(), 1.into()),
-                Response::success("y".into(), 2.into()),
-            ]
-        );
+    async fn v2_headers(req: tide::Request<()>) -> tide::Result {
+        assert_eq!(req.header("x-api-key").unwrap().as_str(), "secret");
+        assert_eq!(req.header("user-agent").unwrap().as_str(), "jsonrpc-test");
+        let response = r#"{"jsonrpc":"2.0","id":1,"result":"x"}"#;
+        Ok(tide::Response::from(response))
+    }
+    async fn v2_slow(_req: tide::Request<()>) -> tide::Result {
+        async_std::task::sleep(std::time::Duration::from_millis(500)).await;
+        let response = r#"{"jsonrpc":"2.0","id":1,"result":"x"}"#;
+        Ok(tide::Response::from(response))
+    }
+    async fn v2_redirect(_req: tide::Request<()>) -> tide::Result {
+        Ok(tide::Response::builder(307).header("location", "/v2_no_params").build())
+    }
+    async fn v2_redirect_loop(_req: tide::Request<()>) -> tide::Result {
+        Ok(tide::Response::builder(307)
+            .header("location", "/v2_redirect_loop")
+            .build())
+    }
+    async fn v2_redirect_cross_origin(req: tide::Request<()>) -> tide::Result {
+        let location = req.url().query().unwrap().to_string();
+        Ok(tide::Response::builder(307).header("location", location).build())
+    }
+    async fn v2_no_credentials(req: tide::Request<()>) -> tide::Result {
+        for name in &["authorization", "cookie", "proxy-authorization", "x-api-token"] {
+            assert!(req.header(*name).is_none(), "unexpected header: {}", name);
+        }
+        let response = r#"{"jsonrpc":"2.0","id":1,"result":"x"}"#;
+        Ok(tide::Response::from(response))
+    }
+    async fn v2_auth(req: tide::Request<()>) -> tide::Result {
+        if req.header("authorization").unwrap().as_str() != "Bearer fresh" {
+            return Ok(tide::Response::builder(401).build());
+        }
+        let response = r#"{"jsonrpc":"2.0","id":1,"result":"x"}"#;
+        Ok(tide::Response::from(response))
+    }
+    async fn v2_unavailable(_req: tide::Request<()>) -> tide::Result {
+        Ok(tide::Response::builder(503).header("retry-after", "0").build())
+    }
+    async fn v2_compression(mut req: tide::Request<()>) -> tide::Result {
+        assert_eq!(req.header("content-encoding").unwrap().as_str(), "gzip");
+        assert_eq!(req.header("accept-encoding").unwrap().as_str(), "gzip, deflate, br");
+        let got = gunzip(&req.body_bytes().await.unwrap());
+        let expected = r#"{"jsonrpc":"2.0","method":"foo","id":1}"#;
+        assert_eq!(got, expected);
+        let response = gzip(br#"{"jsonrpc":"2.0","id":1,"result":"x"}"#);
+        Ok(tide::Response::builder(200)
+            .header("content-encoding", "gzip")
+            .body(response)
+            .build())
     }
+
+    server.at("/v2_no_params").post(v2_no_params);
+    server.at("/v2_params").post(v2_params);
+    server.at("/v2_batch").post(v2_batch);
+    server.at("/v2_status_failure").post(v2_status_failure);
+    server.at("/v2_status_html").post(v2_status_html);
+    server.at("/v2_headers").post(v2_headers);
+    server.at("/v2_slow").post(v2_slow);
+    server.at("/v2_redirect").post(v2_redirect);
+    server.at("/v2_redirect_loop").post(v2_redirect_loop);
+    server.at("/v2_redirect_cross_origin").post(v2_redirect_cross_origin);
+    server.at("/v2_no_credentials").post(v2_no_credentials);
+    server.at("/v2_auth").post(v2_auth);
+    server.at("/v2_unavailable").post(v2_unavailable);
+    server.at("/v2_compression").post(v2_compression);
+    server.listen(addr).await
 }
 
-#[cfg(feature = "http-tokio")]
+#[cfg(any(feature = "http-tokio", feature = "http-hyper"))]
 async fn dispatch_fn(req: hyper::Request<hyper::Body>) -> hyper::Result<hyper::Response<hyper::Body>> {
     use hyper::body::HttpBody as _;
     assert_eq!(req.method(), &hyper::Method::POST);
 
     let path = req.uri().path().to_string();
+    let query = req.uri().query().map(str::to_string);
+    let headers = req.headers().clone();
     let mut content = vec![];
     let mut body = req.into_body();
     while let Some(Ok(chunk)) = body.data().await {
         content.extend(&*chunk);
     }
     match path.as_str() {
-        "/v2_no_params" => {
+        "/" | "/v2_no_params" => {
             let expected = r#"{"jsonrpc":"2.0","method":"foo","id":1}"#;
             assert_eq!(std::str::from_utf8(&content), Ok(expected));
             let response = r#"{"jsonrpc":"2.0","id":1,"result":"x"}"#;
@@ -119,44 +497,192 @@ async fn dispatch_fn(req: hyper::Request<hyper::Body>) -> hyper::Result<hyper::R
             let response = r#"[{"jsonrpc":"2.0","id":1,"result":"x"},{"jsonrpc":"2.0","id":2,"result":"y"}]"#;
             Ok(hyper::Response::new(response.into()))
         }
+        "/v2_status_failure" => {
+            let response = r#"{"jsonrpc":"2.0","error":{"code":-32603,"message":"Internal error"},"id":1}"#;
+            Ok(hyper::Response::builder().status(500).body(response.into()).unwrap())
+        }
+        "/v2_status_html" => {
+            let response = "<html><body>502 Bad Gateway</body></html>";
+            Ok(hyper::Response::builder().status(502).body(response.into()).unwrap())
+        }
+        "/v2_headers" => {
+            assert_eq!(headers["x-api-key"], "secret");
+            assert_eq!(headers["user-agent"], "jsonrpc-test");
+            let response = r#"{"jsonrpc":"2.0","id":1,"result":"x"}"#;
+            Ok(hyper::Response::new(response.into()))
+        }
+        "/v2_slow" => {
+            tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+            let response = r#"{"jsonrpc":"2.0","id":1,"result":"x"}"#;
+            Ok(hyper::Response::new(response.into()))
+        }
+        "/v2_redirect" => Ok(hyper::Response::builder()
+            .status(307)
+            .header("location", "/v2_no_params")
+            .body(hyper::Body::empty())
+            .unwrap()),
+        "/v2_redirect_loop" => Ok(hyper::Response::builder()
+            .status(307)
+            .header("location", "/v2_redirect_loop")
+            .body(hyper::Body::empty())
+            .unwrap()),
+        "/v2_redirect_cross_origin" => Ok(hyper::Response::builder()
+            .status(307)
+            .header("location", query.unwrap())
+            .body(hyper::Body::empty())
+            .unwrap()),
+        "/v2_no_credentials" => {
+            for name in &["authorization", "cookie", "proxy-authorization", "x-api-token"] {
+                assert!(!headers.contains_key(*name), "unexpected header: {}", name);
+            }
+            let response = r#"{"jsonrpc":"2.0","id":1,"result":"x"}"#;
+            Ok(hyper::Response::new(response.into()))
+        }
+        "/v2_compression" => {
+            assert_eq!(headers["content-encoding"], "gzip");
+            assert_eq!(headers["accept-encoding"], "gzip, deflate, br");
+            let expected = r#"{"jsonrpc":"2.0","method":"foo","id":1}"#;
+            assert_eq!(gunzip(&content), expected);
+            let response = gzip(br#"{"jsonrpc":"2.0","id":1,"result":"x"}"#);
+            Ok(hyper::Response::builder()
+                .header("content-encoding", "gzip")
+                .body(response.into())
+                .unwrap())
+        }
+        "/v2_auth" => {
+            if headers["authorization"] != "Bearer fresh" {
+                return Ok(hyper::Response::builder()
+                    .status(401)
+                    .body(hyper::Body::empty())
+                    .unwrap());
+            }
+            let response = r#"{"jsonrpc":"2.0","id":1,"result":"x"}"#;
+            Ok(hyper::Response::new(response.into()))
+        }
+        "/v2_unavailable" => Ok(hyper::Response::builder()
+            .status(503)
+            .header("retry-after", "0")
+            .body(hyper::Body::empty())
+            .unwrap()),
         _ => unreachable!(),
     }
 }
 
-#[cfg(feature = "http-tokio")]
-#[tokio::test]
-async fn make_jsonrpc_request() {
+#[cfg(any(feature = "http-tokio", feature = "http-hyper"))]
+fn spawn_hyper_server(addr: &str) {
     use hyper::service::{make_service_fn, service_fn};
 
-    let addr = "127.0.0.1:8080";
     let service = make_service_fn(|_| async { Ok::<_, hyper::Error>(service_fn(dispatch_fn)) });
     let server = hyper::Server::bind(&addr.parse().unwrap()).serve(service);
     tokio::spawn(server);
+}
+
+#[cfg(any(feature = "http-tokio", feature = "http-hyper"))]
+#[tokio::test]
+async fn make_jsonrpc_request_with_http2() {
+    use hyper::service::{make_service_fn, service_fn};
+
+    let addr = "127.0.0.1:8083";
+    let service = make_service_fn(|_| async { Ok::<_, hyper::Error>(service_fn(dispatch_fn)) });
+    let server = hyper::Server::bind(&addr.parse().unwrap())
+        .http2_only(true)
+        .serve(service);
+    tokio::spawn(server);
 
-    {
-        let client = HttpClient::new(format!("http://{}/v2_no_params", addr)).unwrap();
+    let client = HttpClient::builder()
+        .http2_prior_knowledge(true)
+        .pool_idle_timeout(std::time::Duration::from_secs(10))
+        .pool_max_idle_per_host(1)
+        .tcp_keepalive(std::time::Duration::from_secs(10))
+        .tcp_nodelay(true)
+        .id_generator(|| Id::Num(1))
+        .build(format!("http://{}/v2_no_params", addr))
+        .unwrap();
+    for _ in 0..3 {
         let response = client.request("foo", None).await.unwrap();
-        assert_eq!(response, Response::success(Value::String("x".to_string()), 1.into()));
+        assert_eq!(response, Response::success("x".into(), 1.into()));
     }
+}
 
-    {
-        let client = HttpClient::new(format!("http://{}/v2_params", addr)).unwrap();
-        let response = client.request("bar", Some(Params::Array(vec![]))).await.unwrap();
-        assert_eq!(response, Response::success("y".into(), 1.into()));
-    }
+#[cfg(all(unix, feature = "http-hyper"))]
+#[tokio::test]
+async fn make_jsonrpc_request_over_unix_socket() {
+    let path = std::env::temp_dir().join(format!("jsonrpc-test-{}.sock", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let listener = tokio::net::UnixListener::bind(&path).unwrap();
+    tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            let service = hyper::service::service_fn(dispatch_fn);
+            tokio::spawn(hyper::server::conn::Http::new().serve_connection(stream, service));
+        }
+    });
 
-    {
-        let client = HttpClient::new(format!("http://{}/v2_batch", addr)).unwrap();
-        let response = client
-            .request_batch(vec![("foo", None), ("bar", Some(Params::Array(vec![])))])
-            .await
-            .unwrap();
-        assert_eq!(
-            response,
-            vec![
-                Response::success("x".into(), 1.into()),
-                Response::success("y".into(), 2.into()),
-            ]
-        );
+    let client = HttpClient::builder()
+        .unix_socket(&path)
+        .build("http://localhost/v2_batch")
+        .unwrap();
+    let response = client
+        .request_batch(vec![("foo", None), ("bar", Some(Params::Array(vec![])))])
+        .await
+        .unwrap();
+    assert_eq!(
+        response,
+        vec![
+            Response::success("x".into(), 1.into()),
+            Response::success("y".into(), 2.into()),
+        ]
+    );
+
+    let client = HttpClient::new(format!("unix://{}", path.display())).unwrap();
+    let response = client.request("foo", None).await.unwrap();
+    assert_eq!(response, Response::success("x".into(), 1.into()));
+
+    let result = HttpClient::builder().build_multi(vec![
+        format!("unix://{}", path.display()),
+        "http://localhost/".to_string(),
+    ]);
+    assert!(matches!(result, Err(HttpClientError::InvalidConfig(_))));
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[cfg(feature = "http-async-std")]
+#[async_std::test]
+async fn make_jsonrpc_request_with_surf() {
+    let (addr, other_addr) = ("127.0.0.1:8080", "127.0.0.1:8090");
+    async_std::task::spawn(server(addr));
+    async_std::task::spawn(server(other_addr));
+    for addr in &[addr, other_addr] {
+        // the server is spawned before listening
+        while async_std::net::TcpStream::connect(addr).await.is_err() {
+            async_std::task::sleep(std::time::Duration::from_millis(10)).await;
+        }
     }
+
+    make_jsonrpc_requests(addr, other_addr, || {
+        HttpClient::builder().backend(SurfBackend::new().unwrap())
+    })
+    .await;
+}
+
+#[cfg(feature = "http-tokio")]
+#[tokio::test]
+async fn make_jsonrpc_request_with_reqwest() {
+    let (addr, other_addr) = ("127.0.0.1:8081", "127.0.0.1:8091");
+    spawn_hyper_server(addr);
+    spawn_hyper_server(other_addr);
+
+    make_jsonrpc_requests(addr, other_addr, || {
+        HttpClient::builder().backend(ReqwestBackend::new().unwrap())
+    })
+    .await;
+}
+
+#[cfg(feature = "http-hyper")]
+#[tokio::test]
+async fn make_jsonrpc_request_with_hyper() {
+    let (addr, other_addr) = ("127.0.0.1:8082", "127.0.0.1:8092");
+    spawn_hyper_server(addr);
+    spawn_hyper_server(other_addr);
+
+    make_jsonrpc_requests(addr, other_addr, || HttpClient::builder().backend(HyperBackend::new())).await;
 }
@@ -0,0 +1,133 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use jsonrpc_types::v2::Id;
+
+/// A generator of JSON-RPC request IDs.
+///
+/// Implemented for any `Fn() -> Id`, so a closure can be used directly.
+pub trait IdGenerator: Send + Sync {
+    /// Returns the ID of the next request.
+    fn next_id(&self) -> Id;
+}
+
+impl<F> IdGenerator for F
+where
+    F: Fn() -> Id + Send + Sync,
+{
+    fn next_id(&self) -> Id {
+        self()
+    }
+}
+
+/// Generates sequential numeric IDs, starting from 1 by default.
+#[derive(Debug)]
+pub struct SequentialIdGenerator {
+    next: AtomicU64,
+}
+
+impl Default for SequentialIdGenerator {
+    fn default() -> Self {
+        Self::new(1)
+    }
+}
+
+impl SequentialIdGenerator {
+    /// Creates a new generator with the given start ID.
+    pub fn new(start: u64) -> Self {
+        Self {
+            next: AtomicU64::new(start),
+        }
+    }
+}
+
+impl IdGenerator for SequentialIdGenerator {
+    fn next_id(&self) -> Id {
+        Id::Num(self.next.fetch_add(1, Ordering::AcqRel))
+    }
+}
+
+/// Generates random numeric IDs.
+///
+/// The IDs are less than 2^53, so that they can be represented exactly by the servers
+/// that parse JSON numbers as double-precision floats.
+#[cfg(feature = "id-random")]
+#[derive(Debug, Default)]
+pub struct RandomIdGenerator;
+
+#[cfg(feature = "id-random")]
+impl IdGenerator for RandomIdGenerator {
+    fn next_id(&self) -> Id {
+        Id::Num(rand::random::<u64>() >> 11)
+    }
+}
+
+/// Generates random UUID (version 4) string IDs.
+#[cfg(feature = "id-uuid")]
+#[derive(Debug, Default)]
+pub struct UuidIdGenerator;
+
+#[cfg(feature = "id-uuid")]
+impl IdGenerator for UuidIdGenerator {
+    fn next_id(&self) -> Id {
+        Id::Str(uuid::Uuid::new_v4().to_string())
+    }
+}
+
+/// Generates sequential string IDs with a prefix, e.g. `client-1`, `client-2`, ...
+#[derive(Debug)]
+pub struct PrefixedIdGenerator {
+    prefix: String,
+    next: AtomicU64,
+}
+
+impl PrefixedIdGenerator {
+    /// Creates a new generator with the given prefix, the sequence starts from 1.
+    pub fn new(prefix: impl Into<String>) -> Self {
+        Self {
+            prefix: prefix.into(),
+            next: AtomicU64::new(1),
+        }
+    }
+}
+
+impl IdGenerator for PrefixedIdGenerator {
+    fn next_id(&self) -> Id {
+        let id = self.next.fetch_add(1, Ordering::AcqRel);
+        Id::Str(format!("{}{}", self.prefix, id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generate_ids() {
+        let generator = SequentialIdGenerator::default();
+        assert_eq!(generator.next_id(), Id::Num(1));
+        assert_eq!(generator.next_id(), Id::Num(2));
+
+        let generator = PrefixedIdGenerator::new("client-");
+        assert_eq!(generator.next_id(), Id::Str("client-1".into()));
+        assert_eq!(generator.next_id(), Id::Str("client-2".into()));
+
+        let generator = || Id::Str("fixed".into());
+        assert_eq!(generator.next_id(), Id::Str("fixed".into()));
+    }
+
+    #[cfg(feature = "id-random")]
+    #[test]
+    fn generate_random_ids() {
+        let generator = RandomIdGenerator;
+        assert!(matches!(generator.next_id(), Id::Num(id) if id < 1 << 53));
+    }
+
+    #[cfg(feature = "id-uuid")]
+    #[test]
+    fn generate_uuid_ids() {
+        let generator = UuidIdGenerator;
+        let id = generator.next_id();
+        assert_eq!(id.as_str().map(str::len), Some(36));
+        assert_ne!(id, generator.next_id());
+    }
+}
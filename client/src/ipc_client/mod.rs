@@ -0,0 +1,46 @@
+mod builder;
+#[cfg(test)]
+mod tests;
+
+use jsonrpc_types::v2::*;
+
+pub use self::builder::IpcClientBuilder;
+use crate::{
+    duplex::{impl_duplex_transports, DuplexSubscription, Frontend},
+    error::IpcClientError,
+};
+
+/// IPC JSON-RPC client, which sends the messages over a Unix domain socket (or any byte stream).
+///
+/// By default, the messages sent by the client are delimited by newlines, while the messages
+/// received from the server may be delimited by newlines or not delimited at all.
+/// See `IpcClientBuilder::framing` for other framings.
+#[derive(Clone)]
+pub struct IpcClient {
+    frontend: Frontend<IpcClientError>,
+}
+
+impl IpcClient {
+    /// Creates a new IPC JSON-RPC client connected to the Unix domain socket at the `path`.
+    #[cfg(unix)]
+    pub async fn new(path: impl AsRef<std::path::Path>) -> Result<Self, IpcClientError> {
+        IpcClientBuilder::new().build(path).await
+    }
+
+    /// Creates a `IpcClientBuilder` to configure a `IpcClient`.
+    ///
+    /// This is the same as `IpcClientBuilder::new()`.
+    pub fn builder() -> IpcClientBuilder {
+        IpcClientBuilder::new()
+    }
+
+    /// Sends a notification to the server, which returns once the notification is sent.
+    pub async fn notify(&self, method: impl Into<String>, params: Option<Params>) -> Result<(), IpcClientError> {
+        self.frontend.send_notification(method, params).await
+    }
+}
+
+/// Active subscription on an IPC client.
+pub type IpcSubscription<Notif> = DuplexSubscription<Notif>;
+
+impl_duplex_transports!(IpcClient, IpcClientError);
@@ -0,0 +1,140 @@
+use super::*;
+use crate::transport::Transport;
+
+/// Serves the JSON-RPC requests of one IPC connection, the responses are written without delimiters.
+#[cfg(all(unix, feature = "ipc-tokio"))]
+async fn serve(stream: tokio::net::UnixStream) {
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+    let (reader, mut writer) = stream.into_split();
+    let mut lines = BufReader::new(reader).lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        let response = match line.as_str() {
+            r#"{"jsonrpc":"2.0","method":"foo","id":1}"# => r#"{"jsonrpc":"2.0","id":1,"result":"x"}"#.to_string(),
+            r#"[{"jsonrpc":"2.0","method":"foo","id":2},{"jsonrpc":"2.0","method":"bar","params":[],"id":3}]"# => {
+                r#"[{"jsonrpc":"2.0","id":2,"result":"x"},{"jsonrpc":"2.0","id":3,"result":"y"}]"#.to_string()
+            }
+            r#"{"jsonrpc":"2.0","method":"subscribe","id":4}"# => concat!(
+                r#"{"jsonrpc":"2.0","id":4,"result":"0x1"}"#,
+                r#"{"jsonrpc":"2.0","method":"notify","params":{"subscription":"0x1","result":"z"}}"#
+            )
+            .to_string(),
+            r#"{"jsonrpc":"2.0","method":"unsubscribe","params":["0x1"],"id":5}"# => {
+                r#"{"jsonrpc":"2.0","id":5,"result":true}"#.to_string()
+            }
+            request => panic!("unexpected request: {}", request),
+        };
+        writer.write_all(response.as_bytes()).await.unwrap();
+    }
+}
+
+#[cfg(all(unix, feature = "ipc-tokio"))]
+#[tokio::test]
+async fn make_jsonrpc_requests_over_unix_socket() {
+    use crate::transport::{BatchTransport, PubsubTransport};
+
+    let path = std::env::temp_dir().join(format!("jsonrpc-ipc-test-{}.sock", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let listener = tokio::net::UnixListener::bind(&path).unwrap();
+    tokio::spawn(async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        serve(stream).await;
+    });
+
+    let client = IpcClient::new(&path).await.unwrap();
+    let response = client.request("foo", None).await.unwrap();
+    assert_eq!(response, Response::success("x".into(), 1.into()));
+
+    let response = client
+        .request_batch(vec![("foo", None), ("bar", Some(Params::Array(vec![])))])
+        .await
+        .unwrap();
+    assert_eq!(
+        response,
+        vec![
+            Response::success("x".into(), 2.into()),
+            Response::success("y".into(), 3.into()),
+        ]
+    );
+
+    let (id, mut subscription) = client.subscribe("subscribe", None).await.unwrap();
+    assert_eq!(id, Id::Str("0x1".into()));
+    let notification = subscription.next().await.unwrap();
+    assert_eq!(notification.params.result, Value::from("z"));
+    assert!(client.unsubscribe("unsubscribe", id).await.unwrap());
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[cfg(all(unix, feature = "ipc-async-std"))]
+#[async_std::test]
+async fn make_jsonrpc_request_over_stream() {
+    use futures::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+    let (client_stream, server_stream) = async_std::os::unix::net::UnixStream::pair().unwrap();
+    async_std::task::spawn(async move {
+        let mut line = String::new();
+        BufReader::new(&server_stream).read_line(&mut line).await.unwrap();
+        assert_eq!(line, "{\"jsonrpc\":\"2.0\",\"method\":\"foo\",\"id\":1}\n");
+        // the response is split into two writes
+        let mut writer = &server_stream;
+        writer.write_all(br#"{"jsonrpc":"2.0","#).await.unwrap();
+        writer.flush().await.unwrap();
+        writer.write_all(br#""id":1,"result":"x"}"#).await.unwrap();
+    });
+
+    let (client, task) = IpcClient::builder().build_with_stream(client_stream);
+    async_std::task::spawn(task);
+    let response = client.request("foo", None).await.unwrap();
+    assert_eq!(response, Response::success("x".into(), 1.into()));
+}
+
+#[cfg(all(unix, feature = "ipc-async-std"))]
+#[async_std::test]
+async fn handle_server_requests_over_stream() {
+    use futures::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+    use crate::duplex::IncomingHandler;
+
+    /// Responds the requests sent by the server with the method names.
+    struct MethodHandler;
+
+    #[async_trait::async_trait]
+    impl IncomingHandler for MethodHandler {
+        async fn handle_request(&self, method: String, _params: Option<Params>) -> Result<Value, Error> {
+            Ok(Value::from(method))
+        }
+    }
+
+    let (client_stream, server_stream) = async_std::os::unix::net::UnixStream::pair().unwrap();
+    let server = async_std::task::spawn(async move {
+        let mut reader = BufReader::new(&server_stream);
+        let mut line = String::new();
+        reader.read_line(&mut line).await.unwrap();
+        assert_eq!(line, "{\"jsonrpc\":\"2.0\",\"method\":\"foo\",\"id\":1}\n");
+        // the request of client is answered after the client answers the request of server.
+        let mut writer = &server_stream;
+        writer
+            .write_all(br#"{"jsonrpc":"2.0","method":"ping","id":"s1"}"#)
+            .await
+            .unwrap();
+        let mut response = String::new();
+        reader.read_line(&mut response).await.unwrap();
+        writer
+            .write_all(br#"{"jsonrpc":"2.0","id":1,"result":"x"}"#)
+            .await
+            .unwrap();
+        response
+    });
+
+    let (client, task) = IpcClient::builder()
+        .handler(MethodHandler)
+        .build_with_stream(client_stream);
+    async_std::task::spawn(task);
+    let response = client.request("foo", None).await.unwrap();
+    assert_eq!(response, Response::success("x".into(), 1.into()));
+    assert_eq!(
+        server.await,
+        "{\"jsonrpc\":\"2.0\",\"result\":\"ping\",\"id\":\"s1\"}\n"
+    );
+}
@@ -0,0 +1,103 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{Arc, Mutex},
+};
+
+use jsonrpc_types::v2::*;
+
+use crate::{
+    layer::{delegate_batch_transport, delegate_pubsub_transport, Layer},
+    transport::Transport,
+};
+
+/// The layer that caches the successful responses of the methods whose results never change,
+/// e.g. the chain id of a blockchain node.
+#[derive(Clone, Debug)]
+pub struct CacheLayer {
+    methods: Arc<HashSet<String>>,
+}
+
+impl CacheLayer {
+    /// Creates a new cache layer, which caches the responses of the `methods` by their parameters.
+    pub fn new<I, M>(methods: I) -> Self
+    where
+        I: IntoIterator<Item = M>,
+        M: Into<String>,
+    {
+        Self {
+            methods: Arc::new(methods.into_iter().map(Into::into).collect()),
+        }
+    }
+}
+
+impl<T> Layer<T> for CacheLayer {
+    type Transport = Cache<T>;
+
+    fn layer(&self, inner: T) -> Self::Transport {
+        Cache {
+            inner,
+            methods: self.methods.clone(),
+            responses: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+}
+
+/// The transport that caches the successful responses of the inner transport.
+///
+/// The cached response is returned as it is, including the request id of the first request.
+/// The batch requests and subscriptions are not cached.
+#[derive(Clone, Debug)]
+pub struct Cache<T> {
+    inner: T,
+    methods: Arc<HashSet<String>>,
+    /// The cached responses by the method and serialized parameters.
+    responses: Arc<Mutex<HashMap<(String, String), Response>>>,
+}
+
+impl<T> Cache<T> {
+    /// Returns the inner transport.
+    pub fn inner(&self) -> &T {
+        &self.inner
+    }
+
+    /// Removes all cached responses.
+    pub fn clear(&self) {
+        self.responses.lock().expect("lock cached responses").clear();
+    }
+}
+
+#[async_trait::async_trait]
+impl<T> Transport for Cache<T>
+where
+    T: Transport + Send + Sync,
+    T::Error: Send,
+{
+    type Error = T::Error;
+
+    async fn request<M>(&self, method: M, params: Option<Params>) -> Result<Response, Self::Error>
+    where
+        M: Into<String> + Send,
+    {
+        let method = method.into();
+        if !self.methods.contains(&method) {
+            return self.inner.request(method, params).await;
+        }
+
+        let key = (method, serde_json::to_string(&params).expect("serialize params; qed"));
+        if let Some(response) = self.responses.lock().expect("lock cached responses").get(&key) {
+            log::debug!("[layer] Cached response: method={}, params={}", key.0, key.1);
+            return Ok(response.clone());
+        }
+        let response = self.inner.request(key.0.clone(), params).await?;
+        if let Response::Success(_) = response {
+            self.responses
+                .lock()
+                .expect("lock cached responses")
+                .insert(key, response.clone());
+        }
+        Ok(response)
+    }
+}
+
+delegate_batch_transport!(Cache);
+delegate_pubsub_transport!(Cache);
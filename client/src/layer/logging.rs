@@ -0,0 +1,136 @@
+use std::time::Instant;
+
+use jsonrpc_types::v2::*;
+
+use crate::{
+    layer::{delegate_pubsub_transport, Layer},
+    transport::{BatchTransport, Transport},
+};
+
+/// The layer that logs the requests and responses of the transport.
+#[derive(Copy, Clone, Debug)]
+pub struct LoggingLayer {
+    level: log::Level,
+}
+
+impl Default for LoggingLayer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LoggingLayer {
+    /// Creates a new logging layer.
+    pub fn new() -> Self {
+        Self {
+            level: log::Level::Debug,
+        }
+    }
+
+    /// Sets the log level of the requests and responses, the transport errors are always logged
+    /// at `Warn` level.
+    ///
+    /// Default is `Debug`.
+    pub fn level(mut self, level: log::Level) -> Self {
+        self.level = level;
+        self
+    }
+}
+
+impl<T> Layer<T> for LoggingLayer {
+    type Transport = Logging<T>;
+
+    fn layer(&self, inner: T) -> Self::Transport {
+        Logging {
+            inner,
+            level: self.level,
+        }
+    }
+}
+
+/// The transport that logs the requests and responses of the inner transport.
+#[derive(Clone, Debug)]
+pub struct Logging<T> {
+    inner: T,
+    level: log::Level,
+}
+
+impl<T> Logging<T> {
+    /// Returns the inner transport.
+    pub fn inner(&self) -> &T {
+        &self.inner
+    }
+}
+
+#[async_trait::async_trait]
+impl<T> Transport for Logging<T>
+where
+    T: Transport + Send + Sync,
+    T::Error: Send,
+{
+    type Error = T::Error;
+
+    async fn request<M>(&self, method: M, params: Option<Params>) -> Result<Response, Self::Error>
+    where
+        M: Into<String> + Send,
+    {
+        let method = method.into();
+        log::log!(self.level, "[layer] Request: method={}, params={:?}", method, params);
+        let start = Instant::now();
+        let result = self.inner.request(method.clone(), params).await;
+        match &result {
+            Ok(response) => log::log!(
+                self.level,
+                "[layer] Response: method={}, elapsed={:?}, response={}",
+                method,
+                start.elapsed(),
+                response
+            ),
+            Err(err) => log::warn!(
+                "[layer] Request error: method={}, elapsed={:?}, error={}",
+                method,
+                start.elapsed(),
+                err
+            ),
+        }
+        result
+    }
+}
+
+#[async_trait::async_trait]
+impl<T> BatchTransport for Logging<T>
+where
+    T: BatchTransport + Send + Sync,
+    T::Error: Send,
+{
+    async fn request_batch<I, M>(&self, batch: I) -> Result<BatchResponse, Self::Error>
+    where
+        I: IntoIterator<Item = (M, Option<Params>)> + Send,
+        I::IntoIter: Send,
+        M: Into<String>,
+    {
+        let batch = batch
+            .into_iter()
+            .map(|(method, params)| (method.into(), params))
+            .collect::<Vec<(String, Option<Params>)>>();
+        log::log!(self.level, "[layer] Batch request: {:?}", batch);
+        let start = Instant::now();
+        let result = self.inner.request_batch(batch).await;
+        match &result {
+            Ok(responses) => log::log!(
+                self.level,
+                "[layer] Batch response: elapsed={:?}, responses={:?}",
+                start.elapsed(),
+                responses
+            ),
+            Err(err) => log::warn!(
+                "[layer] Batch request error: elapsed={:?}, error={}",
+                start.elapsed(),
+                err
+            ),
+        }
+        result
+    }
+}
+
+delegate_pubsub_transport!(Logging);
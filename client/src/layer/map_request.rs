@@ -0,0 +1,129 @@
+use std::sync::Arc;
+
+use jsonrpc_types::v2::*;
+
+use crate::{
+    layer::Layer,
+    transport::{BatchTransport, PubsubTransport, Transport},
+};
+
+type RequestMapper = Arc<dyn Fn(&str, Option<Params>) -> Option<Params> + Send + Sync>;
+
+/// The layer that maps the parameters of the requests before they're sent, e.g. signs the
+/// requests by adding the signature to the parameters.
+#[derive(Clone)]
+pub struct MapRequestLayer {
+    mapper: RequestMapper,
+}
+
+impl MapRequestLayer {
+    /// Creates a new layer, the `mapper` is called with the method and parameters of every
+    /// request, and returns the new parameters.
+    pub fn new<F>(mapper: F) -> Self
+    where
+        F: Fn(&str, Option<Params>) -> Option<Params> + Send + Sync + 'static,
+    {
+        Self {
+            mapper: Arc::new(mapper),
+        }
+    }
+}
+
+impl<T> Layer<T> for MapRequestLayer {
+    type Transport = MapRequest<T>;
+
+    fn layer(&self, inner: T) -> Self::Transport {
+        MapRequest {
+            inner,
+            mapper: self.mapper.clone(),
+        }
+    }
+}
+
+/// The transport that maps the parameters of the requests sent by the inner transport.
+///
+/// The subscribe requests are mapped too, but the unsubscribe requests are not, since their
+/// parameters are built by the inner transport.
+#[derive(Clone)]
+pub struct MapRequest<T> {
+    inner: T,
+    mapper: RequestMapper,
+}
+
+impl<T> MapRequest<T> {
+    /// Returns the inner transport.
+    pub fn inner(&self) -> &T {
+        &self.inner
+    }
+}
+
+#[async_trait::async_trait]
+impl<T> Transport for MapRequest<T>
+where
+    T: Transport + Send + Sync,
+    T::Error: Send,
+{
+    type Error = T::Error;
+
+    async fn request<M>(&self, method: M, params: Option<Params>) -> Result<Response, Self::Error>
+    where
+        M: Into<String> + Send,
+    {
+        let method = method.into();
+        let params = (self.mapper)(&method, params);
+        self.inner.request(method, params).await
+    }
+}
+
+#[async_trait::async_trait]
+impl<T> BatchTransport for MapRequest<T>
+where
+    T: BatchTransport + Send + Sync,
+    T::Error: Send,
+{
+    async fn request_batch<I, M>(&self, batch: I) -> Result<BatchResponse, Self::Error>
+    where
+        I: IntoIterator<Item = (M, Option<Params>)> + Send,
+        I::IntoIter: Send,
+        M: Into<String>,
+    {
+        let batch = batch
+            .into_iter()
+            .map(|(method, params)| {
+                let method = method.into();
+                let params = (self.mapper)(&method, params);
+                (method, params)
+            })
+            .collect::<Vec<_>>();
+        self.inner.request_batch(batch).await
+    }
+}
+
+#[async_trait::async_trait]
+impl<T> PubsubTransport for MapRequest<T>
+where
+    T: PubsubTransport + Send + Sync,
+    T::Error: Send,
+{
+    type NotificationStream = T::NotificationStream;
+
+    async fn subscribe<M>(
+        &self,
+        subscribe_method: M,
+        params: Option<Params>,
+    ) -> Result<(Id, Self::NotificationStream), Self::Error>
+    where
+        M: Into<String> + Send,
+    {
+        let subscribe_method = subscribe_method.into();
+        let params = (self.mapper)(&subscribe_method, params);
+        self.inner.subscribe(subscribe_method, params).await
+    }
+
+    async fn unsubscribe<M>(&self, unsubscribe_method: M, subscription_id: Id) -> Result<bool, Self::Error>
+    where
+        M: Into<String> + Send,
+    {
+        self.inner.unsubscribe(unsubscribe_method, subscription_id).await
+    }
+}
@@ -0,0 +1,97 @@
+use std::{sync::Arc, time::Duration, time::Instant};
+
+use jsonrpc_types::v2::*;
+
+use crate::{
+    layer::{delegate_batch_transport, delegate_pubsub_transport, Layer},
+    transport::Transport,
+};
+
+type Recorder = Arc<dyn Fn(&RequestMetrics<'_>) + Send + Sync>;
+
+/// The metrics of a request sent by the transport.
+#[derive(Clone, Debug)]
+pub struct RequestMetrics<'a> {
+    /// The method of the request.
+    pub method: &'a str,
+    /// The time elapsed from sending the request to receiving the response or error.
+    pub elapsed: Duration,
+    /// `Some(true)` if the response is successful, `Some(false)` if the response is a failure,
+    /// and `None` if the request fails with a transport error.
+    pub success: Option<bool>,
+}
+
+/// The layer that records the metrics of the requests by a recorder function, e.g. counts the
+/// requests and their latencies in a metrics library.
+#[derive(Clone)]
+pub struct MetricsLayer {
+    recorder: Recorder,
+}
+
+impl MetricsLayer {
+    /// Creates a new metrics layer, the `recorder` is called after every request.
+    pub fn new<F>(recorder: F) -> Self
+    where
+        F: Fn(&RequestMetrics<'_>) + Send + Sync + 'static,
+    {
+        Self {
+            recorder: Arc::new(recorder),
+        }
+    }
+}
+
+impl<T> Layer<T> for MetricsLayer {
+    type Transport = Metrics<T>;
+
+    fn layer(&self, inner: T) -> Self::Transport {
+        Metrics {
+            inner,
+            recorder: self.recorder.clone(),
+        }
+    }
+}
+
+/// The transport that records the metrics of the requests sent by the inner transport.
+#[derive(Clone)]
+pub struct Metrics<T> {
+    inner: T,
+    recorder: Recorder,
+}
+
+impl<T> Metrics<T> {
+    /// Returns the inner transport.
+    pub fn inner(&self) -> &T {
+        &self.inner
+    }
+}
+
+#[async_trait::async_trait]
+impl<T> Transport for Metrics<T>
+where
+    T: Transport + Send + Sync,
+    T::Error: Send,
+{
+    type Error = T::Error;
+
+    async fn request<M>(&self, method: M, params: Option<Params>) -> Result<Response, Self::Error>
+    where
+        M: Into<String> + Send,
+    {
+        let method = method.into();
+        let start = Instant::now();
+        let result = self.inner.request(method.clone(), params).await;
+        let metrics = RequestMetrics {
+            method: &method,
+            elapsed: start.elapsed(),
+            success: result
+                .as_ref()
+                .ok()
+                .map(|response| matches!(response, Response::Success(_))),
+        };
+        (self.recorder)(&metrics);
+        result
+    }
+}
+
+delegate_batch_transport!(Metrics);
+delegate_pubsub_transport!(Metrics);
@@ -0,0 +1,189 @@
+mod cache;
+mod logging;
+mod map_request;
+mod metrics;
+mod retry;
+#[cfg(test)]
+mod tests;
+
+pub use self::{
+    cache::{Cache, CacheLayer},
+    logging::{Logging, LoggingLayer},
+    map_request::{MapRequest, MapRequestLayer},
+    metrics::{Metrics, MetricsLayer, RequestMetrics},
+    retry::{Retry, RetryLayer},
+};
+use crate::transport::Transport;
+
+/// Decorates a transport with another transport, e.g. adds logging or retries to the requests.
+///
+/// This is similar to the `Layer` of `tower`, so that the middlewares can be implemented once
+/// and stacked on any transport.
+pub trait Layer<T> {
+    /// The transport wrapping the inner transport.
+    type Transport;
+
+    /// Wraps the `inner` transport.
+    fn layer(&self, inner: T) -> Self::Transport;
+}
+
+/// The layer that returns the inner transport as it is.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct Identity;
+
+impl<T> Layer<T> for Identity {
+    type Transport = T;
+
+    fn layer(&self, inner: T) -> Self::Transport {
+        inner
+    }
+}
+
+/// Two layers stacked, the `inner` layer wraps the transport first, then the `outer` layer.
+#[derive(Copy, Clone, Debug)]
+pub struct Stack<Inner, Outer> {
+    inner: Inner,
+    outer: Outer,
+}
+
+impl<Inner, Outer> Stack<Inner, Outer> {
+    /// Creates a new stack of layers.
+    pub fn new(inner: Inner, outer: Outer) -> Self {
+        Self { inner, outer }
+    }
+}
+
+impl<T, Inner, Outer> Layer<T> for Stack<Inner, Outer>
+where
+    Inner: Layer<T>,
+    Outer: Layer<Inner::Transport>,
+{
+    type Transport = Outer::Transport;
+
+    fn layer(&self, inner: T) -> Self::Transport {
+        self.outer.layer(self.inner.layer(inner))
+    }
+}
+
+/// A builder of the layer stack.
+///
+/// The layers are applied in the order they're added, so the first added layer is the outermost
+/// one, which sees the requests first.
+///
+/// # Example
+///
+/// ```
+/// use async_jsonrpc_client::{CacheLayer, LayerBuilder, LoggingLayer, RetryLayer};
+///
+/// // logs every request once, and retries the requests of the uncached methods.
+/// let layers = LayerBuilder::new()
+///     .layer(LoggingLayer::new())
+///     .layer(CacheLayer::new(vec!["eth_chainId"]))
+///     .layer(RetryLayer::new(3));
+/// // let client = layers.build(client);
+/// ```
+#[derive(Clone, Debug)]
+pub struct LayerBuilder<L> {
+    layer: L,
+}
+
+impl Default for LayerBuilder<Identity> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LayerBuilder<Identity> {
+    /// Creates a new builder without any layers.
+    pub fn new() -> Self {
+        Self { layer: Identity }
+    }
+}
+
+impl<L> LayerBuilder<L> {
+    /// Adds a layer inside the added layers.
+    pub fn layer<Inner>(self, layer: Inner) -> LayerBuilder<Stack<Inner, L>> {
+        LayerBuilder {
+            layer: Stack::new(layer, self.layer),
+        }
+    }
+
+    /// Returns the stack of the added layers.
+    pub fn into_inner(self) -> L {
+        self.layer
+    }
+
+    /// Wraps the `transport` with the added layers.
+    pub fn build<T>(self, transport: T) -> L::Transport
+    where
+        L: Layer<T>,
+    {
+        self.layer.layer(transport)
+    }
+}
+
+/// An extension trait for wrapping a transport with layers.
+pub trait TransportExt: Transport + Sized {
+    /// Wraps the transport with the `layer`.
+    fn with_layer<L: Layer<Self>>(self, layer: L) -> L::Transport {
+        layer.layer(self)
+    }
+}
+
+impl<T: Transport> TransportExt for T {}
+
+/// Implements `BatchTransport` for the wrapper transport, which delegates the requests to its
+/// `inner` field.
+macro_rules! delegate_batch_transport {
+    ($wrapper:ident) => {
+        #[async_trait::async_trait]
+        impl<T> $crate::transport::BatchTransport for $wrapper<T>
+        where
+            T: $crate::transport::BatchTransport + Send + Sync,
+            T::Error: Send,
+        {
+            async fn request_batch<I, M>(&self, batch: I) -> Result<BatchResponse, Self::Error>
+            where
+                I: IntoIterator<Item = (M, Option<Params>)> + Send,
+                I::IntoIter: Send,
+                M: Into<String>,
+            {
+                self.inner.request_batch(batch).await
+            }
+        }
+    };
+}
+
+/// Implements `PubsubTransport` for the wrapper transport, which delegates the subscriptions to
+/// its `inner` field.
+macro_rules! delegate_pubsub_transport {
+    ($wrapper:ident) => {
+        #[async_trait::async_trait]
+        impl<T> $crate::transport::PubsubTransport for $wrapper<T>
+        where
+            T: $crate::transport::PubsubTransport + Send + Sync,
+            T::Error: Send,
+        {
+            type NotificationStream = T::NotificationStream;
+
+            async fn subscribe<M>(
+                &self,
+                subscribe_method: M,
+                params: Option<Params>,
+            ) -> Result<(Id, Self::NotificationStream), Self::Error>
+            where
+                M: Into<String> + Send,
+            {
+                self.inner.subscribe(subscribe_method, params).await
+            }
+
+            async fn unsubscribe<M>(&self, unsubscribe_method: M, subscription_id: Id) -> Result<bool, Self::Error>
+            where
+                M: Into<String> + Send,
+            {
+                self.inner.unsubscribe(unsubscribe_method, subscription_id).await
+            }
+        }
+    };
+}
+use {delegate_batch_transport, delegate_pubsub_transport};
@@ -0,0 +1,134 @@
+use std::{error::Error, sync::Arc};
+
+use jsonrpc_types::v2::*;
+
+use crate::{
+    layer::{delegate_pubsub_transport, Layer},
+    transport::{BatchTransport, Transport},
+};
+
+type RetryPredicate = Arc<dyn Fn(&dyn Error) -> bool + Send + Sync>;
+
+/// The layer that retries the requests failed with transport errors.
+///
+/// The failure responses returned by the server are not retried, and the requests are retried
+/// immediately, the clients which support backoff (e.g. `HttpClient`) should use their own retry
+/// policy instead.
+#[derive(Clone)]
+pub struct RetryLayer {
+    max_retries: usize,
+    retry_if: RetryPredicate,
+}
+
+impl RetryLayer {
+    /// Creates a new retry layer, which retries the failed requests at most `max_retries` times.
+    pub fn new(max_retries: usize) -> Self {
+        Self {
+            max_retries,
+            retry_if: Arc::new(|_| true),
+        }
+    }
+
+    /// Sets the predicate of the transport errors to retry.
+    ///
+    /// Default retries all transport errors.
+    pub fn retry_if<F>(mut self, predicate: F) -> Self
+    where
+        F: Fn(&dyn Error) -> bool + Send + Sync + 'static,
+    {
+        self.retry_if = Arc::new(predicate);
+        self
+    }
+}
+
+impl<T> Layer<T> for RetryLayer {
+    type Transport = Retry<T>;
+
+    fn layer(&self, inner: T) -> Self::Transport {
+        Retry {
+            inner,
+            max_retries: self.max_retries,
+            retry_if: self.retry_if.clone(),
+        }
+    }
+}
+
+/// The transport that retries the requests of the inner transport failed with transport errors.
+#[derive(Clone)]
+pub struct Retry<T> {
+    inner: T,
+    max_retries: usize,
+    retry_if: RetryPredicate,
+}
+
+impl<T> Retry<T> {
+    /// Returns the inner transport.
+    pub fn inner(&self) -> &T {
+        &self.inner
+    }
+
+    /// Returns `true` if the request should be retried after the `error` of the `attempt`.
+    fn should_retry<E: Error>(&self, attempt: usize, error: &E) -> bool {
+        let retry = attempt < self.max_retries && (self.retry_if)(error);
+        if retry {
+            log::debug!(
+                "[layer] Retry request ({}/{}): {}",
+                attempt + 1,
+                self.max_retries,
+                error
+            );
+        }
+        retry
+    }
+}
+
+#[async_trait::async_trait]
+impl<T> Transport for Retry<T>
+where
+    T: Transport + Send + Sync,
+    T::Error: Send,
+{
+    type Error = T::Error;
+
+    async fn request<M>(&self, method: M, params: Option<Params>) -> Result<Response, Self::Error>
+    where
+        M: Into<String> + Send,
+    {
+        let method = method.into();
+        let mut attempt = 0;
+        loop {
+            match self.inner.request(method.clone(), params.clone()).await {
+                Err(err) if self.should_retry(attempt, &err) => attempt += 1,
+                result => return result,
+            }
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl<T> BatchTransport for Retry<T>
+where
+    T: BatchTransport + Send + Sync,
+    T::Error: Send,
+{
+    async fn request_batch<I, M>(&self, batch: I) -> Result<BatchResponse, Self::Error>
+    where
+        I: IntoIterator<Item = (M, Option<Params>)> + Send,
+        I::IntoIter: Send,
+        M: Into<String>,
+    {
+        let batch = batch
+            .into_iter()
+            .map(|(method, params)| (method.into(), params))
+            .collect::<Vec<(String, Option<Params>)>>();
+        let mut attempt = 0;
+        loop {
+            match self.inner.request_batch(batch.clone()).await {
+                Err(err) if self.should_retry(attempt, &err) => attempt += 1,
+                result => return result,
+            }
+        }
+    }
+}
+
+delegate_pubsub_transport!(Retry);
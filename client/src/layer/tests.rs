@@ -0,0 +1,143 @@
+use std::{
+    fmt,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+};
+
+use futures::executor::block_on;
+use jsonrpc_types::v2::*;
+
+use super::*;
+use crate::transport::BatchTransport;
+
+#[derive(Debug)]
+struct FakeError;
+
+impl fmt::Display for FakeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("fake error")
+    }
+}
+
+impl std::error::Error for FakeError {}
+
+/// Responds the method names, after failing the first `failures` requests.
+#[derive(Clone, Default)]
+struct FakeTransport {
+    failures: usize,
+    calls: Arc<AtomicUsize>,
+}
+
+#[async_trait::async_trait]
+impl Transport for FakeTransport {
+    type Error = FakeError;
+
+    async fn request<M>(&self, method: M, params: Option<Params>) -> Result<Response, Self::Error>
+    where
+        M: Into<String> + Send,
+    {
+        let id = self.calls.fetch_add(1, Ordering::SeqCst);
+        if id < self.failures {
+            return Err(FakeError);
+        }
+        let result = match params {
+            Some(params) => serde_json::to_value(params).unwrap(),
+            None => Value::from(method.into()),
+        };
+        Ok(Response::success(result, Id::Num(id as u64)))
+    }
+}
+
+#[async_trait::async_trait]
+impl BatchTransport for FakeTransport {
+    async fn request_batch<I, M>(&self, batch: I) -> Result<BatchResponse, Self::Error>
+    where
+        I: IntoIterator<Item = (M, Option<Params>)> + Send,
+        I::IntoIter: Send,
+        M: Into<String>,
+    {
+        let batch = batch
+            .into_iter()
+            .map(|(method, params)| (method.into(), params))
+            .collect::<Vec<(String, Option<Params>)>>();
+        let mut responses = Vec::new();
+        for (method, params) in batch {
+            responses.push(self.request(method, params).await?);
+        }
+        Ok(responses)
+    }
+}
+
+#[test]
+fn stack_layers() {
+    let metrics = Arc::new(Mutex::new(Vec::new()));
+    let recorded = metrics.clone();
+    let inner = FakeTransport {
+        failures: 2,
+        ..Default::default()
+    };
+    let calls = inner.calls.clone();
+    let transport = LayerBuilder::new()
+        .layer(LoggingLayer::new())
+        .layer(MetricsLayer::new(move |metrics| {
+            recorded
+                .lock()
+                .unwrap()
+                .push((metrics.method.to_string(), metrics.success));
+        }))
+        .layer(CacheLayer::new(vec!["foo"]))
+        .layer(RetryLayer::new(2))
+        .layer(MapRequestLayer::new(|method, params| match method {
+            "bar" => Some(Params::Array(vec![Value::from("signed")])),
+            _ => params,
+        }))
+        .build(inner);
+
+    // the first two attempts fail, then the response is cached.
+    let response = block_on(transport.request("foo", None)).unwrap();
+    assert_eq!(response, Response::success("foo".into(), 2.into()));
+    let response = block_on(transport.request("foo", None)).unwrap();
+    assert_eq!(response, Response::success("foo".into(), 2.into()));
+    assert_eq!(calls.load(Ordering::SeqCst), 3);
+
+    let response = block_on(transport.request("bar", None)).unwrap();
+    assert_eq!(response, Response::success(Value::from(vec!["signed"]), 3.into()));
+    let response = block_on(transport.request_batch(vec![("bar", None), ("baz", None)])).unwrap();
+    assert_eq!(
+        response,
+        vec![
+            Response::success(Value::from(vec!["signed"]), 4.into()),
+            Response::success("baz".into(), 5.into())
+        ]
+    );
+
+    let metrics = metrics.lock().unwrap().clone();
+    assert_eq!(
+        metrics,
+        vec![
+            ("foo".to_string(), Some(true)),
+            ("foo".to_string(), Some(true)),
+            ("bar".to_string(), Some(true))
+        ]
+    );
+}
+
+#[test]
+fn retry_transport_errors() {
+    let inner = FakeTransport {
+        failures: 2,
+        ..Default::default()
+    };
+    let transport = inner.clone().with_layer(RetryLayer::new(1));
+    assert!(block_on(transport.request("foo", None)).is_err());
+    assert!(block_on(transport.request("foo", None)).is_ok());
+
+    let transport = FakeTransport {
+        failures: 1,
+        ..Default::default()
+    }
+    .with_layer(RetryLayer::new(3).retry_if(|_| false));
+    assert!(block_on(transport.request("foo", None)).is_err());
+}
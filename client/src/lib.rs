@@ -1,26 +1,148 @@
 //! An async JSON-RPC 2.0 client library.
+//!
+//! # Features
+//!
+//! - `tokio-runtime` (default): `http-tokio` and `ws-tokio`.
+//! - `async-std-runtime`: `ws-async-std`.
+//! - `http-tokio`, `http-hyper`, `http-async-std`: HTTP client built on `reqwest`, `hyper` or `surf`.
+//! - `ws-tokio`, `ws-async-std`: WebSocket client.
+//! - `ipc-tokio`, `ipc-async-std`: IPC (Unix domain socket) client.
+//! - `tcp-tokio`, `tcp-async-std`: raw TCP client.
+//! - `stdio-tokio`, `stdio-async-std`: stdio client of a child process.
+//! - `duplex`: client over any message-oriented channel (`DuplexClient`).
+//! - `local`: in-process `LocalTransport` calling a server `Router` directly.
+//! - `mock`: `MockTransport` with stubbed responses (for testing).
+//! - `cassette`: record and replay exchanges (for testing).
+//! - `id-random`: `RandomIdGenerator`, generating random numeric request IDs.
+//! - `id-uuid`: `UuidIdGenerator`, generating UUID v4 request IDs.
 
 #![deny(missing_docs)]
 
+#[cfg(any(
+    feature = "http-async-std",
+    feature = "http-tokio",
+    feature = "http-hyper",
+    feature = "ws-async-std",
+    feature = "ws-tokio",
+    feature = "ipc-async-std",
+    feature = "ipc-tokio"
+))]
+mod any_client;
+#[cfg(feature = "cassette")]
+mod cassette;
+#[cfg(feature = "duplex")]
+mod duplex;
 mod error;
+mod id;
+mod layer;
+#[cfg(feature = "local")]
+mod local_transport;
+#[cfg(feature = "mock")]
+mod mock_transport;
+#[cfg(any(feature = "stream-async-std", feature = "stream-tokio"))]
+mod stream;
 mod transport;
 
-#[cfg(any(feature = "http-async-std", feature = "http-tokio"))]
+#[cfg(any(feature = "http-async-std", feature = "http-tokio", feature = "http-hyper"))]
 mod http_client;
+#[cfg(any(feature = "ipc-async-std", feature = "ipc-tokio"))]
+mod ipc_client;
+#[cfg(any(feature = "stdio-async-std", feature = "stdio-tokio"))]
+mod stdio_client;
+#[cfg(any(feature = "tcp-async-std", feature = "tcp-tokio"))]
+mod tcp_client;
 #[cfg(any(feature = "ws-async-std", feature = "ws-tokio"))]
 mod ws_client;
 
+#[cfg(feature = "local")]
+pub use async_jsonrpc_server::{Router, Subscriber};
 pub use http::header::{self, HeaderName, HeaderValue};
 pub use jsonrpc_types::v2::*;
 
-pub use self::transport::{BatchTransport, PubsubTransport, Transport};
-#[cfg(any(feature = "http-async-std", feature = "http-tokio"))]
+#[cfg(feature = "http-hyper")]
+pub use self::http_client::HyperBackend;
+#[cfg(feature = "http-tokio")]
+pub use self::http_client::ReqwestBackend;
+#[cfg(feature = "http-async-std")]
+pub use self::http_client::SurfBackend;
+#[cfg(all(unix, feature = "http-hyper"))]
+pub use self::http_client::{UnixConnector, UnixStream};
+#[cfg(feature = "id-random")]
+pub use self::id::RandomIdGenerator;
+#[cfg(feature = "id-uuid")]
+pub use self::id::UuidIdGenerator;
+#[cfg(any(
+    feature = "ipc-async-std",
+    feature = "ipc-tokio",
+    feature = "tcp-async-std",
+    feature = "tcp-tokio",
+    feature = "stdio-async-std",
+    feature = "stdio-tokio"
+))]
+pub use self::stream::Framing;
+#[cfg(any(
+    feature = "http-async-std",
+    feature = "http-tokio",
+    feature = "http-hyper",
+    feature = "ws-async-std",
+    feature = "ws-tokio",
+    feature = "ipc-async-std",
+    feature = "ipc-tokio"
+))]
+pub use self::{any_client::AnyClient, error::AnyClientError};
+#[cfg(feature = "cassette")]
+pub use self::{
+    cassette::{RecordSubscription, RecordTransport, ReplaySubscription, ReplayTransport},
+    error::{RecordTransportError, ReplayTransportError},
+};
+#[cfg(feature = "duplex")]
+pub use self::{
+    duplex::{DuplexClient, DuplexClientBuilder, DuplexSubscription, IncomingHandler},
+    error::DuplexClientError,
+};
+#[cfg(any(feature = "http-async-std", feature = "http-tokio", feature = "http-hyper"))]
 pub use self::{
     error::HttpClientError,
-    http_client::{HttpClient, HttpClientBuilder},
+    http_client::{
+        CredentialProvider, FailoverStrategy, HttpBackend, HttpClient, HttpClientBuilder, ResponseBody, RetryPolicy,
+    },
+};
+#[cfg(any(feature = "ipc-async-std", feature = "ipc-tokio"))]
+pub use self::{
+    error::IpcClientError,
+    ipc_client::{IpcClient, IpcClientBuilder, IpcSubscription},
+};
+#[cfg(feature = "local")]
+pub use self::{
+    error::LocalTransportError,
+    local_transport::{LocalSubscription, LocalTransport, LocalTransportBuilder},
+};
+#[cfg(feature = "mock")]
+pub use self::{
+    error::MockTransportError,
+    mock_transport::{Expectation, MockSubscription, MockTransport},
+};
+#[cfg(any(feature = "stdio-async-std", feature = "stdio-tokio"))]
+pub use self::{
+    error::StdioClientError,
+    stdio_client::{StdioClient, StdioClientBuilder, StdioSubscription},
+};
+#[cfg(any(feature = "tcp-async-std", feature = "tcp-tokio"))]
+pub use self::{
+    error::TcpClientError,
+    tcp_client::{TcpClient, TcpClientBuilder, TcpSubscription},
+};
+pub use self::{
+    error::{ClientError, DynTransportError},
+    id::{IdGenerator, PrefixedIdGenerator, SequentialIdGenerator},
+    layer::{
+        Cache, CacheLayer, Identity, Layer, LayerBuilder, Logging, LoggingLayer, MapRequest, MapRequestLayer, Metrics,
+        MetricsLayer, RequestMetrics, Retry, RetryLayer, Stack, TransportExt,
+    },
+    transport::{BatchTransport, DynBatchTransport, DynPubsubTransport, DynTransport, PubsubTransport, Transport},
 };
 #[cfg(any(feature = "ws-async-std", feature = "ws-tokio"))]
 pub use self::{
     error::{WsClientError, WsError},
-    ws_client::{WsClient, WsClientBuilder, WsSubscription},
+    ws_client::{Executor, Proxy, WsClient, WsClientBuilder, WsSubscription},
 };
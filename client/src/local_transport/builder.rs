@@ -0,0 +1,76 @@
+use std::{fmt, sync::Arc};
+
+use async_jsonrpc_server::Router;
+
+use crate::{
+    id::{IdGenerator, SequentialIdGenerator},
+    local_transport::LocalTransport,
+};
+
+/// A `LocalTransportBuilder` can be used to create a `LocalTransport` with custom configuration.
+pub struct LocalTransportBuilder {
+    id_generator: Arc<dyn IdGenerator>,
+    serialize: bool,
+}
+
+impl fmt::Debug for LocalTransportBuilder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LocalTransportBuilder")
+            .field("serialize", &self.serialize)
+            .finish()
+    }
+}
+
+impl Default for LocalTransportBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LocalTransportBuilder {
+    /// Creates a new `LocalTransportBuilder`.
+    ///
+    /// This is the same as `LocalTransport::builder()`.
+    pub fn new() -> Self {
+        Self {
+            id_generator: Arc::new(SequentialIdGenerator::default()),
+            serialize: false,
+        }
+    }
+
+    // ========================================================================
+    // Serialization options
+    // ========================================================================
+
+    /// Serializes and deserializes all requests, responses and notifications passing through the
+    /// transport, as if they were sent over a connection.
+    ///
+    /// Default is disabled.
+    pub fn serialize(mut self, enable: bool) -> Self {
+        self.serialize = enable;
+        self
+    }
+
+    // ========================================================================
+    // Request ID options
+    // ========================================================================
+
+    /// Sets the generator of request IDs.
+    ///
+    /// Default is `SequentialIdGenerator`, which generates sequential numeric IDs starting from 1.
+    pub fn id_generator<G: IdGenerator + 'static>(mut self, id_generator: G) -> Self {
+        self.id_generator = Arc::new(id_generator);
+        self
+    }
+
+    // ========================================================================
+
+    /// Returns a `LocalTransport` calling the methods of `router`.
+    pub fn build(self, router: Router) -> LocalTransport {
+        LocalTransport {
+            router: Arc::new(router),
+            id_generator: self.id_generator,
+            serialize: self.serialize,
+        }
+    }
+}
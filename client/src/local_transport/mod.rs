@@ -0,0 +1,179 @@
+mod builder;
+#[cfg(test)]
+mod tests;
+
+use std::{
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+use async_jsonrpc_server::Router;
+use futures::{
+    channel::mpsc,
+    future,
+    stream::{BoxStream, Stream, StreamExt},
+};
+use jsonrpc_types::v2::*;
+use serde::{de::DeserializeOwned, Serialize};
+
+pub use self::builder::LocalTransportBuilder;
+use crate::{
+    error::LocalTransportError,
+    id::IdGenerator,
+    transport::{BatchTransport, PubsubTransport, Transport},
+};
+
+/// In-process JSON-RPC transport, which calls the methods of a server `Router` directly without
+/// any connection.
+///
+/// The messages are passed to the router as they are by default, they can also be serialized and
+/// deserialized on the way to catch the serde mismatches between the client and server types.
+#[derive(Clone)]
+pub struct LocalTransport {
+    router: Arc<Router>,
+    id_generator: Arc<dyn IdGenerator>,
+    serialize: bool,
+}
+
+impl LocalTransport {
+    /// Creates a new local JSON-RPC transport calling the methods of `router`.
+    pub fn new(router: Router) -> Self {
+        LocalTransportBuilder::new().build(router)
+    }
+
+    /// Creates a `LocalTransportBuilder` to configure a `LocalTransport`.
+    ///
+    /// This is the same as `LocalTransportBuilder::new()`.
+    pub fn builder() -> LocalTransportBuilder {
+        LocalTransportBuilder::new()
+    }
+
+    /// Returns the router of the transport.
+    pub fn router(&self) -> &Router {
+        &self.router
+    }
+
+    /// Passes the message through the serialization if it's enabled.
+    fn roundtrip<T: Serialize + DeserializeOwned>(&self, msg: T) -> Result<T, serde_json::Error> {
+        if !self.serialize {
+            return Ok(msg);
+        }
+        let json = serde_json::to_string(&msg)?;
+        log::trace!("[local] Serialized message: {}", json);
+        serde_json::from_str(&json)
+    }
+}
+
+#[async_trait::async_trait]
+impl Transport for LocalTransport {
+    type Error = LocalTransportError;
+
+    async fn request<M>(&self, method: M, params: Option<Params>) -> Result<Response, Self::Error>
+    where
+        M: Into<String> + Send,
+    {
+        let request = self.roundtrip(Request::new(method, params, self.id_generator.next_id()))?;
+        let response = self.router.handle_request(request).await;
+        Ok(self.roundtrip(response)?)
+    }
+}
+
+#[async_trait::async_trait]
+impl BatchTransport for LocalTransport {
+    async fn request_batch<I, M>(&self, batch: I) -> Result<BatchResponse, Self::Error>
+    where
+        I: IntoIterator<Item = (M, Option<Params>)> + Send,
+        I::IntoIter: Send,
+        M: Into<String>,
+    {
+        let batch = batch
+            .into_iter()
+            .map(|(method, params)| Request::new(method, params, self.id_generator.next_id()))
+            .collect::<BatchRequest>();
+        let batch = self.roundtrip(batch)?;
+        let responses = self.router.handle_batch_request(batch).await;
+        Ok(self.roundtrip(responses)?)
+    }
+}
+
+#[async_trait::async_trait]
+impl PubsubTransport for LocalTransport {
+    type NotificationStream = LocalSubscription;
+
+    async fn subscribe<M>(
+        &self,
+        subscribe_method: M,
+        params: Option<Params>,
+    ) -> Result<(Id, Self::NotificationStream), Self::Error>
+    where
+        M: Into<String> + Send,
+    {
+        let request = self.roundtrip(Request::new(subscribe_method, params, self.id_generator.next_id()))?;
+        let (tx, rx) = mpsc::unbounded();
+        let response = match self.router.subscribe(&request.method, request.params, tx).await {
+            Ok(subscription_id) => Response::success(serde_json::to_value(subscription_id)?, request.id),
+            Err(err) => Response::failure(err, Some(request.id)),
+        };
+        let id = match self.roundtrip(response)? {
+            Response::Success(success) => serde_json::from_value::<Id>(success.result)?,
+            Response::Failure(_) => return Err(LocalTransportError::InvalidSubscriptionId),
+        };
+
+        let notification_rx = if self.serialize {
+            let this = self.clone();
+            rx.filter_map(move |notification| {
+                future::ready(match this.roundtrip(notification) {
+                    Ok(notification) => Some(notification),
+                    Err(err) => {
+                        log::error!("[local] Ignore malformed subscription notification: {}", err);
+                        None
+                    }
+                })
+            })
+            .boxed()
+        } else {
+            rx.boxed()
+        };
+        Ok((id.clone(), LocalSubscription { id, notification_rx }))
+    }
+
+    async fn unsubscribe<M>(&self, unsubscribe_method: M, subscription_id: Id) -> Result<bool, Self::Error>
+    where
+        M: Into<String> + Send,
+    {
+        let params = Params::Array(vec![serde_json::to_value(subscription_id)?]);
+        match self.request(unsubscribe_method, Some(params)).await? {
+            Response::Success(success) => Ok(serde_json::from_value::<bool>(success.result)?),
+            Response::Failure(failure) => {
+                log::warn!("[local] Unexpected response of unsubscribe request: {}", failure);
+                Err(LocalTransportError::InvalidUnsubscribeResult)
+            }
+        }
+    }
+}
+
+/// Active subscription on a local transport.
+pub struct LocalSubscription {
+    /// Subscription ID.
+    pub id: Id,
+    /// Stream from which we receive notifications from the router.
+    notification_rx: BoxStream<'static, SubscriptionNotification>,
+}
+
+impl LocalSubscription {
+    /// Returns the next notification from the router.
+    ///
+    /// Ignore any malformed notification.
+    pub async fn next(&mut self) -> Option<SubscriptionNotification> {
+        self.notification_rx.next().await
+    }
+}
+
+impl Stream for LocalSubscription {
+    type Item = SubscriptionNotification;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.notification_rx.poll_next_unpin(cx)
+    }
+}
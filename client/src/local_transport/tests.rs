@@ -0,0 +1,61 @@
+use futures::executor::block_on;
+
+use super::*;
+
+fn router() -> Router {
+    Router::new()
+        .method("foo", |_| async { Ok(Value::from("x")) })
+        .method("bar", |_| async { Err(Error::invalid_params("bar")) })
+        .subscription("notify", "subscribe", "unsubscribe", |params, subscriber| async move {
+            match params {
+                Some(Params::Array(params)) => {
+                    for result in params {
+                        subscriber.notify(result).unwrap();
+                    }
+                    Ok(())
+                }
+                _ => Err(Error::invalid_params("expected an array")),
+            }
+        })
+}
+
+#[test]
+fn make_jsonrpc_requests_in_process() {
+    for serialize in [false, true] {
+        let transport = LocalTransport::builder().serialize(serialize).build(router());
+
+        let response = block_on(transport.request("foo", None)).unwrap();
+        assert_eq!(response, Response::success("x".into(), 1.into()));
+
+        let response = block_on(transport.request_batch(vec![("foo", None), ("bar", None), ("baz", None)])).unwrap();
+        assert_eq!(
+            response,
+            vec![
+                Response::success("x".into(), 2.into()),
+                Response::failure(Error::invalid_params("bar"), Some(3.into())),
+                Response::failure(Error::method_not_found(), Some(4.into())),
+            ]
+        );
+
+        let params = Params::Array(vec![1.into(), 2.into()]);
+        let (id, mut subscription) = block_on(transport.subscribe("subscribe", Some(params))).unwrap();
+        for result in [1, 2] {
+            let notification = block_on(subscription.next()).unwrap();
+            assert_eq!(notification.method, "notify");
+            assert_eq!(notification.params.subscription, id);
+            assert_eq!(notification.params.result, Value::from(result));
+        }
+        assert!(block_on(transport.unsubscribe("unsubscribe", id.clone())).unwrap());
+        assert_eq!(block_on(subscription.next()), None);
+        assert!(!block_on(transport.unsubscribe("unsubscribe", id)).unwrap());
+
+        assert!(matches!(
+            block_on(transport.subscribe("subscribe", None)),
+            Err(LocalTransportError::InvalidSubscriptionId)
+        ));
+        assert!(matches!(
+            block_on(transport.unsubscribe("bar", 1.into())),
+            Err(LocalTransportError::InvalidUnsubscribeResult)
+        ));
+    }
+}
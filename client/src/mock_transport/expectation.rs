@@ -0,0 +1,135 @@
+use jsonrpc_types::v2::*;
+
+use crate::mock_transport::MockTransport;
+
+/// The stubbed outcome of an expected method call.
+#[derive(Clone, Debug)]
+pub(crate) enum Outcome {
+    /// Responds the result.
+    Success(Value),
+    /// Responds the error.
+    Failure(Error),
+    /// Accepts the subscription, and sends the notifications with the method.
+    Subscription {
+        notification_method: String,
+        results: Vec<Value>,
+    },
+}
+
+/// An expected method call registered in the `MockTransport`.
+#[derive(Clone, Debug)]
+pub(crate) struct Stub {
+    method: String,
+    /// `None` matches any parameters.
+    params: Option<Option<Params>>,
+    /// `None` matches any number of times.
+    times: Option<usize>,
+    pub(crate) calls: usize,
+    pub(crate) outcome: Outcome,
+}
+
+impl Stub {
+    /// Returns `true` if the stub matches the method call and has not been used up.
+    pub(crate) fn matches(&self, method: &str, params: &Option<Params>) -> bool {
+        let params_matched = match &self.params {
+            Some(expected) => expected == params,
+            None => true,
+        };
+        self.method == method && params_matched && !matches!(self.times, Some(times) if self.calls >= times)
+    }
+
+    /// Returns `true` if the stub has been called the expected number of times.
+    pub(crate) fn is_satisfied(&self) -> bool {
+        match self.times {
+            Some(times) => self.calls == times,
+            None => self.calls > 0,
+        }
+    }
+
+    pub(crate) fn describe(&self) -> String {
+        match &self.params {
+            Some(Some(params)) => format!("{}({})", self.method, serde_json::to_string(params).unwrap_or_default()),
+            Some(None) => format!("{}()", self.method),
+            None => format!("{}(..)", self.method),
+        }
+    }
+}
+
+/// An expectation of the method call, which is registered in the `MockTransport` by its
+/// `returns`, `fails` or `notifies` method.
+#[must_use = "the expectation is registered by `returns`, `fails` or `notifies`"]
+pub struct Expectation<'a> {
+    transport: &'a MockTransport,
+    method: String,
+    params: Option<Option<Params>>,
+    times: Option<usize>,
+}
+
+impl<'a> Expectation<'a> {
+    pub(crate) fn new(transport: &'a MockTransport, method: String) -> Self {
+        Self {
+            transport,
+            method,
+            params: None,
+            times: None,
+        }
+    }
+
+    /// Matches the method calls with the `params` only.
+    ///
+    /// Default matches any parameters.
+    pub fn with_params(mut self, params: Params) -> Self {
+        self.params = Some(Some(params));
+        self
+    }
+
+    /// Matches the method calls without parameters only.
+    pub fn without_params(mut self) -> Self {
+        self.params = Some(None);
+        self
+    }
+
+    /// Matches the method calls `times` times at most, then the next matching expectation is used.
+    ///
+    /// Default matches any number of times.
+    pub fn times(mut self, times: usize) -> Self {
+        self.times = Some(times);
+        self
+    }
+
+    /// Responds the successful `result` to the matched method calls.
+    pub fn returns(self, result: impl Into<Value>) -> &'a MockTransport {
+        self.register(Outcome::Success(result.into()))
+    }
+
+    /// Responds the `error` to the matched method calls.
+    pub fn fails(self, error: Error) -> &'a MockTransport {
+        self.register(Outcome::Failure(error))
+    }
+
+    /// Accepts the matched subscriptions, each of which receives a notification with the
+    /// `notification_method` for every result of `results`, then the subscription stream is
+    /// pending until it's unsubscribed.
+    pub fn notifies<I>(self, notification_method: impl Into<String>, results: I) -> &'a MockTransport
+    where
+        I: IntoIterator,
+        I::Item: Into<Value>,
+    {
+        self.register(Outcome::Subscription {
+            notification_method: notification_method.into(),
+            results: results.into_iter().map(Into::into).collect(),
+        })
+    }
+
+    fn register(self, outcome: Outcome) -> &'a MockTransport {
+        let stub = Stub {
+            method: self.method,
+            params: self.params,
+            times: self.times,
+            calls: 0,
+            outcome,
+        };
+        self.transport.register(stub);
+        self.transport
+    }
+}
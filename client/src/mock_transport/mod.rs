@@ -0,0 +1,319 @@
+mod expectation;
+#[cfg(test)]
+mod tests;
+
+use std::{
+    collections::{HashMap, VecDeque},
+    pin::Pin,
+    sync::{Arc, Mutex, MutexGuard},
+    task::{Context, Poll, Waker},
+};
+
+use futures::{future, stream::Stream};
+use jsonrpc_types::v2::*;
+
+pub use self::expectation::Expectation;
+use self::expectation::{Outcome, Stub};
+use crate::{
+    error::MockTransportError,
+    id::{IdGenerator, SequentialIdGenerator},
+    transport::{BatchTransport, PubsubTransport, Transport},
+};
+
+#[derive(Default)]
+struct MockState {
+    stubs: Vec<Stub>,
+    requests: Vec<Request>,
+    /// The notification queues of the active subscriptions.
+    subscriptions: HashMap<Id, Arc<Mutex<NotificationQueue>>>,
+}
+
+/// The notification queue shared by the `MockTransport` and a `MockSubscription`.
+struct NotificationQueue {
+    notification_method: String,
+    notifications: VecDeque<SubscriptionNotification>,
+    /// The subscription is closed by unsubscribing it.
+    closed: bool,
+    /// The waker of the subscription stream waiting for the next notification.
+    waker: Option<Waker>,
+}
+
+impl NotificationQueue {
+    fn wake(&mut self) {
+        if let Some(waker) = self.waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+/// Mock JSON-RPC transport for testing the code written against the transport traits.
+///
+/// The method calls are responded by the expectations registered by `expect`, which are matched
+/// in the registration order. All requests (including the batch, subscribe and unsubscribe
+/// requests) are recorded, and can be asserted after the calls.
+///
+/// The method calls without any matched expectation fail with the `UnexpectedRequest` error.
+#[derive(Clone)]
+pub struct MockTransport {
+    state: Arc<Mutex<MockState>>,
+    id_generator: Arc<dyn IdGenerator>,
+}
+
+impl Default for MockTransport {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MockTransport {
+    /// Creates a new mock transport without any expectations.
+    pub fn new() -> Self {
+        Self {
+            state: Arc::new(Mutex::new(MockState::default())),
+            id_generator: Arc::new(SequentialIdGenerator::default()),
+        }
+    }
+
+    /// Expects the calls of the `method`, whose response is set by the returned `Expectation`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use async_jsonrpc_client::{Error, MockTransport};
+    ///
+    /// let transport = MockTransport::new();
+    /// transport
+    ///     .expect("eth_blockNumber")
+    ///     .returns("0x1")
+    ///     .expect("eth_getBalance")
+    ///     .fails(Error::invalid_params("invalid address"));
+    /// ```
+    pub fn expect(&self, method: impl Into<String>) -> Expectation<'_> {
+        Expectation::new(self, method.into())
+    }
+
+    pub(crate) fn register(&self, stub: Stub) {
+        self.lock().stubs.push(stub);
+    }
+
+    /// Sends a notification with the `result` to the active subscription, whose stream is pending
+    /// for the next notification after all scripted notifications are received.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no active subscription with the `subscription_id`.
+    pub fn notify(&self, subscription_id: &Id, result: impl Into<Value>) {
+        let queue = self
+            .lock()
+            .subscriptions
+            .get(subscription_id)
+            .cloned()
+            .unwrap_or_else(|| panic!("no active subscription: id={:?}", subscription_id));
+        let mut queue = queue.lock().expect("lock notification queue");
+        let params = SubscriptionNotificationParams::new(subscription_id.clone(), result.into());
+        let notification = SubscriptionNotification::new(queue.notification_method.clone(), params);
+        queue.notifications.push_back(notification);
+        queue.wake();
+    }
+
+    /// Returns all recorded requests in the order of calls.
+    pub fn requests(&self) -> Vec<Request> {
+        self.lock().requests.clone()
+    }
+
+    /// Asserts that the `method` has been called with the `params`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no such request in the recorded requests.
+    pub fn assert_requested(&self, method: &str, params: Option<Params>) {
+        let state = self.lock();
+        let requested = state
+            .requests
+            .iter()
+            .any(|request| request.method == method && request.params == params);
+        assert!(
+            requested,
+            "expected request `{}` with params {:?}, but the recorded requests are: {:?}",
+            method, params, state.requests
+        );
+    }
+
+    /// Asserts that every expectation has been matched, at least once or exactly the number of
+    /// times set by `Expectation::times`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any expectation is not satisfied.
+    pub fn assert_expectations(&self) {
+        let state = self.lock();
+        let unsatisfied = state
+            .stubs
+            .iter()
+            .filter(|stub| !stub.is_satisfied())
+            .map(Stub::describe)
+            .collect::<Vec<_>>();
+        assert!(
+            unsatisfied.is_empty(),
+            "unsatisfied expectations: {:?}, the recorded requests are: {:?}",
+            unsatisfied,
+            state.requests
+        );
+    }
+
+    fn lock(&self) -> MutexGuard<'_, MockState> {
+        self.state.lock().expect("lock mock state")
+    }
+
+    /// Records the request and returns the outcome of the first matched expectation.
+    fn call(&self, request: Request) -> Result<(Id, Outcome), MockTransportError> {
+        log::debug!("[mock] Receive request: {}", request);
+        let mut state = self.lock();
+        state.requests.push(request.clone());
+        let stub = state
+            .stubs
+            .iter_mut()
+            .find(|stub| stub.matches(&request.method, &request.params));
+        match stub {
+            Some(stub) => {
+                stub.calls += 1;
+                Ok((request.id, stub.outcome.clone()))
+            }
+            None => Err(MockTransportError::UnexpectedRequest(request)),
+        }
+    }
+
+    fn respond(&self, method: impl Into<String>, params: Option<Params>) -> Result<Response, MockTransportError> {
+        let (id, outcome) = self.call(Request::new(method, params, self.id_generator.next_id()))?;
+        Ok(match outcome {
+            Outcome::Success(result) => Response::success(result, id),
+            Outcome::Failure(err) => Response::failure(err, Some(id)),
+            // the subscription id is responded to the subscribe method called as a normal request.
+            Outcome::Subscription { .. } => Response::success(serde_json::to_value(&id)?, id),
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl Transport for MockTransport {
+    type Error = MockTransportError;
+
+    async fn request<M>(&self, method: M, params: Option<Params>) -> Result<Response, Self::Error>
+    where
+        M: Into<String> + Send,
+    {
+        self.respond(method, params)
+    }
+}
+
+#[async_trait::async_trait]
+impl BatchTransport for MockTransport {
+    async fn request_batch<I, M>(&self, batch: I) -> Result<BatchResponse, Self::Error>
+    where
+        I: IntoIterator<Item = (M, Option<Params>)> + Send,
+        I::IntoIter: Send,
+        M: Into<String>,
+    {
+        batch
+            .into_iter()
+            .map(|(method, params)| self.respond(method, params))
+            .collect()
+    }
+}
+
+#[async_trait::async_trait]
+impl PubsubTransport for MockTransport {
+    type NotificationStream = MockSubscription;
+
+    async fn subscribe<M>(
+        &self,
+        subscribe_method: M,
+        params: Option<Params>,
+    ) -> Result<(Id, Self::NotificationStream), Self::Error>
+    where
+        M: Into<String> + Send,
+    {
+        let request = Request::new(subscribe_method, params, self.id_generator.next_id());
+        match self.call(request)? {
+            // the request id is used as the subscription id.
+            (
+                id,
+                Outcome::Subscription {
+                    notification_method,
+                    results,
+                },
+            ) => {
+                let notifications = results
+                    .into_iter()
+                    .map(|result| {
+                        let params = SubscriptionNotificationParams::new(id.clone(), result);
+                        SubscriptionNotification::new(notification_method.clone(), params)
+                    })
+                    .collect();
+                let queue = Arc::new(Mutex::new(NotificationQueue {
+                    notification_method,
+                    notifications,
+                    closed: false,
+                    waker: None,
+                }));
+                self.lock().subscriptions.insert(id.clone(), queue.clone());
+                Ok((id.clone(), MockSubscription { id, queue }))
+            }
+            (_, Outcome::Success(_)) | (_, Outcome::Failure(_)) => Err(MockTransportError::InvalidSubscriptionId),
+        }
+    }
+
+    async fn unsubscribe<M>(&self, unsubscribe_method: M, subscription_id: Id) -> Result<bool, Self::Error>
+    where
+        M: Into<String> + Send,
+    {
+        let params = Params::Array(vec![serde_json::to_value(&subscription_id)?]);
+        let result = match self.respond(unsubscribe_method, Some(params))? {
+            Response::Success(success) => serde_json::from_value::<bool>(success.result)?,
+            Response::Failure(_) => return Err(MockTransportError::InvalidUnsubscribeResult),
+        };
+        if result {
+            // end the subscription stream after the queued notifications.
+            if let Some(queue) = self.lock().subscriptions.remove(&subscription_id) {
+                let mut queue = queue.lock().expect("lock notification queue");
+                queue.closed = true;
+                queue.wake();
+            }
+        }
+        Ok(result)
+    }
+}
+
+/// Active subscription on a mock transport, which yields the scripted notifications and the
+/// notifications sent by `MockTransport::notify`.
+///
+/// The stream is pending when there is no queued notification, and ends after the subscription is
+/// unsubscribed.
+pub struct MockSubscription {
+    /// Subscription ID.
+    pub id: Id,
+    queue: Arc<Mutex<NotificationQueue>>,
+}
+
+impl MockSubscription {
+    /// Returns the next notification, or `None` if the subscription is unsubscribed.
+    pub async fn next(&mut self) -> Option<SubscriptionNotification> {
+        future::poll_fn(|cx| Pin::new(&mut *self).poll_next(cx)).await
+    }
+}
+
+impl Stream for MockSubscription {
+    type Item = SubscriptionNotification;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut queue = self.queue.lock().expect("lock notification queue");
+        match queue.notifications.pop_front() {
+            Some(notification) => Poll::Ready(Some(notification)),
+            None if queue.closed => Poll::Ready(None),
+            None => {
+                queue.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
@@ -0,0 +1,111 @@
+use futures::{executor::block_on, future::FutureExt, stream::StreamExt};
+
+use super::*;
+
+#[test]
+fn respond_expected_requests() {
+    let transport = MockTransport::new();
+    transport
+        .expect("foo")
+        .with_params(Params::Array(vec![1.into()]))
+        .times(1)
+        .returns("x")
+        .expect("foo")
+        .returns("y")
+        .expect("bar")
+        .without_params()
+        .fails(Error::invalid_params("bar"));
+
+    let params = Some(Params::Array(vec![1.into()]));
+    let response = block_on(transport.request("foo", params.clone())).unwrap();
+    assert_eq!(response, Response::success("x".into(), 1.into()));
+    let response = block_on(transport.request("foo", params.clone())).unwrap();
+    assert_eq!(response, Response::success("y".into(), 2.into()));
+
+    let response = block_on(transport.request_batch(vec![("foo", None), ("bar", None)])).unwrap();
+    assert_eq!(
+        response,
+        vec![
+            Response::success("y".into(), 3.into()),
+            Response::failure(Error::invalid_params("bar"), Some(4.into())),
+        ]
+    );
+    assert!(matches!(
+        block_on(transport.request("bar", params.clone())),
+        Err(MockTransportError::UnexpectedRequest(request)) if request.method == "bar"
+    ));
+
+    transport.assert_requested("foo", params.clone());
+    transport.assert_requested("bar", params);
+    transport.assert_expectations();
+    let methods = transport
+        .requests()
+        .into_iter()
+        .map(|request| request.method)
+        .collect::<Vec<_>>();
+    assert_eq!(methods, vec!["foo", "foo", "foo", "bar", "bar"]);
+}
+
+#[test]
+fn notify_scripted_subscriptions() {
+    let transport = MockTransport::new();
+    transport
+        .expect("subscribe")
+        .notifies("notify", vec!["x", "y"])
+        .expect("unsubscribe")
+        .returns(true);
+
+    let (id, subscription) = block_on(transport.subscribe("subscribe", None)).unwrap();
+    let notifications = block_on(subscription.take(2).collect::<Vec<_>>());
+    let expected = ["x", "y"]
+        .iter()
+        .map(|&result| {
+            SubscriptionNotification::new("notify", SubscriptionNotificationParams::new(id.clone(), result.into()))
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(notifications, expected);
+
+    assert!(block_on(transport.unsubscribe("unsubscribe", id.clone())).unwrap());
+    transport.assert_requested(
+        "unsubscribe",
+        Some(Params::Array(vec![serde_json::to_value(id).unwrap()])),
+    );
+}
+
+#[test]
+fn pend_on_empty_subscriptions() {
+    let transport = MockTransport::new();
+    transport
+        .expect("subscribe")
+        .notifies("notify", vec!["x"])
+        .expect("unsubscribe")
+        .returns(true);
+
+    let (id, mut subscription) = block_on(transport.subscribe("subscribe", None)).unwrap();
+    let notification = |result: &str| {
+        SubscriptionNotification::new("notify", SubscriptionNotificationParams::new(id.clone(), result.into()))
+    };
+    assert_eq!(block_on(subscription.next()), Some(notification("x")));
+    // no notification yet.
+    assert!(subscription.next().now_or_never().is_none());
+
+    // the pending stream is woken by the next notification.
+    let waiting = std::thread::spawn(move || block_on(subscription.next()).map(|_| subscription));
+    transport.notify(&id, "y");
+    let mut subscription = waiting.join().unwrap().expect("notification");
+    transport.notify(&id, "z");
+    assert_eq!(block_on(subscription.next()), Some(notification("z")));
+
+    // the stream ends after the subscription is unsubscribed.
+    assert!(block_on(transport.unsubscribe("unsubscribe", id.clone())).unwrap());
+    assert_eq!(block_on(subscription.next()), None);
+}
+
+#[test]
+#[should_panic(expected = "unsatisfied expectations")]
+fn panic_on_unsatisfied_expectations() {
+    let transport = MockTransport::new();
+    transport.expect("foo").times(2).returns("x");
+    block_on(transport.request("foo", None)).unwrap();
+    transport.assert_expectations();
+}
@@ -0,0 +1,198 @@
+use std::{fmt, future::Future, process::Command, sync::Arc, time::Duration};
+
+use futures::{
+    channel::mpsc,
+    io::{AsyncRead, AsyncWrite},
+};
+
+use crate::{
+    duplex::{DuplexTask, Frontend, IncomingHandler},
+    error::StdioClientError,
+    id::{IdGenerator, SequentialIdGenerator},
+    stdio_client::StdioClient,
+    stream::{framed_sink, framed_stream, Framing, Runtime, DEFAULT_MAX_FRAME_SIZE},
+};
+
+/// A `StdioClientBuilder` can be used to create a `StdioClient` with custom configuration.
+pub struct StdioClientBuilder {
+    id_generator: Arc<dyn IdGenerator>,
+    timeout: Option<Duration>,
+    framing: Framing,
+    max_frame_size: usize,
+    handler: Option<Arc<dyn IncomingHandler>>,
+    max_concurrent_request_capacity: usize,
+    max_capacity_per_subscription: usize,
+}
+
+impl fmt::Debug for StdioClientBuilder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StdioClientBuilder")
+            .field("timeout", &self.timeout)
+            .field("framing", &self.framing)
+            .field("max_frame_size", &self.max_frame_size)
+            .field("max_concurrent_request_capacity", &self.max_concurrent_request_capacity)
+            .field("max_capacity_per_subscription", &self.max_capacity_per_subscription)
+            .finish()
+    }
+}
+
+impl Default for StdioClientBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl StdioClientBuilder {
+    /// Creates a new `StdioClientBuilder`.
+    ///
+    /// This is the same as `StdioClient::builder()`.
+    pub fn new() -> Self {
+        Self {
+            id_generator: Arc::new(SequentialIdGenerator::default()),
+            timeout: None,
+            framing: Framing::ContentLength,
+            max_frame_size: DEFAULT_MAX_FRAME_SIZE,
+            handler: None,
+            max_concurrent_request_capacity: 256,
+            max_capacity_per_subscription: 64,
+        }
+    }
+
+    // ========================================================================
+    // Framing options
+    // ========================================================================
+
+    /// Sets the framing of the messages over the connection.
+    ///
+    /// Default is `Framing::ContentLength`.
+    pub fn framing(mut self, framing: Framing) -> Self {
+        self.framing = framing;
+        self
+    }
+
+    /// Sets the max size of a message received from the connection, the larger messages are
+    /// rejected and the client is terminated.
+    ///
+    /// Default is 8 MiB.
+    pub fn max_frame_size(mut self, max_frame_size: usize) -> Self {
+        self.max_frame_size = max_frame_size;
+        self
+    }
+
+    // ========================================================================
+    // Handler options
+    // ========================================================================
+
+    /// Sets the handler of the requests and notifications sent by the server.
+    ///
+    /// Default responds `Method not found` errors to all requests and ignores all notifications.
+    pub fn handler<H: IncomingHandler + 'static>(mut self, handler: H) -> Self {
+        self.handler = Some(Arc::new(handler));
+        self
+    }
+
+    // ========================================================================
+    // Channel options
+    // ========================================================================
+
+    /// Sets the max channel capacity of sending request concurrently.
+    ///
+    /// Default is 256.
+    pub fn max_concurrent_request_capacity(mut self, capacity: usize) -> Self {
+        self.max_concurrent_request_capacity = capacity;
+        self
+    }
+
+    /// Sets the max channel capacity of every subscription stream.
+    ///
+    /// Default is 64.
+    pub fn max_capacity_per_subscription(mut self, capacity: usize) -> Self {
+        self.max_capacity_per_subscription = capacity;
+        self
+    }
+
+    // ========================================================================
+    // Timeout options
+    // ========================================================================
+
+    /// Enables a request timeout.
+    ///
+    /// Default is no timeout.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    // ========================================================================
+    // Request ID options
+    // ========================================================================
+
+    /// Sets the generator of request IDs.
+    ///
+    /// Default is `SequentialIdGenerator`, which generates sequential numeric IDs starting from 1.
+    pub fn id_generator<G: IdGenerator + 'static>(mut self, id_generator: G) -> Self {
+        self.id_generator = Arc::new(id_generator);
+        self
+    }
+
+    // ========================================================================
+
+    /// Returns a `StdioClient` over the child process spawned by the `command`, whose stdin and
+    /// stdout are piped to the client.
+    ///
+    /// The background task of the client is spawned by the tokio/async-std runtime, and the child
+    /// process is killed when the background task is terminated (e.g. all clients are dropped).
+    pub fn build(self, command: Command) -> Result<StdioClient, StdioClientError> {
+        let runtime = Runtime::detect();
+        let (client, task) = self.build_with_task_on(runtime, command)?;
+        runtime.spawn(Box::pin(task));
+        Ok(client)
+    }
+
+    /// Returns a `StdioClient` over the child process spawned by the `command`, and the background
+    /// task of the client.
+    ///
+    /// The background task processes all requests and subscriptions of the client and the requests of the server, it must be polled
+    /// (e.g. spawned on any executor) for the client to work. The child process is killed when the
+    /// background task is terminated or dropped.
+    pub fn build_with_task(
+        self,
+        command: Command,
+    ) -> Result<(StdioClient, impl Future<Output = ()> + Send + 'static), StdioClientError> {
+        self.build_with_task_on(Runtime::detect(), command)
+    }
+
+    fn build_with_task_on(
+        self,
+        runtime: Runtime,
+        command: Command,
+    ) -> Result<(StdioClient, impl Future<Output = ()> + Send + 'static), StdioClientError> {
+        let child = runtime.spawn_child(command)?;
+        let (client, task) = self.build_with_io(child.stdout, child.stdin);
+        let process = child.child;
+        let task = async move {
+            task.await;
+            drop(process);
+        };
+        Ok((client, task))
+    }
+
+    /// Returns a `StdioClient` over the given `reader` and `writer` (e.g. the stdin and stdout of
+    /// the current process), and the background task of the client.
+    ///
+    /// The background task must be polled (e.g. spawned on any executor) for the client to work.
+    pub fn build_with_io<R, W>(self, reader: R, writer: W) -> (StdioClient, impl Future<Output = ()> + Send + 'static)
+    where
+        R: AsyncRead + Send + 'static,
+        W: AsyncWrite + Send + 'static,
+    {
+        let sink = framed_sink::<_, StdioClientError>(Box::pin(writer), self.framing);
+        let stream = framed_stream(Box::pin(reader), self.framing, self.max_frame_size);
+        let (to_back, from_front) = mpsc::channel(self.max_concurrent_request_capacity);
+        let task = DuplexTask::new(self.max_capacity_per_subscription).handler(self.handler);
+        let client = StdioClient {
+            frontend: Frontend::new(to_back, self.id_generator, self.timeout),
+        };
+        (client, task.into_task(sink, stream, from_front))
+    }
+}
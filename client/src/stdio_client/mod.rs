@@ -0,0 +1,46 @@
+mod builder;
+#[cfg(test)]
+mod tests;
+
+use jsonrpc_types::v2::*;
+
+pub use self::builder::StdioClientBuilder;
+use crate::{
+    duplex::{impl_duplex_transports, DuplexSubscription, Frontend},
+    error::StdioClientError,
+};
+
+/// Stdio JSON-RPC client, which sends the messages to the stdin of a child process (e.g. a language
+/// server) and receives the messages from its stdout, or over any pair of byte streams.
+///
+/// By default, the messages are framed by the `Content-Length` headers as in the Language Server
+/// Protocol. The requests and notifications sent by the server are handled by the
+/// `IncomingHandler` of the client.
+#[derive(Clone)]
+pub struct StdioClient {
+    frontend: Frontend<StdioClientError>,
+}
+
+impl StdioClient {
+    /// Creates a new stdio JSON-RPC client over the child process spawned by the `command`.
+    pub fn new(command: std::process::Command) -> Result<Self, StdioClientError> {
+        StdioClientBuilder::new().build(command)
+    }
+
+    /// Creates a `StdioClientBuilder` to configure a `StdioClient`.
+    ///
+    /// This is the same as `StdioClientBuilder::new()`.
+    pub fn builder() -> StdioClientBuilder {
+        StdioClientBuilder::new()
+    }
+
+    /// Sends a notification to the server, which returns once the notification is sent.
+    pub async fn notify(&self, method: impl Into<String>, params: Option<Params>) -> Result<(), StdioClientError> {
+        self.frontend.send_notification(method, params).await
+    }
+}
+
+/// Active subscription on a stdio client.
+pub type StdioSubscription<Notif> = DuplexSubscription<Notif>;
+
+impl_duplex_transports!(StdioClient, StdioClientError);
@@ -0,0 +1,76 @@
+use super::*;
+use crate::transport::Transport;
+
+/// Responds the requests with their methods, and records the methods of notifications.
+#[cfg(all(unix, feature = "stdio-tokio"))]
+#[derive(Default)]
+struct EchoHandler {
+    notifications: std::sync::Arc<std::sync::Mutex<Vec<String>>>,
+}
+
+#[cfg(all(unix, feature = "stdio-tokio"))]
+#[async_trait::async_trait]
+impl crate::duplex::IncomingHandler for EchoHandler {
+    async fn handle_request(&self, method: String, _params: Option<Params>) -> Result<Value, Error> {
+        match method.as_str() {
+            "unknown" => Err(Error::method_not_found()),
+            _ => Ok(Value::from(method)),
+        }
+    }
+
+    async fn handle_notification(&self, method: String, _params: Option<Params>) {
+        self.notifications.lock().unwrap().push(method);
+    }
+}
+
+/// The `cat` process echoes the messages of client, so the requests of client are handled by the
+/// handler of client itself, and the responses are sent back to the client.
+#[cfg(all(unix, feature = "stdio-tokio"))]
+#[tokio::test]
+async fn make_jsonrpc_requests_to_child_process() {
+    let handler = EchoHandler::default();
+    let notifications = handler.notifications.clone();
+    let client = StdioClient::builder()
+        .handler(handler)
+        .build(std::process::Command::new("cat"))
+        .unwrap();
+
+    let response = client.request("foo", None).await.unwrap();
+    assert_eq!(response, Response::success("foo".into(), 1.into()));
+    let response = client.request("unknown", None).await.unwrap();
+    assert_eq!(response, Response::failure(Error::method_not_found(), Some(2.into())));
+
+    client.notify("bar", None).await.unwrap();
+    // the notification is handled before the response of next request.
+    client.request("foo", None).await.unwrap();
+    assert_eq!(*notifications.lock().unwrap(), vec!["bar".to_string()]);
+}
+
+#[cfg(all(unix, feature = "stdio-async-std"))]
+#[async_std::test]
+async fn make_jsonrpc_requests_over_io() {
+    use futures::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+
+    let (client_stream, server_stream) = async_std::os::unix::net::UnixStream::pair().unwrap();
+    async_std::task::spawn(async move {
+        let mut reader = BufReader::new(&server_stream);
+        let mut header = String::new();
+        reader.read_line(&mut header).await.unwrap();
+        assert_eq!(header, "Content-Length: 39\r\n");
+        reader.read_line(&mut header).await.unwrap();
+        let mut request = vec![0u8; 39];
+        reader.read_exact(&mut request).await.unwrap();
+        assert_eq!(request, br#"{"jsonrpc":"2.0","method":"foo","id":1}"#);
+
+        let response = r#"{"jsonrpc":"2.0","id":1,"result":"x"}"#;
+        let mut writer = &server_stream;
+        let frame = format!("Content-Length: {}\r\n\r\n{}", response.len(), response);
+        writer.write_all(frame.as_bytes()).await.unwrap();
+    });
+
+    let (reader, writer) = client_stream.split();
+    let (client, task) = StdioClient::builder().build_with_io(reader, writer);
+    async_std::task::spawn(task);
+    let response = client.request("foo", None).await.unwrap();
+    assert_eq!(response, Response::success("x".into(), 1.into()));
+}
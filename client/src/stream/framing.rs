@@ -0,0 +1,391 @@
+use std::{collections::VecDeque, convert::TryFrom, io};
+
+use futures::{
+    io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt},
+    sink::Sink,
+    stream::Stream,
+};
+
+/// The default max size of a message, which is 8 MiB.
+pub(crate) const DEFAULT_MAX_FRAME_SIZE: usize = 8 * 1024 * 1024;
+
+/// The framing of JSON-RPC messages over a byte stream.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Framing {
+    /// The messages are split by the JSON syntax, so they may be delimited by newlines or not
+    /// delimited at all. The messages sent are delimited by newlines.
+    Json,
+    /// The messages are delimited by newlines (`\n` or `\r\n`).
+    LineDelimited,
+    /// The messages are prefixed by the `Content-Length` header (and other headers), as in the
+    /// Language Server Protocol.
+    ContentLength,
+    /// The messages are prefixed by the 4-byte big-endian length.
+    LengthPrefixed,
+}
+
+impl Framing {
+    /// Encodes the message into a frame.
+    pub(crate) fn encode(self, message: String) -> Vec<u8> {
+        match self {
+            Self::Json | Self::LineDelimited => {
+                let mut frame = message.into_bytes();
+                frame.push(b'\n');
+                frame
+            }
+            Self::ContentLength => {
+                let mut frame = format!("Content-Length: {}\r\n\r\n", message.len()).into_bytes();
+                frame.extend(message.into_bytes());
+                frame
+            }
+            Self::LengthPrefixed => {
+                let len = u32::try_from(message.len()).expect("message is too large");
+                let mut frame = len.to_be_bytes().to_vec();
+                frame.extend(message.into_bytes());
+                frame
+            }
+        }
+    }
+}
+
+/// Decodes the messages out of the byte stream with the framing.
+#[derive(Debug)]
+pub(crate) struct Decoder {
+    framing: Framing,
+    max_frame_size: usize,
+    buf: Vec<u8>,
+    json: JsonSplitter,
+}
+
+impl Decoder {
+    pub(crate) fn new(framing: Framing, max_frame_size: usize) -> Self {
+        Self {
+            framing,
+            max_frame_size,
+            buf: Vec::new(),
+            json: JsonSplitter::default(),
+        }
+    }
+
+    /// Feeds the next chunk of the byte stream, and returns the messages completed by it.
+    ///
+    /// The messages decoded before an invalid frame are returned, followed by the error.
+    pub(crate) fn decode(&mut self, chunk: &[u8]) -> Vec<io::Result<String>> {
+        self.buf.extend_from_slice(chunk);
+        let mut messages = Vec::new();
+        let mut start = 0;
+        loop {
+            let frame = match self.framing {
+                Framing::Json => self.json.next_frame(&self.buf, start),
+                Framing::LineDelimited => Ok(next_line(&self.buf, start)),
+                Framing::ContentLength => next_content_length_frame(&self.buf, start, self.max_frame_size),
+                Framing::LengthPrefixed => next_length_prefixed_frame(&self.buf, start, self.max_frame_size),
+            };
+            match frame {
+                Ok(Some((body, end))) => {
+                    start = end;
+                    if body.len() > self.max_frame_size {
+                        messages.push(Err(frame_too_large(body.len(), self.max_frame_size)));
+                    } else if !body.is_empty() {
+                        messages.push(String::from_utf8(body).map_err(|err| invalid_data(err.to_string())));
+                    }
+                }
+                Ok(None) => {
+                    // the incomplete message without length header can't grow beyond the max size.
+                    let unbounded = matches!(self.framing, Framing::Json | Framing::LineDelimited);
+                    if unbounded && self.buf.len() - start > self.max_frame_size {
+                        messages.push(Err(frame_too_large(self.buf.len() - start, self.max_frame_size)));
+                    }
+                    break;
+                }
+                Err(err) => {
+                    messages.push(Err(err));
+                    break;
+                }
+            }
+        }
+        // keep the incomplete frame only
+        self.buf.drain(..start);
+        self.json.pos = self.json.pos.saturating_sub(start);
+        messages
+    }
+}
+
+/// Converts the writer into a sink of messages, which are encoded with the framing.
+pub(crate) fn framed_sink<W, E>(writer: W, framing: Framing) -> impl Sink<String, Error = E>
+where
+    W: AsyncWrite + Unpin,
+    E: From<io::Error>,
+{
+    futures::sink::unfold(writer, move |mut writer, msg: String| async move {
+        writer.write_all(&framing.encode(msg)).await?;
+        writer.flush().await?;
+        Ok(writer)
+    })
+}
+
+/// Converts the reader into a stream of messages, which are decoded with the framing.
+///
+/// The messages larger than `max_frame_size` are rejected with the `InvalidData` error.
+pub(crate) fn framed_stream<R, E>(
+    reader: R,
+    framing: Framing,
+    max_frame_size: usize,
+) -> impl Stream<Item = Result<String, E>>
+where
+    R: AsyncRead + Unpin,
+    E: From<io::Error>,
+{
+    futures::stream::unfold(
+        (
+            reader,
+            Decoder::new(framing, max_frame_size),
+            VecDeque::<io::Result<String>>::new(),
+        ),
+        |(mut reader, mut decoder, mut messages)| async move {
+            loop {
+                if let Some(message) = messages.pop_front() {
+                    let message = message.map_err(E::from);
+                    return Some((message, (reader, decoder, messages)));
+                }
+                let mut chunk = [0u8; 8 * 1024];
+                match reader.read(&mut chunk).await {
+                    Ok(0) => return None,
+                    Ok(len) => messages.extend(decoder.decode(&chunk[..len])),
+                    Err(err) => return Some((Err(err.into()), (reader, decoder, messages))),
+                }
+            }
+        },
+    )
+}
+
+fn invalid_data(msg: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+fn frame_too_large(size: usize, max_frame_size: usize) -> io::Error {
+    invalid_data(format!("frame size {} exceeds the max size {}", size, max_frame_size))
+}
+
+/// A frame body and the end position of the frame.
+type Frame = Option<(Vec<u8>, usize)>;
+
+fn next_line(buf: &[u8], start: usize) -> Frame {
+    let len = buf[start..].iter().position(|&byte| byte == b'\n')?;
+    let line = &buf[start..start + len];
+    let line = line.strip_suffix(b"\r").unwrap_or(line);
+    Some((line.to_vec(), start + len + 1))
+}
+
+fn next_content_length_frame(buf: &[u8], start: usize, max_frame_size: usize) -> io::Result<Frame> {
+    let header_len = match buf[start..].windows(4).position(|window| window == b"\r\n\r\n") {
+        Some(len) => len,
+        None if buf.len() - start > max_frame_size => return Err(frame_too_large(buf.len() - start, max_frame_size)),
+        None => return Ok(None),
+    };
+    let headers = std::str::from_utf8(&buf[start..start + header_len])
+        .map_err(|_| invalid_data("invalid frame headers".into()))?;
+    let content_length = headers
+        .split("\r\n")
+        .filter_map(|header| header.split_once(':'))
+        .find(|(name, _)| name.trim().eq_ignore_ascii_case("content-length"))
+        .and_then(|(_, value)| value.trim().parse::<usize>().ok())
+        .ok_or_else(|| invalid_data(format!("missing `Content-Length` header: {:?}", headers)))?;
+    if content_length > max_frame_size {
+        return Err(frame_too_large(content_length, max_frame_size));
+    }
+    let body_start = start + header_len + 4;
+    let body_end = body_start
+        .checked_add(content_length)
+        .ok_or_else(|| invalid_data(format!("invalid `Content-Length` header: {}", content_length)))?;
+    match buf.get(body_start..body_end) {
+        Some(body) => Ok(Some((body.to_vec(), body_end))),
+        None => Ok(None),
+    }
+}
+
+fn next_length_prefixed_frame(buf: &[u8], start: usize, max_frame_size: usize) -> io::Result<Frame> {
+    let prefix = match buf.get(start..start + 4) {
+        Some(prefix) => prefix,
+        None => return Ok(None),
+    };
+    let len = u32::from_be_bytes([prefix[0], prefix[1], prefix[2], prefix[3]]) as usize;
+    if len > max_frame_size {
+        return Err(frame_too_large(len, max_frame_size));
+    }
+    match buf.get(start + 4..start + 4 + len) {
+        Some(body) => Ok(Some((body.to_vec(), start + 4 + len))),
+        None => Ok(None),
+    }
+}
+
+/// Splits the JSON objects or arrays by the JSON syntax.
+#[derive(Debug, Default)]
+struct JsonSplitter {
+    /// The position of buffer that has been scanned.
+    pos: usize,
+    /// The nesting depth of the current message.
+    depth: usize,
+    in_string: bool,
+    escaped: bool,
+}
+
+impl JsonSplitter {
+    fn next_frame(&mut self, buf: &[u8], frame_start: usize) -> io::Result<Frame> {
+        let mut start = frame_start;
+        let mut i = self.pos.max(start);
+        while i < buf.len() {
+            let byte = buf[i];
+            i += 1;
+            if self.in_string {
+                if self.escaped {
+                    self.escaped = false;
+                } else if byte == b'\\' {
+                    self.escaped = true;
+                } else if byte == b'"' {
+                    self.in_string = false;
+                }
+                continue;
+            }
+            match byte {
+                b'{' | b'[' => {
+                    if self.depth == 0 {
+                        start = i - 1;
+                    }
+                    self.depth += 1;
+                }
+                b'}' | b']' if self.depth > 0 => {
+                    self.depth -= 1;
+                    if self.depth == 0 {
+                        self.pos = i;
+                        return Ok(Some((buf[start..i].to_vec(), i)));
+                    }
+                }
+                b'"' if self.depth > 0 => self.in_string = true,
+                byte if self.depth == 0 && byte.is_ascii_whitespace() => start = i,
+                byte if self.depth == 0 => {
+                    return Err(invalid_data(format!(
+                        "unexpected byte `{}` between messages",
+                        byte as char
+                    )))
+                }
+                _ => {}
+            }
+        }
+        self.pos = i;
+        // the whitespaces before the incomplete message are consumed
+        let end = if self.depth == 0 { i } else { start };
+        if end > frame_start {
+            return Ok(Some((Vec::new(), end)));
+        }
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Decodes the stream which is split into chunks.
+    fn decode(framing: Framing, stream: &[u8], chunk_size: usize) -> io::Result<Vec<String>> {
+        let mut decoder = Decoder::new(framing, DEFAULT_MAX_FRAME_SIZE);
+        let mut messages = Vec::new();
+        for chunk in stream.chunks(chunk_size) {
+            for message in decoder.decode(chunk) {
+                messages.push(message?);
+            }
+        }
+        assert!(decoder.buf.is_empty());
+        Ok(messages)
+    }
+
+    #[test]
+    fn decode_json_messages() {
+        let stream = concat!(
+            r#"{"jsonrpc":"2.0","id":1,"result":"}"}"#,
+            "\n",
+            r#"[{"jsonrpc":"2.0","id":2,"result":"\"]"}]"#,
+            r#"{"jsonrpc":"2.0","id":3,"result":{"a":[1]}}"#,
+            "\r\n"
+        );
+        let expected = vec![
+            r#"{"jsonrpc":"2.0","id":1,"result":"}"}"#,
+            r#"[{"jsonrpc":"2.0","id":2,"result":"\"]"}]"#,
+            r#"{"jsonrpc":"2.0","id":3,"result":{"a":[1]}}"#,
+        ];
+        for chunk_size in [1, 7, 1024] {
+            assert_eq!(decode(Framing::Json, stream.as_bytes(), chunk_size).unwrap(), expected);
+        }
+        assert!(decode(Framing::Json, br#"{"id":1} x"#, 1024).is_err());
+    }
+
+    #[test]
+    fn decode_framed_messages() {
+        let messages = vec![r#"{"jsonrpc":"2.0","id":1,"result":"x"}"#, r#"{"id":"中文"}"#];
+        for framing in [Framing::LineDelimited, Framing::ContentLength, Framing::LengthPrefixed] {
+            let stream = messages
+                .iter()
+                .flat_map(|message| framing.encode(message.to_string()))
+                .collect::<Vec<_>>();
+            for chunk_size in [1, 7, 1024] {
+                assert_eq!(decode(framing, &stream, chunk_size).unwrap(), messages);
+            }
+        }
+
+        let stream = "Content-Type: application/json\r\ncontent-length: 8\r\n\r\n{\"id\":1}";
+        assert_eq!(
+            decode(Framing::ContentLength, stream.as_bytes(), 3).unwrap(),
+            vec!["{\"id\":1}"]
+        );
+        assert!(decode(Framing::ContentLength, b"X: 1\r\n\r\n{}", 1024).is_err());
+        let stream = "{\"id\":1}\r\n\n{\"id\":2}\n";
+        assert_eq!(
+            decode(Framing::LineDelimited, stream.as_bytes(), 1).unwrap(),
+            vec!["{\"id\":1}", "{\"id\":2}"]
+        );
+    }
+
+    #[test]
+    fn reject_oversized_frames() {
+        let is_invalid_data =
+            |result: &io::Result<String>| matches!(result, Err(err) if err.kind() == io::ErrorKind::InvalidData);
+        let message = r#"{"jsonrpc":"2.0","id":1,"result":"x"}"#;
+        for framing in [
+            Framing::Json,
+            Framing::LineDelimited,
+            Framing::ContentLength,
+            Framing::LengthPrefixed,
+        ] {
+            let mut decoder = Decoder::new(framing, 16);
+            let messages = decoder.decode(&framing.encode(message.to_string()));
+            assert!(is_invalid_data(&messages[0]), "{:?}: {:?}", framing, messages);
+        }
+
+        // the oversized frame is rejected before it's received completely.
+        let mut decoder = Decoder::new(Framing::LengthPrefixed, 1024);
+        let messages = decoder.decode(&u32::MAX.to_be_bytes());
+        assert!(is_invalid_data(&messages[0]));
+        let mut decoder = Decoder::new(Framing::LineDelimited, 1024);
+        let messages = decoder.decode(&[b'x'; 2048]);
+        assert!(is_invalid_data(&messages[0]));
+    }
+
+    #[test]
+    fn reject_overflowing_content_length() {
+        let stream = format!("Content-Length: {}\r\n\r\n{{}}", usize::MAX);
+        let messages = Decoder::new(Framing::ContentLength, usize::MAX).decode(stream.as_bytes());
+        assert!(matches!(&messages[..], [Err(err)] if err.kind() == io::ErrorKind::InvalidData));
+        let messages = Decoder::new(Framing::ContentLength, DEFAULT_MAX_FRAME_SIZE).decode(stream.as_bytes());
+        assert!(matches!(&messages[..], [Err(err)] if err.kind() == io::ErrorKind::InvalidData));
+    }
+
+    #[test]
+    fn keep_messages_before_invalid_frame() {
+        let mut decoder = Decoder::new(Framing::LineDelimited, DEFAULT_MAX_FRAME_SIZE);
+        let messages = decoder.decode(b"{\"id\":1}\n\xff\n{\"id\":2}\n");
+        assert_eq!(messages.len(), 3);
+        assert_eq!(messages[0].as_ref().unwrap(), "{\"id\":1}");
+        assert!(messages[1].is_err());
+        assert_eq!(messages[2].as_ref().unwrap(), "{\"id\":2}");
+    }
+}
@@ -0,0 +1,31 @@
+// the framing is used by the byte stream clients, while the runtime is shared with the WebSocket client.
+#[cfg(any(
+    feature = "ipc-async-std",
+    feature = "ipc-tokio",
+    feature = "tcp-async-std",
+    feature = "tcp-tokio",
+    feature = "stdio-async-std",
+    feature = "stdio-tokio"
+))]
+pub(crate) mod framing;
+mod runtime;
+
+#[cfg(any(
+    feature = "ipc-async-std",
+    feature = "ipc-tokio",
+    feature = "tcp-async-std",
+    feature = "tcp-tokio",
+    feature = "stdio-async-std",
+    feature = "stdio-tokio"
+))]
+pub use self::framing::Framing;
+#[cfg(any(
+    feature = "ipc-async-std",
+    feature = "ipc-tokio",
+    feature = "tcp-async-std",
+    feature = "tcp-tokio",
+    feature = "stdio-async-std",
+    feature = "stdio-tokio"
+))]
+pub(crate) use self::framing::{framed_sink, framed_stream, DEFAULT_MAX_FRAME_SIZE};
+pub(crate) use self::runtime::Runtime;
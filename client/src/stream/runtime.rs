@@ -0,0 +1,247 @@
+#[cfg(any(
+    feature = "ipc-async-std",
+    feature = "ipc-tokio",
+    feature = "tcp-async-std",
+    feature = "tcp-tokio",
+    feature = "stdio-async-std",
+    feature = "stdio-tokio"
+))]
+use std::io;
+#[cfg(all(unix, any(feature = "ipc-async-std", feature = "ipc-tokio")))]
+use std::path::Path;
+#[cfg(all(
+    feature = "stream-tokio",
+    any(
+        feature = "ipc-async-std",
+        feature = "ipc-tokio",
+        feature = "tcp-async-std",
+        feature = "tcp-tokio",
+        feature = "stdio-async-std",
+        feature = "stdio-tokio"
+    )
+))]
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use futures::future::BoxFuture;
+#[cfg(any(
+    feature = "ipc-async-std",
+    feature = "ipc-tokio",
+    feature = "tcp-async-std",
+    feature = "tcp-tokio",
+    feature = "stdio-async-std",
+    feature = "stdio-tokio"
+))]
+use futures::io::{AsyncRead, AsyncWrite};
+#[cfg(all(
+    feature = "stream-tokio",
+    any(
+        feature = "ipc-async-std",
+        feature = "ipc-tokio",
+        feature = "tcp-async-std",
+        feature = "tcp-tokio",
+        feature = "stdio-async-std",
+        feature = "stdio-tokio"
+    )
+))]
+use futures::ready;
+
+/// The async runtime used to connect the sockets and spawn background tasks.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub(crate) enum Runtime {
+    #[cfg(feature = "stream-tokio")]
+    Tokio,
+    #[cfg(feature = "stream-async-std")]
+    AsyncStd,
+}
+
+impl Runtime {
+    /// Detects the runtime to use.
+    ///
+    /// When both tokio and async-std features are enabled, tokio is used within the context of a
+    /// tokio runtime and async-std is used otherwise.
+    pub(crate) fn detect() -> Self {
+        #[cfg(all(feature = "stream-tokio", feature = "stream-async-std"))]
+        {
+            if tokio::runtime::Handle::try_current().is_ok() {
+                Self::Tokio
+            } else {
+                Self::AsyncStd
+            }
+        }
+        #[cfg(all(feature = "stream-tokio", not(feature = "stream-async-std")))]
+        {
+            Self::Tokio
+        }
+        #[cfg(all(feature = "stream-async-std", not(feature = "stream-tokio")))]
+        {
+            Self::AsyncStd
+        }
+    }
+
+    pub(crate) fn spawn(self, task: BoxFuture<'static, ()>) {
+        match self {
+            #[cfg(feature = "stream-tokio")]
+            Self::Tokio => {
+                let _handle = tokio::spawn(task);
+            }
+            #[cfg(feature = "stream-async-std")]
+            Self::AsyncStd => {
+                let _handle = async_std::task::spawn(task);
+            }
+        }
+    }
+
+    /// Connects the Unix domain socket at the `path`.
+    #[cfg(all(unix, any(feature = "ipc-async-std", feature = "ipc-tokio")))]
+    pub(crate) async fn connect_unix(self, path: &Path) -> io::Result<Box<dyn ByteStream>> {
+        match self {
+            #[cfg(feature = "stream-tokio")]
+            Self::Tokio => Ok(Box::new(TokioStream(tokio::net::UnixStream::connect(path).await?))),
+            #[cfg(feature = "stream-async-std")]
+            Self::AsyncStd => Ok(Box::new(async_std::os::unix::net::UnixStream::connect(path).await?)),
+        }
+    }
+
+    /// Connects the TCP socket at the `addr`.
+    #[cfg(any(feature = "tcp-async-std", feature = "tcp-tokio"))]
+    pub(crate) async fn connect_tcp(self, addr: &str, nodelay: bool) -> io::Result<Box<dyn ByteStream>> {
+        match self {
+            #[cfg(feature = "stream-tokio")]
+            Self::Tokio => {
+                let stream = tokio::net::TcpStream::connect(addr).await?;
+                stream.set_nodelay(nodelay)?;
+                Ok(Box::new(TokioStream(stream)))
+            }
+            #[cfg(feature = "stream-async-std")]
+            Self::AsyncStd => {
+                let stream = async_std::net::TcpStream::connect(addr).await?;
+                stream.set_nodelay(nodelay)?;
+                Ok(Box::new(stream))
+            }
+        }
+    }
+
+    /// Spawns the child process of the `command`, whose stdin and stdout are piped.
+    ///
+    /// The child process is killed when the returned `ChildProcess` is dropped.
+    #[cfg(any(feature = "stdio-async-std", feature = "stdio-tokio"))]
+    pub(crate) fn spawn_child(self, command: std::process::Command) -> io::Result<ChildProcess> {
+        use std::process::Stdio;
+
+        match self {
+            #[cfg(feature = "stream-tokio")]
+            Self::Tokio => {
+                let mut command = tokio::process::Command::from(command);
+                command.stdin(Stdio::piped()).stdout(Stdio::piped()).kill_on_drop(true);
+                let mut child = command.spawn()?;
+                let stdin = child.stdin.take().expect("stdin is piped; qed");
+                let stdout = child.stdout.take().expect("stdout is piped; qed");
+                Ok(ChildProcess {
+                    stdin: Box::new(TokioStream(stdin)),
+                    stdout: Box::new(TokioStream(stdout)),
+                    child: Box::new(child),
+                })
+            }
+            #[cfg(feature = "stream-async-std")]
+            Self::AsyncStd => {
+                let mut command = async_std::process::Command::from(command);
+                command.stdin(Stdio::piped()).stdout(Stdio::piped()).kill_on_drop(true);
+                let mut child = command.spawn()?;
+                let stdin = child.stdin.take().expect("stdin is piped; qed");
+                let stdout = child.stdout.take().expect("stdout is piped; qed");
+                Ok(ChildProcess {
+                    stdin: Box::new(stdin),
+                    stdout: Box::new(stdout),
+                    child: Box::new(child),
+                })
+            }
+        }
+    }
+}
+
+/// The spawned child process and its piped stdin and stdout.
+#[cfg(any(feature = "stdio-async-std", feature = "stdio-tokio"))]
+pub(crate) struct ChildProcess {
+    pub(crate) stdin: Box<dyn AsyncWrite + Send + Unpin>,
+    pub(crate) stdout: Box<dyn AsyncRead + Send + Unpin>,
+    /// The handle of child process, which kills the process when dropped.
+    pub(crate) child: Box<dyn Send>,
+}
+
+/// The connected byte stream.
+#[cfg(any(
+    feature = "ipc-async-std",
+    feature = "ipc-tokio",
+    feature = "tcp-async-std",
+    feature = "tcp-tokio"
+))]
+pub(crate) trait ByteStream: AsyncRead + AsyncWrite + Send + Unpin {}
+
+#[cfg(any(
+    feature = "ipc-async-std",
+    feature = "ipc-tokio",
+    feature = "tcp-async-std",
+    feature = "tcp-tokio"
+))]
+impl<S: AsyncRead + AsyncWrite + Send + Unpin> ByteStream for S {}
+
+/// Adapts the tokio stream to the `AsyncRead` and `AsyncWrite` traits of futures.
+#[cfg(all(
+    feature = "stream-tokio",
+    any(
+        feature = "ipc-async-std",
+        feature = "ipc-tokio",
+        feature = "tcp-async-std",
+        feature = "tcp-tokio",
+        feature = "stdio-async-std",
+        feature = "stdio-tokio"
+    )
+))]
+pub(crate) struct TokioStream<S>(pub(crate) S);
+
+#[cfg(all(
+    feature = "stream-tokio",
+    any(
+        feature = "ipc-async-std",
+        feature = "ipc-tokio",
+        feature = "tcp-async-std",
+        feature = "tcp-tokio",
+        feature = "stdio-async-std",
+        feature = "stdio-tokio"
+    )
+))]
+impl<S: tokio::io::AsyncRead + Unpin> AsyncRead for TokioStream<S> {
+    fn poll_read(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<io::Result<usize>> {
+        let mut buf = tokio::io::ReadBuf::new(buf);
+        ready!(tokio::io::AsyncRead::poll_read(Pin::new(&mut self.0), cx, &mut buf))?;
+        Poll::Ready(Ok(buf.filled().len()))
+    }
+}
+
+#[cfg(all(
+    feature = "stream-tokio",
+    any(
+        feature = "ipc-async-std",
+        feature = "ipc-tokio",
+        feature = "tcp-async-std",
+        feature = "tcp-tokio",
+        feature = "stdio-async-std",
+        feature = "stdio-tokio"
+    )
+))]
+impl<S: tokio::io::AsyncWrite + Unpin> AsyncWrite for TokioStream<S> {
+    fn poll_write(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        tokio::io::AsyncWrite::poll_write(Pin::new(&mut self.0), cx, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        tokio::io::AsyncWrite::poll_flush(Pin::new(&mut self.0), cx)
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        tokio::io::AsyncWrite::poll_shutdown(Pin::new(&mut self.0), cx)
+    }
+}
@@ -0,0 +1,237 @@
+use std::{fmt, future::Future, sync::Arc, time::Duration};
+
+use futures::{
+    channel::mpsc,
+    io::{AsyncRead, AsyncReadExt, AsyncWrite},
+};
+use jsonrpc_types::v2::{Id, Notification};
+
+use crate::{
+    duplex::{CancelNotification, DuplexTask, Frontend, IncomingHandler, UnsubscribeMethod},
+    error::TcpClientError,
+    id::{IdGenerator, SequentialIdGenerator},
+    stream::{framed_sink, framed_stream, Framing, Runtime, DEFAULT_MAX_FRAME_SIZE},
+    tcp_client::TcpClient,
+};
+
+/// A `TcpClientBuilder` can be used to create a `TcpClient` with custom configuration.
+pub struct TcpClientBuilder {
+    id_generator: Arc<dyn IdGenerator>,
+    timeout: Option<Duration>,
+    framing: Framing,
+    max_frame_size: usize,
+    handler: Option<Arc<dyn IncomingHandler>>,
+    cancel_notification: Option<CancelNotification>,
+    unsubscribe_method: Option<UnsubscribeMethod>,
+    nodelay: bool,
+    max_concurrent_request_capacity: usize,
+    max_capacity_per_subscription: usize,
+}
+
+impl fmt::Debug for TcpClientBuilder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TcpClientBuilder")
+            .field("timeout", &self.timeout)
+            .field("framing", &self.framing)
+            .field("max_frame_size", &self.max_frame_size)
+            .field("handler", &self.handler.as_ref().map(|_| "IncomingHandler"))
+            .field("cancel_notification", &self.cancel_notification.as_ref().map(|_| "Fn"))
+            .field("unsubscribe_method", &self.unsubscribe_method.as_ref().map(|_| "Fn"))
+            .field("nodelay", &self.nodelay)
+            .field("max_concurrent_request_capacity", &self.max_concurrent_request_capacity)
+            .field("max_capacity_per_subscription", &self.max_capacity_per_subscription)
+            .finish()
+    }
+}
+
+impl Default for TcpClientBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TcpClientBuilder {
+    /// Creates a new `TcpClientBuilder`.
+    ///
+    /// This is the same as `TcpClient::builder()`.
+    pub fn new() -> Self {
+        Self {
+            id_generator: Arc::new(SequentialIdGenerator::default()),
+            timeout: None,
+            framing: Framing::LineDelimited,
+            max_frame_size: DEFAULT_MAX_FRAME_SIZE,
+            handler: None,
+            cancel_notification: None,
+            unsubscribe_method: None,
+            nodelay: true,
+            max_concurrent_request_capacity: 256,
+            max_capacity_per_subscription: 64,
+        }
+    }
+
+    // ========================================================================
+    // Framing options
+    // ========================================================================
+
+    /// Sets the framing of the messages over the connection.
+    ///
+    /// Default is `Framing::LineDelimited`.
+    pub fn framing(mut self, framing: Framing) -> Self {
+        self.framing = framing;
+        self
+    }
+
+    /// Sets the max size of a message received from the connection, the larger messages are
+    /// rejected and the client is terminated.
+    ///
+    /// Default is 8 MiB.
+    pub fn max_frame_size(mut self, max_frame_size: usize) -> Self {
+        self.max_frame_size = max_frame_size;
+        self
+    }
+
+    // ========================================================================
+    // Handler options
+    // ========================================================================
+
+    /// Sets the handler of the requests and notifications sent by the server.
+    ///
+    /// Default responds `Method not found` errors to all requests and ignores all notifications.
+    pub fn handler<H: IncomingHandler + 'static>(mut self, handler: H) -> Self {
+        self.handler = Some(Arc::new(handler));
+        self
+    }
+
+    // ========================================================================
+    // TCP options
+    // ========================================================================
+
+    /// Sets the `TCP_NODELAY` option of the connection.
+    ///
+    /// Default is true.
+    pub fn nodelay(mut self, nodelay: bool) -> Self {
+        self.nodelay = nodelay;
+        self
+    }
+
+    // ========================================================================
+    // Channel options
+    // ========================================================================
+
+    /// Sets the max channel capacity of sending request concurrently.
+    ///
+    /// Default is 256.
+    pub fn max_concurrent_request_capacity(mut self, capacity: usize) -> Self {
+        self.max_concurrent_request_capacity = capacity;
+        self
+    }
+
+    /// Sets the max channel capacity of every subscription stream.
+    ///
+    /// Default is 64.
+    pub fn max_capacity_per_subscription(mut self, capacity: usize) -> Self {
+        self.max_capacity_per_subscription = capacity;
+        self
+    }
+
+    // ========================================================================
+    // Timeout options
+    // ========================================================================
+
+    /// Enables a request timeout.
+    ///
+    /// Default is no timeout.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    // ========================================================================
+    // Request ID options
+    // ========================================================================
+
+    /// Sets the generator of request IDs.
+    ///
+    /// Default is `SequentialIdGenerator`, which generates sequential numeric IDs starting from 1.
+    pub fn id_generator<G: IdGenerator + 'static>(mut self, id_generator: G) -> Self {
+        self.id_generator = Arc::new(id_generator);
+        self
+    }
+
+    // ========================================================================
+    // Cancellation options
+    // ========================================================================
+
+    /// Sets a function to build the notification sent to the server, when a pending request
+    /// is canceled because of timeout or dropping the request future.
+    ///
+    /// The canceled request is always removed from the client, and its response will be ignored.
+    pub fn cancel_notification<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&Id) -> Notification + Send + Sync + 'static,
+    {
+        self.cancel_notification = Some(Box::new(f));
+        self
+    }
+
+    /// Sets a function to get the unsubscribe method by the subscribe method, which is called to
+    /// close the subscription on the server, when the subscribe future is dropped after the server
+    /// has answered it.
+    ///
+    /// Default replaces `subscribe` with `unsubscribe` in the subscribe method (e.g. `eth_subscribe`
+    /// to `eth_unsubscribe`), and the subscription isn't closed if the function returns `None`.
+    pub fn unsubscribe_method<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&str) -> Option<String> + Send + Sync + 'static,
+    {
+        self.unsubscribe_method = Some(Box::new(f));
+        self
+    }
+
+    // ========================================================================
+
+    /// Returns a `TcpClient` connected to the `addr` (e.g. `127.0.0.1:8545`).
+    ///
+    /// The background task of the client is spawned by the tokio/async-std runtime.
+    pub async fn build(self, addr: impl AsRef<str>) -> Result<TcpClient, TcpClientError> {
+        let runtime = Runtime::detect();
+        let stream = runtime.connect_tcp(addr.as_ref(), self.nodelay).await?;
+        let (client, task) = self.build_with_stream(stream);
+        runtime.spawn(Box::pin(task));
+        Ok(client)
+    }
+
+    /// Returns a `TcpClient` connected to the `addr`, and the background task of the client.
+    ///
+    /// The background task processes all requests and subscriptions of the client, it must be
+    /// polled (e.g. spawned on any executor) for the client to work.
+    pub async fn build_with_task(
+        self,
+        addr: impl AsRef<str>,
+    ) -> Result<(TcpClient, impl Future<Output = ()> + Send + 'static), TcpClientError> {
+        let stream = Runtime::detect().connect_tcp(addr.as_ref(), self.nodelay).await?;
+        Ok(self.build_with_stream(stream))
+    }
+
+    /// Returns a `TcpClient` over the given connected stream (e.g. a TLS stream), and the background
+    /// task of the client.
+    ///
+    /// The background task must be polled (e.g. spawned on any executor) for the client to work.
+    pub fn build_with_stream<S>(self, stream: S) -> (TcpClient, impl Future<Output = ()> + Send + 'static)
+    where
+        S: AsyncRead + AsyncWrite + Send + 'static,
+    {
+        let (reader, writer) = stream.split();
+        let sink = framed_sink::<_, TcpClientError>(writer, self.framing);
+        let stream = framed_stream(reader, self.framing, self.max_frame_size);
+        let (to_back, from_front) = mpsc::channel(self.max_concurrent_request_capacity);
+        let task = DuplexTask::new(self.max_capacity_per_subscription)
+            .handler(self.handler)
+            .cancel_notification(self.cancel_notification)
+            .unsubscribe_method(self.unsubscribe_method);
+        let client = TcpClient {
+            frontend: Frontend::new(to_back, self.id_generator, self.timeout),
+        };
+        (client, task.into_task(sink, stream, from_front))
+    }
+}
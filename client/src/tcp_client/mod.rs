@@ -0,0 +1,44 @@
+mod builder;
+#[cfg(test)]
+mod tests;
+
+use jsonrpc_types::v2::*;
+
+pub use self::builder::TcpClientBuilder;
+use crate::{
+    duplex::{impl_duplex_transports, DuplexSubscription, Frontend},
+    error::TcpClientError,
+};
+
+/// TCP JSON-RPC client, which sends the messages over a plain TCP connection.
+///
+/// By default, the messages are delimited by newlines. See `TcpClientBuilder::framing` for other
+/// framings.
+#[derive(Clone)]
+pub struct TcpClient {
+    frontend: Frontend<TcpClientError>,
+}
+
+impl TcpClient {
+    /// Creates a new TCP JSON-RPC client connected to the `addr` (e.g. `127.0.0.1:8545`).
+    pub async fn new(addr: impl AsRef<str>) -> Result<Self, TcpClientError> {
+        TcpClientBuilder::new().build(addr).await
+    }
+
+    /// Creates a `TcpClientBuilder` to configure a `TcpClient`.
+    ///
+    /// This is the same as `TcpClientBuilder::new()`.
+    pub fn builder() -> TcpClientBuilder {
+        TcpClientBuilder::new()
+    }
+
+    /// Sends a notification to the server, which returns once the notification is sent.
+    pub async fn notify(&self, method: impl Into<String>, params: Option<Params>) -> Result<(), TcpClientError> {
+        self.frontend.send_notification(method, params).await
+    }
+}
+
+/// Active subscription on a TCP client.
+pub type TcpSubscription<Notif> = DuplexSubscription<Notif>;
+
+impl_duplex_transports!(TcpClient, TcpClientError);
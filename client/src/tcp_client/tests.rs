@@ -0,0 +1,154 @@
+use super::*;
+use crate::stream::Framing;
+use crate::transport::Transport;
+
+/// Serves the JSON-RPC requests of one TCP connection with the `framing`.
+#[cfg(feature = "tcp-tokio")]
+async fn serve(mut stream: tokio::net::TcpStream, framing: Framing) {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    use crate::stream::framing::{Decoder, DEFAULT_MAX_FRAME_SIZE};
+
+    let mut decoder = Decoder::new(framing, DEFAULT_MAX_FRAME_SIZE);
+    let mut chunk = [0u8; 1024];
+    while let Ok(len) = stream.read(&mut chunk).await {
+        if len == 0 {
+            break;
+        }
+        for request in decoder.decode(&chunk[..len]) {
+            let responses = match request.unwrap().as_str() {
+                r#"{"jsonrpc":"2.0","method":"foo","id":1}"# => vec![r#"{"jsonrpc":"2.0","id":1,"result":"x"}"#],
+                r#"[{"jsonrpc":"2.0","method":"foo","id":2},{"jsonrpc":"2.0","method":"bar","params":[],"id":3}]"# => {
+                    vec![r#"[{"jsonrpc":"2.0","id":2,"result":"x"},{"jsonrpc":"2.0","id":3,"result":"y"}]"#]
+                }
+                r#"{"jsonrpc":"2.0","method":"subscribe","id":4}"# => vec![
+                    r#"{"jsonrpc":"2.0","id":4,"result":"0x1"}"#,
+                    r#"{"jsonrpc":"2.0","method":"notify","params":{"subscription":"0x1","result":"z"}}"#,
+                ],
+                r#"{"jsonrpc":"2.0","method":"unsubscribe","params":["0x1"],"id":5}"# => {
+                    vec![r#"{"jsonrpc":"2.0","id":5,"result":true}"#]
+                }
+                request => panic!("unexpected request: {}", request),
+            };
+            for response in responses {
+                stream.write_all(&framing.encode(response.to_string())).await.unwrap();
+            }
+        }
+    }
+}
+
+#[cfg(feature = "tcp-tokio")]
+#[tokio::test]
+async fn make_jsonrpc_requests_over_tcp() {
+    use crate::transport::{BatchTransport, PubsubTransport};
+
+    for framing in [Framing::LineDelimited, Framing::ContentLength, Framing::LengthPrefixed] {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            serve(stream, framing).await;
+        });
+
+        let client = TcpClient::builder()
+            .framing(framing)
+            .build(addr.to_string())
+            .await
+            .unwrap();
+        let response = client.request("foo", None).await.unwrap();
+        assert_eq!(response, Response::success("x".into(), 1.into()));
+
+        let response = client
+            .request_batch(vec![("foo", None), ("bar", Some(Params::Array(vec![])))])
+            .await
+            .unwrap();
+        assert_eq!(
+            response,
+            vec![
+                Response::success("x".into(), 2.into()),
+                Response::success("y".into(), 3.into()),
+            ]
+        );
+
+        let (id, mut subscription) = client.subscribe("subscribe", None).await.unwrap();
+        assert_eq!(id, Id::Str("0x1".into()));
+        let notification = subscription.next().await.unwrap();
+        assert_eq!(notification.params.result, Value::from("z"));
+        assert!(client.unsubscribe("unsubscribe", id).await.unwrap());
+    }
+}
+
+#[cfg(feature = "tcp-tokio")]
+#[tokio::test]
+async fn handle_server_requests_over_tcp() {
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+    use crate::duplex::IncomingHandler;
+
+    /// Responds the requests sent by the server with the method names.
+    struct MethodHandler;
+
+    #[async_trait::async_trait]
+    impl IncomingHandler for MethodHandler {
+        async fn handle_request(&self, method: String, _params: Option<Params>) -> Result<Value, Error> {
+            Ok(Value::from(method))
+        }
+    }
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = tokio::spawn(async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        let (reader, mut writer) = stream.into_split();
+        let mut lines = BufReader::new(reader).lines();
+        let request = lines.next_line().await.unwrap().unwrap();
+        assert_eq!(request, r#"{"jsonrpc":"2.0","method":"foo","id":1}"#);
+        // the request of client is answered after the client answers the request of server.
+        writer
+            .write_all(b"{\"jsonrpc\":\"2.0\",\"method\":\"ping\",\"id\":\"s1\"}\n")
+            .await
+            .unwrap();
+        let response = lines.next_line().await.unwrap().unwrap();
+        writer
+            .write_all(b"{\"jsonrpc\":\"2.0\",\"id\":1,\"result\":\"x\"}\n")
+            .await
+            .unwrap();
+        response
+    });
+
+    let client = TcpClient::builder()
+        .handler(MethodHandler)
+        .build(addr.to_string())
+        .await
+        .unwrap();
+    let response = client.request("foo", None).await.unwrap();
+    assert_eq!(response, Response::success("x".into(), 1.into()));
+    assert_eq!(server.await.unwrap(), r#"{"jsonrpc":"2.0","result":"ping","id":"s1"}"#);
+}
+
+#[cfg(feature = "tcp-async-std")]
+#[async_std::test]
+async fn make_jsonrpc_request_over_tcp_with_length_prefix() {
+    use futures::io::{AsyncReadExt, AsyncWriteExt};
+
+    let listener = async_std::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    async_std::task::spawn(async move {
+        let (mut stream, _) = listener.accept().await.unwrap();
+        let mut len = [0u8; 4];
+        stream.read_exact(&mut len).await.unwrap();
+        let mut request = vec![0u8; u32::from_be_bytes(len) as usize];
+        stream.read_exact(&mut request).await.unwrap();
+        assert_eq!(request, br#"{"jsonrpc":"2.0","method":"foo","id":1}"#);
+        let response = Framing::LengthPrefixed.encode(r#"{"jsonrpc":"2.0","id":1,"result":"x"}"#.to_string());
+        stream.write_all(&response).await.unwrap();
+    });
+
+    let client = TcpClient::builder()
+        .framing(Framing::LengthPrefixed)
+        .build(addr.to_string())
+        .await
+        .unwrap();
+    let response = client.request("foo", None).await.unwrap();
+    assert_eq!(response, Response::success("x".into(), 1.into()));
+}
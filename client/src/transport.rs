@@ -1,8 +1,10 @@
 use std::error::Error;
 
-use futures::stream::Stream;
+use futures::stream::{BoxStream, Stream, StreamExt};
 use jsonrpc_types::v2::*;
 
+use crate::error::DynTransportError;
+
 /// A JSON-RPC 2.0 transport.
 #[async_trait::async_trait]
 pub trait Transport {
@@ -48,3 +50,152 @@ pub trait PubsubTransport: Transport {
     where
         M: Into<String> + Send;
 }
+
+/// An object-safe version of `Transport`, which is implemented for all transports.
+///
+/// The transports can be used as `Box<dyn DynTransport>` (which implements `Transport` too) to
+/// switch between them at runtime.
+#[async_trait::async_trait]
+pub trait DynTransport: Send + Sync {
+    /// Send a RPC call with the given method and parameters.
+    async fn request_dyn(&self, method: String, params: Option<Params>) -> Result<Response, DynTransportError>;
+}
+
+#[async_trait::async_trait]
+impl<T> DynTransport for T
+where
+    T: Transport + Send + Sync,
+    T::Error: Send + Sync + 'static,
+{
+    async fn request_dyn(&self, method: String, params: Option<Params>) -> Result<Response, DynTransportError> {
+        self.request(method, params).await.map_err(DynTransportError::new)
+    }
+}
+
+/// An object-safe version of `BatchTransport`, which is implemented for all batch transports.
+#[async_trait::async_trait]
+pub trait DynBatchTransport: DynTransport {
+    /// Send a batch of RPC calls with the given method and parameters.
+    async fn request_batch_dyn(&self, batch: Vec<(String, Option<Params>)>)
+        -> Result<BatchResponse, DynTransportError>;
+}
+
+#[async_trait::async_trait]
+impl<T> DynBatchTransport for T
+where
+    T: BatchTransport + Send + Sync,
+    T::Error: Send + Sync + 'static,
+{
+    async fn request_batch_dyn(
+        &self,
+        batch: Vec<(String, Option<Params>)>,
+    ) -> Result<BatchResponse, DynTransportError> {
+        self.request_batch(batch).await.map_err(DynTransportError::new)
+    }
+}
+
+/// An object-safe version of `PubsubTransport`, which is implemented for all pubsub transports
+/// whose notification streams are `Send`.
+#[async_trait::async_trait]
+pub trait DynPubsubTransport: DynTransport {
+    /// Add a subscription to this transport.
+    async fn subscribe_dyn(
+        &self,
+        subscribe_method: String,
+        params: Option<Params>,
+    ) -> Result<(Id, BoxStream<'static, SubscriptionNotification>), DynTransportError>;
+
+    /// Send an unsubscribe request to the server manually.
+    async fn unsubscribe_dyn(&self, unsubscribe_method: String, subscription_id: Id)
+        -> Result<bool, DynTransportError>;
+}
+
+#[async_trait::async_trait]
+impl<T> DynPubsubTransport for T
+where
+    T: PubsubTransport + Send + Sync,
+    T::Error: Send + Sync + 'static,
+    T::NotificationStream: Send + 'static,
+{
+    async fn subscribe_dyn(
+        &self,
+        subscribe_method: String,
+        params: Option<Params>,
+    ) -> Result<(Id, BoxStream<'static, SubscriptionNotification>), DynTransportError> {
+        let (id, stream) = self
+            .subscribe(subscribe_method, params)
+            .await
+            .map_err(DynTransportError::new)?;
+        Ok((id, stream.boxed()))
+    }
+
+    async fn unsubscribe_dyn(
+        &self,
+        unsubscribe_method: String,
+        subscription_id: Id,
+    ) -> Result<bool, DynTransportError> {
+        self.unsubscribe(unsubscribe_method, subscription_id)
+            .await
+            .map_err(DynTransportError::new)
+    }
+}
+
+/// Implements `Transport` for the boxed trait object of `DynTransport` or its sub-traits.
+macro_rules! impl_transport_for_boxed {
+    ($($dyn_transport:ident),+) => {$(
+        #[async_trait::async_trait]
+        impl Transport for Box<dyn $dyn_transport> {
+            type Error = DynTransportError;
+
+            async fn request<M>(&self, method: M, params: Option<Params>) -> Result<Response, Self::Error>
+            where
+                M: Into<String> + Send,
+            {
+                (**self).request_dyn(method.into(), params).await
+            }
+        }
+    )+};
+}
+
+impl_transport_for_boxed!(DynTransport, DynBatchTransport, DynPubsubTransport);
+
+#[async_trait::async_trait]
+impl BatchTransport for Box<dyn DynBatchTransport> {
+    async fn request_batch<I, M>(&self, batch: I) -> Result<BatchResponse, Self::Error>
+    where
+        I: IntoIterator<Item = (M, Option<Params>)> + Send,
+        I::IntoIter: Send,
+        M: Into<String>,
+    {
+        let batch = batch
+            .into_iter()
+            .map(|(method, params)| (method.into(), params))
+            .collect();
+        (**self).request_batch_dyn(batch).await
+    }
+}
+
+#[async_trait::async_trait]
+impl PubsubTransport for Box<dyn DynPubsubTransport> {
+    type NotificationStream = BoxStream<'static, SubscriptionNotification>;
+
+    async fn subscribe<M>(
+        &self,
+        subscribe_method: M,
+        params: Option<Params>,
+    ) -> Result<(Id, Self::NotificationStream), Self::Error>
+    where
+        M: Into<String> + Send,
+    {
+        (**self).subscribe_dyn(subscribe_method.into(), params).await
+    }
+
+    async fn unsubscribe<M>(&self, unsubscribe_method: M, subscription_id: Id) -> Result<bool, Self::Error>
+    where
+        M: Into<String> + Send,
+    {
+        (**self)
+            .unsubscribe_dyn(unsubscribe_method.into(), subscription_id)
+            .await
+    }
+}
@@ -1,23 +1,50 @@
-use std::{fmt, time::Duration};
+use std::{fmt, future::Future, sync::Arc, time::Duration};
 
 use async_tungstenite::tungstenite::handshake::client::Request as HandShakeRequest;
 use futures::channel::mpsc;
 use http::header::{self, HeaderMap, HeaderName, HeaderValue};
+use jsonrpc_types::v2::{Id, Notification};
 
 use crate::{
+    duplex::{CancelNotification, Frontend, UnsubscribeMethod},
     error::WsError,
-    ws_client::{task::WsTask, WsClient},
+    id::{IdGenerator, SequentialIdGenerator},
+    stream::Runtime,
+    ws_client::{executor::Executor, proxy::Proxy, task::WsTask, WsClient},
 };
 
 /// A `WsClientBuilder` can be used to create a `HttpClient` with  custom configuration.
-#[derive(Debug)]
 pub struct WsClientBuilder {
     headers: HeaderMap,
+    pub(crate) subprotocols: Vec<String>,
+    proxy: Option<Proxy>,
+    env_proxy: bool,
+    executor: Option<Box<dyn Executor>>,
+    cancel_notification: Option<CancelNotification>,
+    unsubscribe_method: Option<UnsubscribeMethod>,
+    id_generator: Arc<dyn IdGenerator>,
     timeout: Option<Duration>,
     max_concurrent_request_capacity: usize,
     max_capacity_per_subscription: usize,
 }
 
+impl fmt::Debug for WsClientBuilder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WsClientBuilder")
+            .field("headers", &self.headers)
+            .field("subprotocols", &self.subprotocols)
+            .field("proxy", &self.proxy)
+            .field("env_proxy", &self.env_proxy)
+            .field("executor", &self.executor.as_ref().map(|_| "Executor"))
+            .field("cancel_notification", &self.cancel_notification.as_ref().map(|_| "Fn"))
+            .field("unsubscribe_method", &self.unsubscribe_method.as_ref().map(|_| "Fn"))
+            .field("timeout", &self.timeout)
+            .field("max_concurrent_request_capacity", &self.max_concurrent_request_capacity)
+            .field("max_capacity_per_subscription", &self.max_capacity_per_subscription)
+            .finish()
+    }
+}
+
 impl Default for WsClientBuilder {
     fn default() -> Self {
         Self::new()
@@ -31,6 +58,13 @@ impl WsClientBuilder {
     pub fn new() -> Self {
         Self {
             headers: HeaderMap::new(),
+            subprotocols: Vec::new(),
+            proxy: None,
+            env_proxy: false,
+            executor: None,
+            cancel_notification: None,
+            unsubscribe_method: None,
+            id_generator: Arc::new(SequentialIdGenerator::default()),
             timeout: None,
             max_concurrent_request_capacity: 256,
             max_capacity_per_subscription: 64,
@@ -80,6 +114,44 @@ impl WsClientBuilder {
         self
     }
 
+    /// Adds WebSocket subprotocols (`Sec-WebSocket-Protocol`) for handshake request.
+    ///
+    /// The server must select one of the given subprotocols or none of them,
+    /// the negotiated subprotocol can be obtained by `WsClient::subprotocol()`.
+    pub fn subprotocols<P: AsRef<str>>(mut self, subprotocols: &[P]) -> Self {
+        self.subprotocols
+            .extend(subprotocols.iter().map(|protocol| protocol.as_ref().to_string()));
+        self
+    }
+
+    // ========================================================================
+    // Proxy options
+    // ========================================================================
+
+    /// Tunnels the websocket connection through the given proxy.
+    ///
+    /// Default is no proxy.
+    pub fn proxy(mut self, proxy: Proxy) -> Self {
+        self.proxy = Some(proxy);
+        self
+    }
+
+    /// Enables or disables detecting the proxy from the `HTTP_PROXY`, `HTTPS_PROXY`, `ALL_PROXY`
+    /// and `NO_PROXY` environment variables, if no proxy is given by `proxy`.
+    ///
+    /// Default is disabled.
+    pub fn env_proxy(mut self, enabled: bool) -> Self {
+        self.env_proxy = enabled;
+        self
+    }
+
+    /// Disables proxy, including the proxy detected from the environment variables.
+    pub fn no_proxy(mut self) -> Self {
+        self.proxy = None;
+        self.env_proxy = false;
+        self
+    }
+
     // ========================================================================
     // Channel options
     // ========================================================================
@@ -100,6 +172,22 @@ impl WsClientBuilder {
         self
     }
 
+    // ========================================================================
+    // Runtime options
+    // ========================================================================
+
+    /// Sets the executor that spawns the background task of `WsClient`.
+    ///
+    /// The connection is opened with the sockets of the `ws-tokio` or `ws-async-std` feature,
+    /// so an executor other than tokio (e.g. smol) requires the `ws-async-std` feature;
+    /// tokio sockets panic outside of a tokio runtime.
+    ///
+    /// Default is the tokio runtime (`ws-tokio` feature) or async-std runtime (`ws-async-std` feature).
+    pub fn executor<E: Executor + 'static>(mut self, executor: E) -> Self {
+        self.executor = Some(Box::new(executor));
+        self
+    }
+
     // ========================================================================
     // Timeout options
     // ========================================================================
@@ -115,28 +203,118 @@ impl WsClientBuilder {
         self
     }
 
+    // ========================================================================
+    // Request ID options
+    // ========================================================================
+
+    /// Sets the generator of request IDs.
+    ///
+    /// Default is `SequentialIdGenerator`, which generates sequential numeric IDs starting from 1.
+    pub fn id_generator<G: IdGenerator + 'static>(mut self, id_generator: G) -> Self {
+        self.id_generator = Arc::new(id_generator);
+        self
+    }
+
+    // ========================================================================
+    // Cancellation options
+    // ========================================================================
+
+    /// Sets a function to build the notification sent to the server, when a pending request
+    /// is canceled because of timeout or dropping the request future.
+    ///
+    /// The canceled request is always removed from the client, and its response will be ignored.
+    ///
+    /// ```
+    /// # use async_jsonrpc_client::{Notification, Params, Value, WsClientBuilder};
+    /// let builder = WsClientBuilder::new().cancel_notification(|id| {
+    ///     let mut params = std::collections::BTreeMap::new();
+    ///     params.insert("id".to_string(), Value::from(id.clone()));
+    ///     Notification::new("$/cancelRequest", Some(Params::Map(params)))
+    /// });
+    /// ```
+    pub fn cancel_notification<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&Id) -> Notification + Send + Sync + 'static,
+    {
+        self.cancel_notification = Some(Box::new(f));
+        self
+    }
+
+    /// Sets a function to get the unsubscribe method by the subscribe method, which is called to
+    /// close the subscription on the server, when the subscribe future is dropped after the server
+    /// has answered it.
+    ///
+    /// Default replaces `subscribe` with `unsubscribe` in the subscribe method (e.g. `eth_subscribe`
+    /// to `eth_unsubscribe`), and the subscription isn't closed if the function returns `None`.
+    pub fn unsubscribe_method<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&str) -> Option<String> + Send + Sync + 'static,
+    {
+        self.unsubscribe_method = Some(Box::new(f));
+        self
+    }
+
     // ========================================================================
 
     /// Returns a `WsClient` that uses this `WsClientBuilder` configuration.
-    pub async fn build(self, url: impl Into<String>) -> Result<WsClient, WsError> {
+    ///
+    /// The background task of the client is spawned by the executor set by `executor()`,
+    /// or by the tokio/async-std runtime by default.
+    pub async fn build(mut self, url: impl Into<String>) -> Result<WsClient, WsError> {
+        let executor = self.executor.take();
+        let (client, task) = self.build_with_task(url).await?;
+        match executor {
+            Some(executor) => executor.spawn(Box::pin(task)),
+            None => Runtime::detect().spawn(Box::pin(task)),
+        }
+        Ok(client)
+    }
+
+    /// Returns a `WsClient` that uses this `WsClientBuilder` configuration, and the background
+    /// task of the client.
+    ///
+    /// The background task processes all requests and subscriptions of the client, it must be
+    /// polled (e.g. spawned on any executor) for the client to work.
+    /// The executor set by `executor()` is ignored.
+    ///
+    /// With the `ws-tokio` feature, both this function and the background task must run within
+    /// a tokio runtime; use the `ws-async-std` feature to run them on other executors.
+    pub async fn build_with_task(
+        self,
+        url: impl Into<String>,
+    ) -> Result<(WsClient, impl Future<Output = ()> + Send + 'static), WsError> {
         let url = url.into();
         let mut handshake_builder = HandShakeRequest::get(&url);
         let headers = handshake_builder.headers_mut().expect("handshake request just created");
         headers.extend(self.headers);
+        if !self.subprotocols.is_empty() {
+            let subprotocols = HeaderValue::from_str(&self.subprotocols.join(", "))?;
+            headers.insert(header::SEC_WEBSOCKET_PROTOCOL, subprotocols);
+        }
         let handshake_req = handshake_builder.body(()).map_err(WsError::HttpFormat)?;
+        let proxy = match self.proxy {
+            Some(proxy) => Some(proxy),
+            None if self.env_proxy => Proxy::from_env(handshake_req.uri()),
+            None => None,
+        };
 
         let (to_back, from_front) = mpsc::channel(self.max_concurrent_request_capacity);
         log::debug!("Connecting '{}' ...", url);
-        let task = WsTask::handshake(handshake_req, self.max_capacity_per_subscription).await?;
+        let task = WsTask::handshake(
+            handshake_req,
+            proxy.as_ref(),
+            self.max_capacity_per_subscription,
+            self.cancel_notification,
+            self.unsubscribe_method,
+        )
+        .await?;
         log::debug!("Connect '{}' successfully", url);
-        #[cfg(feature = "ws-async-std")]
-        let _handle = async_std::task::spawn(task.into_task(from_front));
-        #[cfg(feature = "ws-tokio")]
-        let _handle = tokio::spawn(task.into_task(from_front));
-
-        Ok(WsClient {
-            to_back,
-            timeout: self.timeout,
-        })
+        let subprotocol = task.subprotocol().map(ToString::to_string);
+
+        let client = WsClient {
+            frontend: Frontend::new(to_back, self.id_generator, self.timeout),
+            subprotocol,
+        };
+        Ok((client, task.into_task(from_front)))
     }
 }
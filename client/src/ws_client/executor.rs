@@ -0,0 +1,23 @@
+use futures::future::BoxFuture;
+
+/// An executor that spawns the background task of `WsClient`.
+///
+/// Implemented for any `Fn(BoxFuture<'static, ()>)`, so a closure can be used directly,
+/// e.g. `|task| smol::spawn(task).detach()` with the `ws-async-std` feature.
+///
+/// The executor only spawns the task, the connection is still driven by the runtime of the
+/// enabled feature: with the `ws-tokio` feature the socket is a tokio socket, which panics
+/// outside of a tokio runtime, so executors other than tokio require the `ws-async-std` feature.
+pub trait Executor: Send + Sync {
+    /// Spawns the given background task, which should run to completion.
+    fn spawn(&self, task: BoxFuture<'static, ()>);
+}
+
+impl<F> Executor for F
+where
+    F: Fn(BoxFuture<'static, ()>) + Send + Sync,
+{
+    fn spawn(&self, task: BoxFuture<'static, ()>) {
+        self(task)
+    }
+}
@@ -0,0 +1,346 @@
+use std::{
+    env, fmt, io,
+    net::{Ipv4Addr, Ipv6Addr},
+};
+
+use async_tungstenite::tungstenite::error::UrlError;
+use futures::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use http::Uri;
+
+use crate::error::WsError;
+
+/// The max length of the HTTP `CONNECT` response header.
+const MAX_CONNECT_RESPONSE_SIZE: usize = 8 * 1024;
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub(crate) enum ProxyScheme {
+    /// HTTP proxy, tunneling by the `CONNECT` method.
+    Http,
+    /// SOCKS5 proxy, the target host name is resolved by the proxy.
+    Socks5,
+}
+
+/// Configuration of a proxy that the websocket connection is tunneled through.
+///
+/// Supports HTTP proxies (using the `CONNECT` method) and SOCKS5 proxies.
+#[derive(Clone, Eq, PartialEq)]
+pub struct Proxy {
+    pub(crate) scheme: ProxyScheme,
+    pub(crate) host: String,
+    pub(crate) port: u16,
+    pub(crate) auth: Option<(String, String)>,
+}
+
+impl fmt::Debug for Proxy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Proxy")
+            .field("scheme", &self.scheme)
+            .field("host", &self.host)
+            .field("port", &self.port)
+            .field("auth", &self.auth.as_ref().map(|(username, _)| (username, "***")))
+            .finish()
+    }
+}
+
+impl Proxy {
+    /// Creates a HTTP proxy with the given `url`, e.g. `http://127.0.0.1:8080`.
+    ///
+    /// The websocket connection is tunneled by the HTTP `CONNECT` method.
+    pub fn http(url: impl AsRef<str>) -> Result<Self, WsError> {
+        Self::parse_with_scheme(url.as_ref(), ProxyScheme::Http)
+    }
+
+    /// Creates a SOCKS5 proxy with the given `url`, e.g. `socks5://127.0.0.1:1080`.
+    pub fn socks5(url: impl AsRef<str>) -> Result<Self, WsError> {
+        Self::parse_with_scheme(url.as_ref(), ProxyScheme::Socks5)
+    }
+
+    /// Creates a proxy with the given `url`, the kind of proxy is detected by the url scheme
+    /// (`http://`, `socks5://` or `socks5h://`).
+    pub fn new(url: impl AsRef<str>) -> Result<Self, WsError> {
+        let url = url.as_ref();
+        let scheme = match url.split_once("://") {
+            Some((scheme, _)) if scheme.eq_ignore_ascii_case("http") => ProxyScheme::Http,
+            Some((scheme, _)) if scheme.eq_ignore_ascii_case("socks5") || scheme.eq_ignore_ascii_case("socks5h") => {
+                ProxyScheme::Socks5
+            }
+            Some(_) => return Err(WsError::Url(UrlError::UnsupportedUrlScheme)),
+            None => ProxyScheme::Http,
+        };
+        Self::parse_with_scheme(url, scheme)
+    }
+
+    /// Sets the credentials used to authenticate with the proxy.
+    ///
+    /// Uses `Proxy-Authorization: Basic` for HTTP proxies and username/password authentication
+    /// for SOCKS5 proxies.
+    pub fn basic_auth(mut self, username: impl Into<String>, password: impl Into<String>) -> Self {
+        self.auth = Some((username.into(), password.into()));
+        self
+    }
+
+    fn parse_with_scheme(url: &str, scheme: ProxyScheme) -> Result<Self, WsError> {
+        // `http::Uri` doesn't provide the userinfo, so split it out manually.
+        let (prefix, rest) = match url.split_once("://") {
+            Some((prefix, rest)) => (Some(prefix), rest),
+            None => (None, url),
+        };
+        let (auth, rest) = match rest
+            .split_once('/')
+            .map_or(rest, |(authority, _)| authority)
+            .rsplit_once('@')
+        {
+            Some((userinfo, _)) => {
+                let auth = match userinfo.split_once(':') {
+                    Some((username, password)) => (username.to_string(), password.to_string()),
+                    None => (userinfo.to_string(), String::new()),
+                };
+                (Some(auth), &rest[userinfo.len() + 1..])
+            }
+            None => (None, rest),
+        };
+        let url = match prefix {
+            Some(prefix) => format!("{}://{}", prefix, rest),
+            None => format!("proxy://{}", rest),
+        };
+        let uri = url.parse::<Uri>().map_err(|_| WsError::Url(UrlError::NoHostName))?;
+        let host = uri
+            .host()
+            .filter(|host| !host.is_empty())
+            .ok_or(WsError::Url(UrlError::EmptyHostName))?;
+        let host = host.trim_start_matches('[').trim_end_matches(']').to_string();
+        let port = uri.port_u16().unwrap_or(match scheme {
+            ProxyScheme::Http => 80,
+            ProxyScheme::Socks5 => 1080,
+        });
+        Ok(Self {
+            scheme,
+            host,
+            port,
+            auth,
+        })
+    }
+
+    /// Detects the proxy for the given websocket `uri` from the environment variables.
+    ///
+    /// Uses `HTTPS_PROXY` for `wss://` urls, `HTTP_PROXY` for `ws://` urls, and `ALL_PROXY`
+    /// as fallback (also the lowercase variants). Hosts listed in `NO_PROXY` are connected directly.
+    pub(crate) fn from_env(uri: &Uri) -> Option<Self> {
+        let host = uri.host()?;
+        if let Some(no_proxy) = env_var(&["NO_PROXY", "no_proxy"]) {
+            if no_proxy_matches(&no_proxy, host) {
+                return None;
+            }
+        }
+        let keys: &[&str] = if is_secure(uri) {
+            &["HTTPS_PROXY", "https_proxy", "ALL_PROXY", "all_proxy"]
+        } else {
+            &["HTTP_PROXY", "http_proxy", "ALL_PROXY", "all_proxy"]
+        };
+        let url = env_var(keys)?;
+        match Self::new(&url) {
+            Ok(proxy) => Some(proxy),
+            Err(err) => {
+                log::warn!("Ignore invalid proxy '{}' in the environment: {}", url, err);
+                None
+            }
+        }
+    }
+
+    /// Returns the address of the proxy.
+    pub(crate) fn addr(&self) -> (&str, u16) {
+        (self.host.as_str(), self.port)
+    }
+
+    /// Establishes a tunnel to the host of the websocket `uri` over the `stream` connected to the proxy.
+    pub(crate) async fn tunnel<S>(&self, stream: &mut S, uri: &Uri) -> Result<(), WsError>
+    where
+        S: AsyncRead + AsyncWrite + Unpin,
+    {
+        let host = uri.host().ok_or(WsError::Url(UrlError::NoHostName))?;
+        let host = host.trim_start_matches('[').trim_end_matches(']');
+        let port = uri.port_u16().unwrap_or(if is_secure(uri) { 443 } else { 80 });
+
+        log::debug!(
+            "Tunneling {}:{} through proxy {}:{} ({:?})",
+            host,
+            port,
+            self.host,
+            self.port,
+            self.scheme
+        );
+        match self.scheme {
+            ProxyScheme::Http => self.http_connect(stream, host, port).await?,
+            ProxyScheme::Socks5 => self.socks5_connect(stream, host, port).await?,
+        }
+        log::debug!("Proxy tunnel to {}:{} established", host, port);
+        Ok(())
+    }
+
+    async fn http_connect<S>(&self, stream: &mut S, host: &str, port: u16) -> io::Result<()>
+    where
+        S: AsyncRead + AsyncWrite + Unpin,
+    {
+        let authority = if host.parse::<Ipv6Addr>().is_ok() {
+            format!("[{}]:{}", host, port)
+        } else {
+            format!("{}:{}", host, port)
+        };
+        let mut request = format!("CONNECT {0} HTTP/1.1\r\nHost: {0}\r\n", authority);
+        if let Some((username, password)) = &self.auth {
+            let auth = base64::encode(format!("{}:{}", username, password));
+            request.push_str(&format!("Proxy-Authorization: Basic {}\r\n", auth));
+        }
+        request.push_str("\r\n");
+        stream.write_all(request.as_bytes()).await?;
+        stream.flush().await?;
+
+        // Read the response byte by byte, so that nothing after the response header is consumed.
+        let mut response = Vec::with_capacity(128);
+        let mut byte = [0u8; 1];
+        while !response.ends_with(b"\r\n\r\n") {
+            if response.len() >= MAX_CONNECT_RESPONSE_SIZE {
+                return Err(proxy_error("HTTP proxy response header is too large"));
+            }
+            if stream.read(&mut byte).await? == 0 {
+                return Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    "HTTP proxy closed the connection",
+                ));
+            }
+            response.push(byte[0]);
+        }
+
+        let response = String::from_utf8_lossy(&response);
+        let status_line = response.lines().next().unwrap_or_default();
+        let status = status_line.split_whitespace().nth(1).unwrap_or_default();
+        if status_line.starts_with("HTTP/1.") && status.starts_with('2') && status.len() == 3 {
+            Ok(())
+        } else {
+            Err(proxy_error(format!("HTTP proxy CONNECT failed: {}", status_line)))
+        }
+    }
+
+    async fn socks5_connect<S>(&self, stream: &mut S, host: &str, port: u16) -> io::Result<()>
+    where
+        S: AsyncRead + AsyncWrite + Unpin,
+    {
+        const VERSION: u8 = 0x05;
+        const NO_AUTH: u8 = 0x00;
+        const USERNAME_PASSWORD: u8 = 0x02;
+
+        // Greeting with supported authentication methods.
+        let greeting: &[u8] = if self.auth.is_some() {
+            &[VERSION, 2, NO_AUTH, USERNAME_PASSWORD]
+        } else {
+            &[VERSION, 1, NO_AUTH]
+        };
+        stream.write_all(greeting).await?;
+        let mut reply = [0u8; 2];
+        stream.read_exact(&mut reply).await?;
+        if reply[0] != VERSION {
+            return Err(proxy_error("invalid SOCKS5 proxy version"));
+        }
+        match (reply[1], &self.auth) {
+            (NO_AUTH, _) => {}
+            (USERNAME_PASSWORD, Some((username, password))) => {
+                if username.len() > 255 || password.len() > 255 {
+                    return Err(proxy_error("SOCKS5 username or password is too long"));
+                }
+                let mut request = vec![0x01, username.len() as u8];
+                request.extend_from_slice(username.as_bytes());
+                request.push(password.len() as u8);
+                request.extend_from_slice(password.as_bytes());
+                stream.write_all(&request).await?;
+                stream.read_exact(&mut reply).await?;
+                if reply[1] != 0x00 {
+                    return Err(proxy_error("SOCKS5 proxy authentication failed"));
+                }
+            }
+            _ => {
+                return Err(proxy_error("no acceptable SOCKS5 authentication method"));
+            }
+        }
+
+        // Connect request, let the proxy resolve the domain name.
+        let mut request = vec![VERSION, 0x01, 0x00];
+        if let Ok(ip) = host.parse::<Ipv4Addr>() {
+            request.push(0x01);
+            request.extend_from_slice(&ip.octets());
+        } else if let Ok(ip) = host.parse::<Ipv6Addr>() {
+            request.push(0x04);
+            request.extend_from_slice(&ip.octets());
+        } else {
+            if host.len() > 255 {
+                return Err(proxy_error("SOCKS5 target host name is too long"));
+            }
+            request.push(0x03);
+            request.push(host.len() as u8);
+            request.extend_from_slice(host.as_bytes());
+        }
+        request.extend_from_slice(&port.to_be_bytes());
+        stream.write_all(&request).await?;
+
+        let mut reply = [0u8; 4];
+        stream.read_exact(&mut reply).await?;
+        if reply[1] != 0x00 {
+            return Err(proxy_error(format!(
+                "SOCKS5 proxy connect failed, reply code: {}",
+                reply[1]
+            )));
+        }
+        // Skip the bound address and port.
+        let addr_len = match reply[3] {
+            0x01 => 4,
+            0x04 => 16,
+            0x03 => {
+                let mut len = [0u8; 1];
+                stream.read_exact(&mut len).await?;
+                len[0] as usize
+            }
+            _ => return Err(proxy_error("invalid SOCKS5 address type")),
+        };
+        let mut bound = vec![0u8; addr_len + 2];
+        stream.read_exact(&mut bound).await?;
+        Ok(())
+    }
+}
+
+fn proxy_error(msg: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg.into())
+}
+
+fn is_secure(uri: &Uri) -> bool {
+    matches!(uri.scheme_str(), Some("wss") | Some("https"))
+}
+
+fn env_var(keys: &[&str]) -> Option<String> {
+    keys.iter()
+        .filter_map(|key| env::var(key).ok())
+        .find(|value| !value.trim().is_empty())
+}
+
+/// Checks whether the `host` matches any entry of the comma-separated `NO_PROXY` list.
+pub(crate) fn no_proxy_matches(no_proxy: &str, host: &str) -> bool {
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    no_proxy
+        .split(',')
+        .map(|entry| entry.trim())
+        .filter(|entry| !entry.is_empty())
+        .any(|entry| {
+            if entry == "*" {
+                return true;
+            }
+            // Ignore the port of the entry.
+            let entry = match entry.rsplit_once(':') {
+                Some((entry, port)) if !entry.contains(':') && port.parse::<u16>().is_ok() => entry,
+                _ => entry,
+            };
+            let entry = entry.trim_start_matches('[').trim_end_matches(']');
+            let domain = entry.trim_start_matches('.');
+            host.eq_ignore_ascii_case(domain)
+                || (host.len() > domain.len()
+                    && host[..host.len() - domain.len()].ends_with('.')
+                    && host[host.len() - domain.len()..].eq_ignore_ascii_case(domain))
+        })
+}
@@ -1,466 +1,181 @@
-#[cfg(feature = "ws-async-std")]
-use async_tungstenite::async_std::{connect_async, ConnectStream};
-#[cfg(feature = "ws-tokio")]
-use async_tungstenite::tokio::{connect_async, ConnectStream};
-use async_tungstenite::{
-    tungstenite::{handshake::client::Request as HandShakeRequest, protocol::Message},
-    WebSocketStream,
+use std::pin::Pin;
+
+use async_tungstenite::tungstenite::{
+    handshake::client::{Request as HandShakeRequest, Response as HandShakeResponse},
+    protocol::Message,
 };
 use futures::{
     channel::mpsc,
-    sink::SinkExt,
-    stream::{SplitSink, SplitStream, StreamExt},
+    future,
+    sink::{Sink, SinkExt},
+    stream::{Stream, StreamExt},
 };
-use jsonrpc_types::v2::*;
+use http::header;
 
 use crate::{
+    duplex::{manager::ToBackTaskMessage, CancelNotification, DuplexTask, UnsubscribeMethod},
     error::{WsClientError, WsError},
-    ws_client::{
-        manager::{RequestStatus, TaskManager},
-        ToBackTaskMessage,
-    },
+    stream::Runtime,
+    ws_client::proxy::Proxy,
 };
 
-type WsMsgSender = SplitSink<WebSocketStream<ConnectStream>, Message>;
-type WsMsgReceiver = SplitStream<WebSocketStream<ConnectStream>>;
-
-struct WsSender {
-    id: u64,
-    sender: WsMsgSender,
-}
-
-impl WsSender {
-    fn new(sender: WsMsgSender) -> Self {
-        Self { id: 1, sender }
-    }
-
-    async fn send_message(&mut self, msg: Message) -> Result<(), WsError> {
-        log::trace!("[backend] Send websocket message: {}", msg);
-        self.sender.feed(msg).await?;
-        self.sender.flush().await?;
-        Ok(())
-    }
-
-    async fn send_request(&mut self, method: impl Into<String>, params: Option<Params>) -> Result<u64, WsError> {
-        let method = method.into();
-        let id = self.id;
-        self.id = id.wrapping_add(1);
-        let call = Request::new(method, params, Id::Num(id));
-        let request = serde_json::to_string(&call).expect("serialize call; qed");
-        log::debug!("[backend] Send a method call: {}", request);
-        self.send_message(Message::Text(request)).await?;
-        Ok(id)
-    }
-
-    async fn send_batch_request<I, M>(&mut self, batch: I) -> Result<Vec<u64>, WsError>
-    where
-        I: IntoIterator<Item = (M, Option<Params>)>,
-        M: Into<String>,
-    {
-        let mut calls = vec![];
-        let mut ids = vec![];
-        for (method, params) in batch {
-            let method = method.into();
-            let id = self.id;
-            self.id = id.wrapping_add(1);
-            let call = Request::new(method, params, Id::Num(id));
-            ids.push(id);
-            calls.push(call);
-        }
-        let request = RequestObj::Batch(calls);
-        let request = serde_json::to_string(&request).expect("serialize calls; qed");
-        log::debug!("[backend] Send a batch of method calls: {}", request);
-        self.send_message(Message::Text(request)).await?;
-        Ok(ids)
-    }
-
-    async fn start_subscription(
-        &mut self,
-        subscribe_method: impl Into<String>,
-        params: Option<Params>,
-    ) -> Result<u64, WsError> {
-        self.send_request(subscribe_method, params).await
-    }
-
-    async fn stop_subscription(
-        &mut self,
-        unsubscribe_method: impl Into<String>,
-        subscription_id: Id,
-    ) -> Result<u64, WsError> {
-        let subscription_id = serde_json::to_value(subscription_id).expect("serialize Id");
-        let params = Params::Array(vec![subscription_id]);
-        self.send_request(unsubscribe_method, Some(params)).await
-    }
-}
-
-struct WsReceiver(WsMsgReceiver);
-impl WsReceiver {
-    fn new(receiver: WsMsgReceiver) -> Self {
-        Self(receiver)
-    }
-
-    async fn recv_message(&mut self) -> Result<Message, WsError> {
-        loop {
-            if let Some(message) = self.0.next().await {
-                let message = message?;
-                log::trace!("[backend] Receive websocket message: {}", message);
-                return Ok(message);
-            }
-        }
-    }
-}
+type WsMsgSender = Pin<Box<dyn Sink<Message, Error = WsError> + Send>>;
+type WsMsgReceiver = Pin<Box<dyn Stream<Item = Result<Message, WsError>> + Send>>;
 
 /// Helper struct for managing tasks on a websocket connection.
 pub(crate) struct WsTask {
-    sender: WsSender,
-    receiver: WsReceiver,
-    manager: TaskManager,
+    sender: WsMsgSender,
+    receiver: WsMsgReceiver,
+    task: DuplexTask<WsClientError>,
+    subprotocol: Option<String>,
 }
 
 impl WsTask {
     /// Setup websocket connection.
     pub(crate) async fn handshake(
         request: HandShakeRequest,
+        proxy: Option<&Proxy>,
         max_capacity_per_subscription: usize,
+        cancel_notification: Option<CancelNotification>,
+        unsubscribe_method: Option<UnsubscribeMethod>,
     ) -> Result<Self, WsError> {
         let uri = request.uri().clone();
+        let request_subprotocols = subprotocols_of(request.headers());
         log::debug!("WebSocket handshake {}, request: {:?}", uri, request);
-        let (ws_stream, response) = connect_async(request).await?;
+        let (sink, stream, response) = match Runtime::detect() {
+            #[cfg(feature = "ws-tokio")]
+            Runtime::Tokio => connect_tokio(request, proxy).await?,
+            // the tokio runtime is enabled by other clients only, e.g. `tcp-tokio` with `ws-async-std`.
+            #[cfg(all(feature = "stream-tokio", not(feature = "ws-tokio")))]
+            Runtime::Tokio => connect_async_std(request, proxy).await?,
+            #[cfg(feature = "ws-async-std")]
+            Runtime::AsyncStd => connect_async_std(request, proxy).await?,
+            // the async-std runtime is enabled by other clients only, e.g. `tcp-async-std` with `ws-tokio`.
+            #[cfg(all(feature = "stream-async-std", not(feature = "ws-async-std")))]
+            Runtime::AsyncStd => connect_tokio(request, proxy).await?,
+        };
         log::debug!("WebSocket handshake {}, response: {:?}", uri, response);
-        let (sink, stream) = ws_stream.split();
+        let subprotocol = negotiated_subprotocol(&request_subprotocols, response)?;
         Ok(Self {
-            sender: WsSender::new(sink),
-            receiver: WsReceiver::new(stream),
-            manager: TaskManager::new(max_capacity_per_subscription),
+            sender: sink,
+            receiver: stream,
+            task: DuplexTask::new(max_capacity_per_subscription)
+                .cancel_notification(cancel_notification)
+                .unsubscribe_method(unsubscribe_method),
+            subprotocol,
         })
     }
 
-    /// Convert self into a spawnable runtime task that processes message sent from the frontend and
-    /// received from backend.
-    pub(crate) async fn into_task(self, from_front: mpsc::Receiver<ToBackTaskMessage>) {
-        let Self {
-            mut sender,
-            receiver,
-            mut manager,
-        } = self;
-
-        let from_back = futures::stream::unfold(receiver, |mut receiver| async {
-            let res = receiver.recv_message().await;
-            Some((res, receiver))
-        });
-        futures::pin_mut!(from_front, from_back);
-
-        loop {
-            futures::select! {
-                msg = from_front.next() => match msg {
-                    Some(msg) => handle_from_front_message(msg, &mut manager, &mut sender).await,
-                    None => {
-                        log::debug!("[backend] Frontend channel dropped; terminate client");
-                        break;
-                    }
-                },
-                msg = from_back.next() => match msg {
-                    Some(Ok(msg)) => if let Err(err) = handle_from_back_message(msg, &mut manager, &mut sender).await {
-                        log::error!("[backend] Handle websocket message error: {}; terminate client", err);
-                        break;
-                    }
-                    Some(Err(err)) => {
-                        log::error!("[backend] Receive websocket message error: {}; terminate client", err);
-                        break;
-                    }
-                    None => {
-                        log::debug!("[backend] Backend channel dropped; terminate client");
-                        break;
-                    }
-                },
-            }
-        }
+    /// Returns the subprotocol negotiated with the server.
+    pub(crate) fn subprotocol(&self) -> Option<&str> {
+        self.subprotocol.as_deref()
     }
-}
 
-async fn handle_from_front_message(msg: ToBackTaskMessage, manager: &mut TaskManager, sender: &mut WsSender) {
-    match msg {
-        ToBackTaskMessage::Request {
-            method,
-            params,
-            send_back,
-        } => match sender.send_request(method, params).await {
-            Ok(req_id) => {
-                if let Err(send_back) = manager.insert_pending_method_call(req_id, send_back) {
-                    send_back
-                        .send(Err(WsClientError::DuplicateRequestId))
-                        .expect("Send request error back");
+    /// Convert self into a spawnable runtime task that processes message sent from the frontend and
+    /// received from backend.
+    ///
+    /// The JSON-RPC messages are sent and received as `Text` messages, the `Ping` messages are
+    /// replied by the websocket stream automatically.
+    pub(crate) async fn into_task(self, from_front: mpsc::Receiver<ToBackTaskMessage<WsClientError>>) {
+        let sink = self
+            .sender
+            .sink_map_err(WsClientError::WebSocket)
+            .with(|msg: String| future::ok::<_, WsClientError>(Message::Text(msg)));
+        let stream = self.receiver.filter_map(|msg| {
+            future::ready(match msg {
+                Ok(Message::Text(msg)) => Some(Ok(msg)),
+                Ok(Message::Binary(msg)) => {
+                    log::warn!("[backend] Ignore `Binary` message: {:?}", msg);
+                    None
                 }
-            }
-            Err(err) => {
-                log::warn!("[backend] Send request error: {}", err);
-                send_back
-                    .send(Err(WsClientError::WebSocket(err)))
-                    .expect("Send request error back");
-            }
-        },
-        ToBackTaskMessage::BatchRequest { batch, send_back } => match sender.send_batch_request(batch).await {
-            Ok(req_ids) => {
-                let min_request_id = req_ids.into_iter().min().expect("must have one");
-                if let Err(send_back) = manager.insert_pending_batch_method_call(min_request_id, send_back) {
-                    send_back
-                        .send(Err(WsClientError::DuplicateRequestId))
-                        .expect("Send batch request error back");
+                Ok(Message::Ping(msg)) => {
+                    log::debug!("[backend] Receive `Ping` message: {:?}", msg);
+                    None
                 }
-            }
-            Err(err) => {
-                log::warn!("[backend] Send a batch of requests error: {}", err);
-                send_back
-                    .send(Err(WsClientError::WebSocket(err)))
-                    .expect("Send batch request error back");
-            }
-        },
-        ToBackTaskMessage::Subscribe {
-            subscribe_method,
-            params,
-            send_back,
-        } => match sender.start_subscription(subscribe_method, params).await {
-            Ok(req_id) => {
-                if let Err(send_back) = manager.insert_pending_subscription(req_id, send_back) {
-                    send_back
-                        .send(Err(WsClientError::DuplicateRequestId))
-                        .expect("Send subscription request error back");
+                Ok(Message::Pong(msg)) => {
+                    log::debug!("[backend] Receive `Pong` message: {:?}", msg);
+                    None
                 }
-            }
-            Err(err) => {
-                log::warn!("[backend] Send subscription request error: {}", err);
-                send_back
-                    .send(Err(WsClientError::WebSocket(err)))
-                    .expect("Send subscription request error back");
-            }
-        },
-        ToBackTaskMessage::Unsubscribe {
-            unsubscribe_method,
-            subscription_id,
-            send_back,
-        } => match sender
-            .stop_subscription(unsubscribe_method, subscription_id.clone())
-            .await
-        {
-            Ok(req_id) => {
-                if let Err(send_back) = manager.insert_pending_unsubscribe(req_id, subscription_id, send_back) {
-                    send_back
-                        .send(Err(WsClientError::DuplicateRequestId))
-                        .expect("Send unsubscribe request error back");
+                Ok(Message::Close(msg)) => {
+                    log::error!("[backend] Receive `Close` message: {:?}; terminate client", msg);
+                    Some(Err(WsClientError::WebSocket(WsError::ConnectionClosed)))
                 }
-            }
-            Err(err) => {
-                log::warn!("[backend] Send unsubscribe request error: {}", err);
-                send_back
-                    .send(Err(WsClientError::WebSocket(err)))
-                    .expect("Send unsubscribe request error back");
-            }
-        },
-    }
-}
-
-async fn handle_from_back_message(
-    msg: Message,
-    manager: &mut TaskManager,
-    sender: &mut WsSender,
-) -> Result<(), WsClientError> {
-    match msg {
-        Message::Text(msg) => {
-            if let Ok(response) = serde_json::from_str::<ResponseObj>(&msg) {
-                handle_response_message(response, manager)?
-            } else if let Ok(notification) = serde_json::from_str::<SubscriptionNotification>(&msg) {
-                handle_subscription_notification_message(notification, manager);
-            } else {
-                log::warn!("[backend] Ignore unknown websocket text message: {}", msg);
-            }
-        }
-        Message::Binary(msg) => log::warn!("[backend] Ignore `Binary` message: {:?}", msg),
-        Message::Ping(msg) => {
-            log::debug!("[backend] Receive `Ping` message: {:?}", msg);
-            log::debug!("[backend] Send `Pong` message back, message: {:?}", msg);
-            sender.send_message(Message::Pong(msg)).await?;
-        }
-        Message::Pong(msg) => log::debug!("[backend] Receive `Pong` message: {:?}", msg),
-        Message::Close(msg) => {
-            log::error!("[backend] Receive `Close` message: {:?}; terminate client", msg);
-            return Err(WsClientError::WebSocket(WsError::ConnectionClosed));
-        }
-    }
-    Ok(())
-}
-
-fn handle_response_message(response: ResponseObj, manager: &mut TaskManager) -> Result<(), WsClientError> {
-    match response {
-        ResponseObj::Single(response) => handle_single_output(response, manager),
-        ResponseObj::Batch(responses) => handle_batch_output(responses, manager),
+                Err(err) => Some(Err(WsClientError::WebSocket(err))),
+            })
+        });
+        self.task.into_task(sink, stream, from_front).await
     }
 }
 
-fn handle_single_output(response: Response, manager: &mut TaskManager) -> Result<(), WsClientError> {
-    let response_id = response_id_of(&response)?;
-    match manager.request_status(&response_id) {
-        RequestStatus::PendingMethodCall => {
-            log::debug!("[backend] Handle response of method call: id={}", response_id);
-            let send_back = manager
-                .complete_pending_method_call(response_id)
-                .ok_or(WsClientError::InvalidRequestId)?;
-            send_back.send(Ok(response)).expect("Send single response back");
-            Ok(())
-        }
-        RequestStatus::PendingSubscription => {
-            log::debug!("[backend] Handle response of subscription request: id={}", response_id);
-            let send_back = manager
-                .complete_pending_subscription(response_id)
-                .ok_or(WsClientError::InvalidRequestId)?;
-            let subscription_id = match response {
-                Response::Success(success) => match serde_json::from_value::<Id>(success.result) {
-                    Ok(id) => id,
-                    Err(err) => {
-                        send_back
-                            .send(Err(WsClientError::Json(err)))
-                            .expect("Send response error back");
-                        return Ok(());
-                    }
-                },
-                Response::Failure(_) => {
-                    send_back
-                        .send(Err(WsClientError::InvalidSubscriptionId))
-                        .expect("Send response error back");
-                    return Ok(());
-                }
-            };
-
-            let (subscribe_tx, subscribe_rx) = mpsc::channel(manager.max_capacity_per_subscription);
-            if manager
-                .insert_active_subscription(response_id, subscription_id.clone(), subscribe_tx)
-                .is_ok()
-            {
-                send_back
-                    .send(Ok((subscription_id, subscribe_rx)))
-                    .expect("Send subscription stream back");
-            } else {
-                send_back
-                    .send(Err(WsClientError::InvalidSubscriptionId))
-                    .expect("Send subscription error back");
-            }
-            Ok(())
-        }
-        RequestStatus::PendingUnsubscribe => {
-            log::debug!("[backend] Handle response of unsubscribe request: id={}", response_id);
-            let (subscription_id, send_back) = manager
-                .complete_pending_unsubscribe(response_id)
-                .ok_or(WsClientError::InvalidRequestId)?;
-            let result = match response {
-                Response::Success(success) => match serde_json::from_value::<bool>(success.result) {
-                    Ok(result) => result,
-                    Err(err) => {
-                        send_back
-                            .send(Err(WsClientError::Json(err)))
-                            .expect("Send response error back");
-                        return Ok(());
-                    }
-                },
-                Response::Failure(failure) => {
-                    log::warn!("[backend] Unexpected response of unsubscribe request: {}", failure);
-                    send_back
-                        .send(Err(WsClientError::InvalidUnsubscribeResult))
-                        .expect("Send response error back");
-                    return Ok(());
-                }
-            };
-
-            send_back.send(Ok(result)).expect("Send single response back");
-
-            if result {
-                // clean the subscription of manager according to the subscription id when unsubscribe successfully.
-                if let Some(request_id) = manager.get_request_id_by(&subscription_id) {
-                    manager.remove_active_subscription(request_id, subscription_id);
-                } else {
-                    log::error!(
-                        "[backend] Task manager cannot find subscription: id={:?}",
-                        subscription_id
-                    );
-                }
-            }
-            Ok(())
-        }
-        RequestStatus::ActiveSubscription | RequestStatus::PendingBatchMethodCall | RequestStatus::Invalid => {
-            Err(WsClientError::InvalidRequestId)
+/// Connects the websocket server with tokio runtime.
+#[cfg(feature = "ws-tokio")]
+async fn connect_tokio(
+    request: HandShakeRequest,
+    proxy: Option<&Proxy>,
+) -> Result<(WsMsgSender, WsMsgReceiver, HandShakeResponse), WsError> {
+    use async_tungstenite::tokio::{client_async_tls, connect_async, TokioAdapter};
+
+    let (ws_stream, response) = match proxy {
+        Some(proxy) => {
+            let mut stream = tokio::net::TcpStream::connect(proxy.addr()).await?;
+            proxy.tunnel(&mut TokioAdapter::new(&mut stream), request.uri()).await?;
+            client_async_tls(request, stream).await?
         }
-    }
-}
-
-fn response_id_of(resp: &Response) -> Result<u64, WsClientError> {
-    Ok(*resp
-        .id()
-        .ok_or(WsClientError::InvalidRequestId)?
-        .as_number()
-        .expect("Response ID must be number"))
+        None => connect_async(request).await?,
+    };
+    let (sink, stream) = ws_stream.split();
+    Ok((Box::pin(sink), Box::pin(stream), response))
 }
 
-fn handle_batch_output(responses: BatchResponse, manager: &mut TaskManager) -> Result<(), WsClientError> {
-    let (min_response_id, max_response_id) = response_id_range_of(&responses)?;
-    // use the min id of batch request for managing task
-    match manager.request_status(&min_response_id) {
-        RequestStatus::PendingBatchMethodCall => {
-            log::debug!(
-                "[backend] Handle batch response of batch request: id=({}~{})",
-                min_response_id,
-                max_response_id
-            );
-            let send_back = manager
-                .complete_pending_batch_method_call(min_response_id)
-                .ok_or(WsClientError::InvalidRequestId)?;
-            send_back.send(Ok(responses)).expect("Send batch response back");
-            Ok(())
+/// Connects the websocket server with async-std runtime.
+#[cfg(feature = "ws-async-std")]
+async fn connect_async_std(
+    request: HandShakeRequest,
+    proxy: Option<&Proxy>,
+) -> Result<(WsMsgSender, WsMsgReceiver, HandShakeResponse), WsError> {
+    use async_tungstenite::async_std::{client_async_tls, connect_async};
+
+    let (ws_stream, response) = match proxy {
+        Some(proxy) => {
+            let mut stream = async_std::net::TcpStream::connect(proxy.addr()).await?;
+            proxy.tunnel(&mut stream, request.uri()).await?;
+            client_async_tls(request, stream).await?
         }
-        RequestStatus::PendingMethodCall
-        | RequestStatus::PendingSubscription
-        | RequestStatus::ActiveSubscription
-        | RequestStatus::PendingUnsubscribe
-        | RequestStatus::Invalid => Err(WsClientError::InvalidRequestId),
-    }
+        None => connect_async(request).await?,
+    };
+    let (sink, stream) = ws_stream.split();
+    Ok((Box::pin(sink), Box::pin(stream), response))
 }
 
-fn response_id_range_of(responses: &[Response]) -> Result<(u64, u64), WsClientError> {
-    assert!(!responses.is_empty());
-    let (mut min, mut max) = (u64::MAX, u64::MIN);
-    for response in responses {
-        let id = *response
-            .id()
-            .ok_or(WsClientError::InvalidRequestId)?
-            .as_number()
-            .expect("Response ID must be number");
-        min = std::cmp::min(id, min);
-        max = std::cmp::max(id, max);
-    }
-    Ok((min, max))
+/// Returns the subprotocols listed in the `Sec-WebSocket-Protocol` headers.
+fn subprotocols_of(headers: &header::HeaderMap) -> Vec<String> {
+    headers
+        .get_all(header::SEC_WEBSOCKET_PROTOCOL)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(|protocol| protocol.trim().to_string())
+        .filter(|protocol| !protocol.is_empty())
+        .collect()
 }
 
-fn handle_subscription_notification_message(notification: SubscriptionNotification, manager: &mut TaskManager) {
-    let subscription_id = notification.params.subscription.clone();
-    let request_id = match manager.get_request_id_by(&subscription_id) {
-        Some(id) => id,
-        None => {
+/// Validates the subprotocol selected by the server, which must be one of the requested subprotocols.
+pub(crate) fn negotiated_subprotocol(
+    requested: &[String],
+    response: HandShakeResponse,
+) -> Result<Option<String>, WsError> {
+    let selected = subprotocols_of(response.headers());
+    match selected.as_slice() {
+        [] => Ok(None),
+        [protocol] if requested.contains(protocol) => Ok(Some(protocol.clone())),
+        _ => {
             log::error!(
-                "[backend] Task manager cannot find subscription: id={:?}",
-                subscription_id
+                "WebSocket handshake: server selected subprotocol {:?}, but requested {:?}",
+                selected,
+                requested
             );
-            return;
-        }
-    };
-    match manager.as_active_subscription_mut(&request_id) {
-        Some(send_back) => {
-            if let Err(err) = send_back.try_send(notification) {
-                log::error!("[backend] Dropping subscription: id={:?}: {}", subscription_id, err);
-                manager
-                    .remove_active_subscription(request_id, subscription_id)
-                    .expect("kind is ActiveSubscription; qed");
-            }
+            let (parts, _) = response.into_parts();
+            Err(WsError::Http(http::Response::from_parts(parts, None)))
         }
-        None => log::error!(
-            "[backend] Subscription id ({:?}) is not an active subscription",
-            subscription_id
-        ),
     }
 }
@@ -2,4 +2,8 @@
 
 #![deny(missing_docs)]
 
-// TODO
+mod router;
+
+pub use jsonrpc_types::v2::*;
+
+pub use self::router::{Router, Subscriber, SubscriberClosed};
@@ -0,0 +1,280 @@
+use std::{
+    collections::HashMap,
+    fmt,
+    future::Future,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+};
+
+use futures::{
+    channel::mpsc,
+    future::{self, BoxFuture, FutureExt},
+};
+use jsonrpc_types::v2::*;
+
+type MethodHandler = Arc<dyn Fn(Option<Params>) -> BoxFuture<'static, Result<Value, Error>> + Send + Sync>;
+type SubscribeHandler = Arc<dyn Fn(Option<Params>, Subscriber) -> BoxFuture<'static, Result<(), Error>> + Send + Sync>;
+type NotificationSender = mpsc::UnboundedSender<SubscriptionNotification>;
+
+#[derive(Clone)]
+enum Method {
+    Call(MethodHandler),
+    Subscribe {
+        notification_method: String,
+        handler: SubscribeHandler,
+    },
+    Unsubscribe,
+}
+
+/// A router dispatching the JSON-RPC method calls and subscriptions to the registered handlers.
+///
+/// The router is independent of any transport, the methods are called with the parsed parameters,
+/// and the subscription notifications are sent to the channel given by the transport.
+#[derive(Clone, Default)]
+pub struct Router {
+    methods: HashMap<String, Method>,
+    /// Active subscriptions, which are closed by the unsubscribe method.
+    subscriptions: Arc<Mutex<HashMap<Id, NotificationSender>>>,
+    next_subscription_id: Arc<AtomicU64>,
+}
+
+impl fmt::Debug for Router {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Router")
+            .field("methods", &self.methods.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+impl Router {
+    /// Creates a new router without any methods.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers a method handler, which is called with the parameters of the method call.
+    pub fn method<F, Fut>(mut self, name: impl Into<String>, handler: F) -> Self
+    where
+        F: Fn(Option<Params>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<Value, Error>> + Send + 'static,
+    {
+        let handler = Arc::new(move |params| handler(params).boxed());
+        self.methods.insert(name.into(), Method::Call(handler));
+        self
+    }
+
+    /// Registers a subscription with its subscribe and unsubscribe methods.
+    ///
+    /// The handler is called with the parameters of the subscribe method call and a `Subscriber`,
+    /// which sends the notifications (with the `notification_method`) to the client, until the
+    /// subscription is closed by the unsubscribe method or the client.
+    pub fn subscription<F, Fut>(
+        mut self,
+        notification_method: impl Into<String>,
+        subscribe_method: impl Into<String>,
+        unsubscribe_method: impl Into<String>,
+        handler: F,
+    ) -> Self
+    where
+        F: Fn(Option<Params>, Subscriber) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<(), Error>> + Send + 'static,
+    {
+        let handler = Arc::new(move |params, subscriber| handler(params, subscriber).boxed());
+        self.methods.insert(
+            subscribe_method.into(),
+            Method::Subscribe {
+                notification_method: notification_method.into(),
+                handler,
+            },
+        );
+        self.methods.insert(unsubscribe_method.into(), Method::Unsubscribe);
+        self
+    }
+
+    /// Handles a method call, which returns the result of the method.
+    ///
+    /// The subscribe methods cannot be called here, since there is no channel for the notifications,
+    /// use `subscribe` instead.
+    pub async fn call(&self, method: &str, params: Option<Params>) -> Result<Value, Error> {
+        match self.methods.get(method) {
+            Some(Method::Call(handler)) => handler(params).await,
+            Some(Method::Unsubscribe) => {
+                let id = subscription_id_of(params)?;
+                Ok(Value::Bool(self.unsubscribe(&id)))
+            }
+            Some(Method::Subscribe { .. }) | None => Err(Error::method_not_found()),
+        }
+    }
+
+    /// Handles a request, which returns the response of the request.
+    pub async fn handle_request(&self, request: Request) -> Response {
+        log::debug!("[router] Handle request: {}", request);
+        match self.call(&request.method, request.params).await {
+            Ok(result) => Response::success(result, request.id),
+            Err(err) => Response::failure(err, Some(request.id)),
+        }
+    }
+
+    /// Handles a batch of requests concurrently, which returns the responses in the order of requests.
+    pub async fn handle_batch_request(&self, batch: BatchRequest) -> BatchResponse {
+        future::join_all(batch.into_iter().map(|request| self.handle_request(request))).await
+    }
+
+    /// Handles a subscribe method call, which returns the subscription id.
+    ///
+    /// The notifications of the subscription are sent to `sink`.
+    pub async fn subscribe(&self, method: &str, params: Option<Params>, sink: NotificationSender) -> Result<Id, Error> {
+        let (notification_method, handler) = match self.methods.get(method) {
+            Some(Method::Subscribe {
+                notification_method,
+                handler,
+            }) => (notification_method.clone(), handler.clone()),
+            _ => return Err(Error::method_not_found()),
+        };
+        let id = Id::Num(self.next_subscription_id.fetch_add(1, Ordering::Relaxed));
+        log::debug!("[router] Subscribe: method={}, id={}", method, id);
+        self.subscriptions
+            .lock()
+            .expect("lock subscriptions")
+            .insert(id.clone(), sink.clone());
+        let subscriber = Subscriber {
+            id: id.clone(),
+            method: notification_method,
+            sink,
+        };
+        match handler(params, subscriber).await {
+            Ok(()) => Ok(id),
+            Err(err) => {
+                self.subscriptions.lock().expect("lock subscriptions").remove(&id);
+                Err(err)
+            }
+        }
+    }
+
+    /// Closes the subscription, which returns `false` if the subscription doesn't exist.
+    pub fn unsubscribe(&self, id: &Id) -> bool {
+        log::debug!("[router] Unsubscribe: id={}", id);
+        match self.subscriptions.lock().expect("lock subscriptions").remove(id) {
+            Some(sink) => {
+                sink.close_channel();
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+fn subscription_id_of(params: Option<Params>) -> Result<Id, Error> {
+    let id = match params {
+        Some(Params::Array(mut params)) if params.len() == 1 => params.remove(0),
+        _ => return Err(Error::invalid_params("expected one parameter: subscription id")),
+    };
+    serde_json::from_value(id).map_err(Error::invalid_params)
+}
+
+/// The sender of the notifications of a subscription.
+#[derive(Clone, Debug)]
+pub struct Subscriber {
+    id: Id,
+    method: String,
+    sink: NotificationSender,
+}
+
+/// The error returned by `Subscriber::notify` when the subscription is closed.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct SubscriberClosed;
+
+impl fmt::Display for SubscriberClosed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("subscription is closed")
+    }
+}
+
+impl std::error::Error for SubscriberClosed {}
+
+impl Subscriber {
+    /// Returns the subscription id.
+    pub fn id(&self) -> &Id {
+        &self.id
+    }
+
+    /// Returns `true` if the subscription is closed by the unsubscribe method or the client.
+    pub fn is_closed(&self) -> bool {
+        self.sink.is_closed()
+    }
+
+    /// Sends a notification with the `result` to the client.
+    pub fn notify(&self, result: Value) -> Result<(), SubscriberClosed> {
+        let params = SubscriptionNotificationParams::new(self.id.clone(), result);
+        self.sink
+            .unbounded_send(SubscriptionNotification::new(self.method.clone(), params))
+            .map_err(|_| SubscriberClosed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::{executor::block_on, stream::StreamExt};
+
+    use super::*;
+
+    fn router() -> Router {
+        Router::new()
+            .method("add", |params| async move {
+                match params {
+                    Some(Params::Array(params)) => Ok(params.iter().filter_map(Value::as_u64).sum::<u64>().into()),
+                    _ => Err(Error::invalid_params("expected an array")),
+                }
+            })
+            .subscription("notify", "subscribe", "unsubscribe", |_, subscriber| async move {
+                subscriber.notify(Value::from("x")).unwrap();
+                Ok(())
+            })
+    }
+
+    #[test]
+    fn handle_requests() {
+        let router = router();
+        let params = Some(Params::Array(vec![1.into(), 2.into()]));
+        let response = block_on(router.handle_request(Request::new("add", params, 1.into())));
+        assert_eq!(response, Response::success(3.into(), 1.into()));
+
+        let batch = vec![
+            Request::new("add", None, 2.into()),
+            Request::new("foo", None, 3.into()),
+            Request::new("subscribe", None, 4.into()),
+        ];
+        let responses = block_on(router.handle_batch_request(batch));
+        assert_eq!(
+            responses,
+            vec![
+                Response::failure(Error::invalid_params("expected an array"), Some(2.into())),
+                Response::failure(Error::method_not_found(), Some(3.into())),
+                Response::failure(Error::method_not_found(), Some(4.into())),
+            ]
+        );
+    }
+
+    #[test]
+    fn handle_subscriptions() {
+        let router = router();
+        let (tx, mut rx) = mpsc::unbounded();
+        let id = block_on(router.subscribe("subscribe", None, tx)).unwrap();
+        let notification = block_on(rx.next()).unwrap();
+        assert_eq!(notification.method, "notify");
+        assert_eq!(
+            notification.params,
+            SubscriptionNotificationParams::new(id.clone(), "x".into())
+        );
+
+        let params = Some(Params::Array(vec![serde_json::to_value(&id).unwrap()]));
+        assert_eq!(
+            block_on(router.call("unsubscribe", params.clone())),
+            Ok(Value::Bool(true))
+        );
+        assert_eq!(block_on(rx.next()), None);
+        assert_eq!(block_on(router.call("unsubscribe", params)), Ok(Value::Bool(false)));
+    }
+}